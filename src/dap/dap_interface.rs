@@ -1,545 +1,2825 @@
 use crate::dap::message::{
-    BreakpointEvent, BreakpointEventReason, ContinueArguments, DapEvent, NextArguments,
-    OutputEvent, ProtocolMessage, RequestMessage, ResponseMessage, SetBreakpointsArguments,
-    StackTraceArguments,
+    BreakpointEvent, BreakpointEventReason, CapabilitiesEvent, CompletionsArguments,
+    CompletionsResponseBody, ContinueArguments, ContinueResponseBody, DapEvent,
+    DataBreakpointInfoArguments, DataBreakpointInfoResponseBody, DisassembleArguments,
+    DisassembleResponseBody, DisconnectArguments, EvaluateArguments, EvaluateResponseBody,
+    ExceptionInfoArguments, ExceptionInfoResponseBody, NextArguments, OutputEvent, PauseArguments,
+    ProtocolMessage, ReadMemoryArguments, ReadMemoryResponseBody, RequestMessage, ResponseMessage,
+    RestartArguments, RestartFrameArguments, ReverseContinueArguments, RunInTerminalArguments,
+    RunInTerminalResponseBody, ScopesArguments, ScopesResponseBody, SetBreakpointsArguments,
+    SetBreakpointsResponseBody, SetDataBreakpointsArguments, SetDataBreakpointsResponseBody,
+    SetExceptionBreakpointsArguments, SetExceptionBreakpointsResponseBody, SetExpressionArguments,
+    SetExpressionResponseBody, SetFunctionBreakpointsArguments, SetFunctionBreakpointsResponseBody,
+    SetInstructionBreakpointsArguments, SetInstructionBreakpointsResponseBody,
+    SetVariableArguments, SetVariableResponseBody, StackTraceArguments, StackTraceResponseBody,
+    StartDebuggingArguments, StepBackArguments, StepInArguments, StepInTargetsArguments,
+    StepInTargetsResponseBody, StepOutArguments, StoppedEvent, TerminateArguments, TerminatedEvent,
+    ThreadEvent, ThreadEventReason, ThreadsResponseBody, VariablesArguments, VariablesResponseBody,
+    WriteMemoryArguments, WriteMemoryResponseBody,
 };
 use crate::dap::message_types::{
-    self, OutputEventCategory, SteppingGranularity, StoppedEventReason,
+    self, Capabilities, EvaluateContext, ExceptionBreakpointsFilter, ExceptionFilterOptions,
+    OutputEventCategory, OutputGroup, RunInTerminalKind, SteppingGranularity, StoppedEventReason,
+    Variable, VariablePresentationHintAttribute,
 };
-use crate::dap::requests::RequestId;
-use crate::dap::{DapError, DapInstance};
-use crate::data::breakpoints::{Breakpoint, BreakpointStore, CodeBreakpoint};
+use crate::dap::requests::{
+    FromResponse, RequestHandle, RequestId, ResponseCallback, ignore_response, on_response,
+};
+use crate::dap::transport::{DapConnection, DapTransport};
+use crate::dap::{ADAPTER_EXIT_TIMEOUT, DapError, DapInstance};
+use crate::data::breakpoints::{
+    Breakpoint, BreakpointStore, CodeBreakpoint, DataBreakpoint, ExceptionBreakpoint,
+    FunctionBreakpoint, InstructionBreakpoint,
+};
+use crate::data::launch::{self, LaunchRequest};
+use crate::data::memory::{MEMORY_CHUNK_SIZE, MemoryByte, MemoryStore};
+use crate::data::output::{OutputLine, OutputStore};
+use crate::data::pty::{self, Pty, TerminalCommand};
+use crate::data::stack::{CallStack, FIRST_STACK_PAGE_SIZE, STACK_PAGE_SIZE, StackStore};
+use crate::data::terminal::{Terminal, TerminalStore};
+use crate::data::threads::{ThreadInfo, ThreadStore};
+use crate::data::types::DebugPointer;
+use crate::data::variables::{ScopesState, VariableStore, VariablesState};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
 use std::sync::{Arc, Mutex, RwLock};
 
-type ProtectedOption<T> = Arc<RwLock<Option<T>>>;
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(0);
+
+/// What a terminal the debugger runs a command in is called, unless it says.
+const DEFAULT_TERMINAL_TITLE: &str = "Debuggee";
 
 pub enum ContinueMode {
     AllThreads,
     SingleThread(u64),
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StepKind {
+    Over,
+    /// Into the function called on the current line. If there are several, `target_id` picks
+    /// one of the targets from [DebugSession::request_step_in_targets].
+    In {
+        target_id: Option<u64>,
+    },
+    Out,
+    /// Backwards, undoing the last step. See [DebugSession::supports_step_back].
+    Back,
+}
+
+/// Something a data breakpoint can watch.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DataBreakpointTarget {
+    /// The child `name` of the variable container `variables_reference`
+    Variable {
+        variables_reference: u64,
+        name: String,
+    },
+    /// A range of memory
+    Address { address: DebugPointer, bytes: u64 },
+}
+
 #[derive(Clone, Default, Debug)]
 pub enum DebugState {
     #[default]
     NotInitialized,
     Ready,
     Running,
-    Paused,
+    /// Stopped because we asked it to pause
+    Paused {
+        thread_id: Option<u64>,
+        file: Option<PathBuf>,
+        lineno: Option<usize>,
+    },
     Stopped {
         thread_id: Option<u64>,
         breakpoint: Option<Breakpoint>,
         file: Option<PathBuf>,
         lineno: Option<usize>,
-        /// The seq id of the request for "stackFrames" as a result of a "Step" breakpoint
-        /// This can be used to update breakpoint information by the stackFrame data
-        step_stackframe_request_seq: Option<u64>,
     },
+    /// Looking at a core dump. It can be inspected like a stopped program, but it can't run.
+    PostMortem {
+        thread_id: Option<u64>,
+        file: Option<PathBuf>,
+        lineno: Option<usize>,
+    },
+}
+
+impl DebugState {
+    /// Whether the debuggee is stopped in a way that lets us inspect it.
+    pub fn is_stopped(&self) -> bool {
+        matches!(
+            self,
+            DebugState::Stopped { .. } | DebugState::Paused { .. } | DebugState::PostMortem { .. }
+        )
+    }
+
+    /// Whether it's a core dump, which can't be run or changed.
+    pub fn is_post_mortem(&self) -> bool {
+        matches!(self, DebugState::PostMortem { .. })
+    }
+
+    /// The thread that stopped, if it's stopped.
+    pub fn thread_id(&self) -> Option<u64> {
+        match self {
+            DebugState::Stopped { thread_id, .. }
+            | DebugState::Paused { thread_id, .. }
+            | DebugState::PostMortem { thread_id, .. } => *thread_id,
+            _ => None,
+        }
+    }
+
+    /// Where it's stopped as far as we know, if it's stopped.
+    pub fn location(&self) -> Option<(PathBuf, usize)> {
+        match self {
+            DebugState::Stopped {
+                file: Some(file),
+                lineno: Some(lineno),
+                ..
+            }
+            | DebugState::Paused {
+                file: Some(file),
+                lineno: Some(lineno),
+                ..
+            }
+            | DebugState::PostMortem {
+                file: Some(file),
+                lineno: Some(lineno),
+                ..
+            } => Some((file.clone(), *lineno)),
+            _ => None,
+        }
+    }
+
+    /// Changes what [Self::thread_id] and [Self::location] return. Does nothing if it's not
+    /// stopped.
+    fn set_stop(
+        &mut self,
+        new_thread_id: Option<u64>,
+        new_file: Option<PathBuf>,
+        new_lineno: Option<usize>,
+    ) {
+        if let DebugState::Stopped {
+            thread_id,
+            file,
+            lineno,
+            ..
+        }
+        | DebugState::Paused {
+            thread_id,
+            file,
+            lineno,
+        }
+        | DebugState::PostMortem {
+            thread_id,
+            file,
+            lineno,
+        } = self
+        {
+            *thread_id = new_thread_id;
+            *file = new_file;
+            *lineno = new_lineno;
+        }
+    }
 }
 
+/// Debugs several programs at once, like a client and its server, each in a session of its own.
+/// Most of what's here is about the current session, the one the views show and the controls
+/// act on. The breakpoints are the project's, every session gets them.
 pub struct DapInterface {
-    instance: ProtectedOption<DapInstance>,
-    breakpoints: BreakpointStore,
+    /// In the order they started. Those that ended are kept until the next one starts, to show
+    /// how they ended.
+    sessions: RwLock<Vec<Arc<DebugSession>>>,
+    /// One of [Self::sessions], or a session that never connects until the first one starts
+    current_session: RwLock<Arc<DebugSession>>,
+    /// The breakpoints the user set. Each session has a copy of its own, along with what its
+    /// debugger says about them.
+    breakpoints: Arc<BreakpointStore>,
+    output: Arc<OutputStore>,
+    stepping_granularity: Arc<Mutex<SteppingGranularity>>,
+    /// The terminals the debuggees run in, see [Self::run_in_terminal] and
+    /// [Self::open_terminal]
+    terminals: TerminalStore,
+    /// The debuggers being connected to, see [Self::start_dap]
+    connecting: Mutex<Vec<PendingConnection>>,
+    /// Why the last session failed to start, see [Self::take_start_error]
+    start_error: Mutex<Option<String>>,
+}
+
+/// What's done with a session once its debugger is connected to, see [DapInterface::start_dap].
+type OnConnected = Box<dyn FnOnce(&DebugSession) -> Result<(), DapError> + Send>;
+
+/// A debugger being connected to in the background. Spawning an adapter and waiting for it to
+/// listen takes a while, the UI can't wait on it.
+struct PendingConnection {
+    name: String,
+    parent_id: Option<u64>,
+    /// The terminal opened for the debuggee, closed if the session fails to start
+    terminal_id: Option<u64>,
+    transport: DapTransport,
+    receiver: Receiver<Result<DapConnection, DapError>>,
+    on_connected: OnConnected,
+}
+
+/// A connection to a debugger, and what we know about the debuggee through it.
+pub struct DebugSession {
+    id: u64,
+    /// What the session switcher calls it
+    name: String,
+    /// The session that asked for this one to be started, see
+    /// [DapInterface::start_child_session]
+    parent_id: Option<u64>,
+    /// None once the session ends, see [Self::end_session]
+    instance: RwLock<Option<DapInstance>>,
+    breakpoints: Arc<BreakpointStore>,
+    /// The breakpoints of the whole project, see [DapInterface::put_breakpoint]
+    project_breakpoints: Arc<BreakpointStore>,
+    memory: MemoryStore,
+    variables: VariableStore,
     debug_state: Mutex<DebugState>,
+    stack: StackStore,
+    threads: ThreadStore,
+    /// Shared by every session
+    output: Arc<OutputStore>,
+    /// Shared by every session
+    stepping_granularity: Arc<Mutex<SteppingGranularity>>,
+    /// The exception a thread stopped at, along with that thread
+    exception: Mutex<Option<(Option<u64>, ExceptionInfoResponseBody)>>,
+    /// The frame variables and evaluations are looked up in
+    selected_frame_id: Mutex<Option<u64>>,
+    /// Whether the session is a core dump, see [DebugState::PostMortem]
+    post_mortem: AtomicBool,
+    /// What the debuggee exited with, once it did
+    exit_code: Mutex<Option<i64>>,
+    /// Why the debugger failed to launch or attach, see [Self::on_launched]
+    launch_error: Mutex<Option<String>>,
+    /// The terminal opened for the debuggee to run in, closed if it fails to launch
+    terminal_id: Option<u64>,
+    /// Whether the debuggee was asked to terminate, see [Self::terminate]
+    terminating: AtomicBool,
+    /// What the debugger wants passed back when it asked for the session to be restarted, see
+    /// [Self::take_restart_request]
+    restart_requested: Mutex<Option<serde_json::Value>>,
+    /// How the session was launched, to launch it again. Core dumps and child sessions have
+    /// none.
+    launch_arguments: Mutex<Option<(LaunchRequest, serde_json::Value)>>,
 }
 
-impl DapInterface {
-    pub fn new() -> Self {
+impl DebugSession {
+    fn new(
+        name: &str,
+        parent_id: Option<u64>,
+        terminal_id: Option<u64>,
+        instance: Option<DapInstance>,
+        project_breakpoints: Arc<BreakpointStore>,
+        output: Arc<OutputStore>,
+        stepping_granularity: Arc<Mutex<SteppingGranularity>>,
+    ) -> Self {
+        // Without a debugger there's no breakpoint data of its own, the project's are shown
+        let breakpoints = if instance.is_some() {
+            Arc::new(project_breakpoints.copy_breakpoints())
+        } else {
+            Arc::clone(&project_breakpoints)
+        };
+
         Self {
-            instance: Default::default(),
-            breakpoints: BreakpointStore::new(),
+            id: NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed),
+            name: name.to_owned(),
+            parent_id,
+            instance: RwLock::new(instance),
+            breakpoints,
+            project_breakpoints,
+            memory: MemoryStore::new(),
+            variables: VariableStore::new(),
             debug_state: Mutex::new(DebugState::NotInitialized),
+            stack: StackStore::new(),
+            threads: ThreadStore::new(),
+            output,
+            stepping_granularity,
+            exception: Mutex::new(None),
+            selected_frame_id: Mutex::new(None),
+            post_mortem: AtomicBool::new(false),
+            exit_code: Mutex::new(None),
+            launch_error: Mutex::new(None),
+            terminal_id,
+            terminating: AtomicBool::new(false),
+            restart_requested: Mutex::new(None),
+            launch_arguments: Mutex::new(None),
         }
     }
 
-    pub fn start_dap<TArgs, TArgStr>(
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// What the session switcher calls it, the name of its launch configuration.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Initializes the debugger, then sends it a launch or attach request with `arguments` as its
+    /// body.
+    pub fn launch(
         &self,
-        filepath: impl AsRef<Path>,
-        options: TArgs,
-    ) -> Result<(), DapError>
-    where
-        TArgs: IntoIterator<Item = TArgStr>,
-        TArgStr: AsRef<str>,
-    {
-        let instance = DapInstance::instance(filepath, options)?;
-        let mut w_dap = self.instance.write().unwrap();
-        tracy_client::Client::start().message("load_target_instance_w", 0);
+        request: LaunchRequest,
+        arguments: serde_json::Value,
+    ) -> Result<(), DapError> {
+        self.post_mortem.store(false, Ordering::Release);
+        *self.launch_arguments.lock().unwrap() = Some((request, arguments.clone()));
+        let adapter_id = launch::adapter_id(&arguments).to_owned();
+        self.initialize(&adapter_id, Some((request, arguments)))
+    }
 
-        *w_dap = Some(instance);
-        Ok(())
+    /// Initializes the debugger to look at a core dump, see [DebugState::PostMortem]. The core
+    /// is loaded by the launch or attach request with `arguments` as its body, or by the
+    /// debugger itself if there's no request.
+    pub fn open_core_dump(
+        &self,
+        request: Option<LaunchRequest>,
+        arguments: serde_json::Value,
+    ) -> Result<(), DapError> {
+        self.post_mortem.store(true, Ordering::Release);
+        let adapter_id = launch::adapter_id(&arguments).to_owned();
+        self.initialize(&adapter_id, request.map(|request| (request, arguments)))
     }
 
-    pub fn launch(&self, launch_json: impl AsRef<str>) -> Result<(), DapError> {
+    fn initialize(
+        &self,
+        adapter_id: &str,
+        launch_request: Option<(LaunchRequest, serde_json::Value)>,
+    ) -> Result<(), DapError> {
         let mut w_dap = self.instance.write().unwrap();
         tracy_client::Client::start().message("launch_instance_w", 0);
         if let Some(w_dap) = &mut *w_dap {
-            w_dap.launch(launch_json.as_ref())?;
+            w_dap.launch(
+                adapter_id,
+                launch_request,
+                on_response(Self::on_initialized),
+            )?;
 
             let mut debug_state = self.debug_state.lock().unwrap();
             *debug_state = DebugState::NotInitialized;
         } else {
             return Err(DapError::NoLoadedTarget);
         }
+        *self.exit_code.lock().unwrap() = None;
+        *self.launch_error.lock().unwrap() = None;
+        *self.restart_requested.lock().unwrap() = None;
         Ok(())
     }
 
-    pub fn process_dap_events(&self) -> Result<(), DapError> {
-        let mut configuration_done = false;
+    /// Whether there's a debugger to talk to.
+    pub fn is_connected(&self) -> bool {
+        self.instance.read().unwrap().is_some()
+    }
 
-        {
-            let mut instance_w = self.instance.write().unwrap();
-            tracy_client::Client::start().message("process_dap_events_instance_w", 0);
-
-            if let Some(dap_instance) = &mut *instance_w {
-                while let Some(msg) = dap_instance.poll_message() {
-                    log::trace!("Received message: {msg:?}");
-                    match msg {
-                        ProtocolMessage::Response(ResponseMessage::Initialize {
-                            success,
-                            body,
-                            ..
-                        }) => {
-                            if success {
-                                configuration_done = true;
-                                if let Some(cap) = &body {
-                                    dap_instance.set_capabilities(*cap);
-                                }
-
-                                let mut debug_state = self.debug_state.lock().unwrap();
-                                *debug_state = DebugState::Ready;
-                            } else {
-                                log::error!("Failed to initialize DAP");
-                            }
-                        }
-                        ProtocolMessage::Response(ResponseMessage::SetBreakpoints {
-                            success,
-                            body,
-                            ..
-                        }) => {
-                            if success {
-                                for breakpoint in body.breakpoints.iter().cloned() {
-                                    log::debug!("Confirming addition of breakpoint {breakpoint:?}");
-                                    self.breakpoints.add_breakpoint_data(breakpoint);
-                                }
-                            } else {
-                                log::error!("Failed to set breakpoints to DAP")
-                            }
-                        }
-                        ProtocolMessage::Response(ResponseMessage::StackTrace {
-                            success,
-                            body,
-                            request_seq,
-                            ..
-                        }) => {
-                            if success {
-                                if let Some(frame) = body.stack_frames.first() {
-                                    let mut state = self.debug_state.lock().unwrap();
-
-                                    // We check to see if we were stopped and if we had a pending
-                                    // request for the stack frame in order to complete the stop
-                                    // state information. In that case, we use the top frame in
-                                    // order to feed file and line number information about the
-                                    // stopped status.
-                                    if let DebugState::Stopped {
-                                        thread_id,
-                                        breakpoint,
-                                        step_stackframe_request_seq,
-                                        ..
-                                    } = &*state
-                                        && step_stackframe_request_seq
-                                            .map(|seq| seq == request_seq)
-                                            .unwrap_or(false)
-                                    {
-                                        *state = DebugState::Stopped {
-                                            thread_id: *thread_id,
-                                            breakpoint: breakpoint.clone(),
-                                            file: frame
-                                                .source
-                                                .as_ref()
-                                                .and_then(|source| source.path.as_ref())
-                                                .map(PathBuf::from),
-                                            lineno: if frame.line > 0 {
-                                                Some(frame.line)
-                                            } else {
-                                                None
-                                            },
-                                            step_stackframe_request_seq: None,
-                                        };
-                                    }
-                                }
-
-                                for frame in body.stack_frames.iter() {
-                                    log::debug!("Received stack frame: {frame:?}");
-                                }
-                            } else {
-                                log::error!("Failed to query stack frames from DAP");
-                            }
-                        }
-                        ProtocolMessage::Event(DapEvent::Output {
-                            body:
-                                OutputEvent {
-                                    category: Some(category),
-                                    output,
-                                },
-                            ..
-                        }) => match category {
-                            OutputEventCategory::Stdout => {
-                                print!("{output}");
-                            }
-                            OutputEventCategory::Stderr => {
-                                eprint!("{output}");
-                            }
-                            _ => {
-                                log::info!("OutputEvent ({category:?}) says: {output}");
-                            }
-                        },
-                        ProtocolMessage::Event(DapEvent::Breakpoint {
-                            body:
-                                BreakpointEvent {
-                                    reason: BreakpointEventReason::New,
-                                    breakpoint,
-                                },
-                            ..
-                        }) => {
-                            log::debug!("Confirming addition of breakpoint {breakpoint:?}");
-                            self.breakpoints.add_breakpoint_data(breakpoint);
-                        }
-                        ProtocolMessage::Event(DapEvent::Breakpoint {
-                            body:
-                                BreakpointEvent {
-                                    reason: BreakpointEventReason::Changed,
-                                    breakpoint,
-                                },
-                            ..
-                        }) => {
-                            log::debug!("Breakpoint updated {breakpoint:?}");
-                            self.breakpoints.update_breakpoint_data(breakpoint);
-                        }
-                        ProtocolMessage::Event(DapEvent::Breakpoint {
-                            body:
-                                BreakpointEvent {
-                                    reason: BreakpointEventReason::Removed,
-                                    breakpoint: message_types::Breakpoint { id: Some(id), .. },
-                                },
-                            ..
-                        }) => {
-                            log::debug!("Breakpoint of id {id} removed");
-                            self.breakpoints.delete_breakpoint_data(id);
-                        }
-                        ProtocolMessage::Event(DapEvent::Stopped { body, .. }) => {
-                            let stack_trace_req_seq_id = if let Some(thread_id) = body.thread_id {
-                                let seq_id = dap_instance.next_seq();
-                                let msg = ProtocolMessage::Request(RequestMessage::StackTrace {
-                                    seq: seq_id,
-                                    arguments: StackTraceArguments {
-                                        thread_id,
-                                        // start_frame: Some(0),
-                                        // TODO: this should be configured somewhere by the
-                                        // user
-                                        levels: Some(2),
-                                        ..Default::default()
-                                    },
-                                });
-
-                                dap_instance.send_message(&msg)?;
-
-                                Some(seq_id)
-                            } else {
-                                log::warn!("Stopped at some unknown thread");
-
-                                None
-                            };
-
-                            match body.reason {
-                                StoppedEventReason::Breakpoint
-                                | StoppedEventReason::FunctionBreakpoint => {
-                                    let hit_breakpoint = body
-                                        .hit_breakpoint_ids
-                                        .and_then(|list| list.first().copied());
-                                    let breakpoint = hit_breakpoint.and_then(|b| {
-                                        self.breakpoints.get_breakpoint_for_dap_id(b)
-                                    });
-
-                                    let mut debug_state = self.debug_state.lock().unwrap();
-                                    match &breakpoint {
-                                        Some(Breakpoint::Source(CodeBreakpoint {
-                                            file,
-                                            lineno,
-                                            ..
-                                        })) => {
-                                            let file = file.as_ref().clone();
-                                            let lineno = *lineno;
-
-                                            *debug_state = DebugState::Stopped {
-                                                thread_id: body.thread_id,
-                                                breakpoint,
-                                                file: Some(file),
-                                                lineno: Some(lineno),
-                                                step_stackframe_request_seq: None,
-                                            };
-                                        }
-                                        None => {}
-                                    }
-                                }
-                                StoppedEventReason::Step => {
-                                    let mut debug_state = self.debug_state.lock().unwrap();
-                                    let state = &*debug_state;
-                                    match state {
-                                        // If it was stopped before, let's not try to change
-                                        // anything besides the thread id, wait for the stack
-                                        // frames to arrive to then make further updates
-                                        DebugState::Stopped {
-                                            breakpoint,
-                                            file,
-                                            lineno,
-                                            ..
-                                        } => {
-                                            *debug_state = DebugState::Stopped {
-                                                thread_id: body.thread_id,
-                                                breakpoint: breakpoint.clone(),
-                                                file: file.clone(),
-                                                lineno: *lineno,
-                                                step_stackframe_request_seq: stack_trace_req_seq_id,
-                                            };
-                                        }
-                                        // If the state is not Stopped, then we need to set it to
-                                        // stopped anyway, even if we don't know what caused it
-                                        _ => {
-                                            *debug_state = DebugState::Stopped {
-                                                thread_id: body.thread_id,
-                                                breakpoint: None,
-                                                file: None,
-                                                lineno: None,
-                                                step_stackframe_request_seq: stack_trace_req_seq_id,
-                                            };
-                                        }
-                                    }
-                                }
-                                _ => {
-                                    log::warn!("Stopped for some unknown reason");
-                                    let mut debug_state = self.debug_state.lock().unwrap();
-                                    let state = &*debug_state;
-                                    match state {
-                                        // If it was stopped before, let's not try to change
-                                        // anything besides the thread id
-                                        DebugState::Stopped {
-                                            breakpoint,
-                                            file,
-                                            lineno,
-                                            ..
-                                        } => {
-                                            *debug_state = DebugState::Stopped {
-                                                thread_id: body.thread_id,
-                                                breakpoint: breakpoint.clone(),
-                                                file: file.clone(),
-                                                lineno: *lineno,
-                                                step_stackframe_request_seq: None,
-                                            };
-                                        }
-                                        // If the state is not Stopped, then we need to set it to
-                                        // stopped anyway, even if we don't know what caused it
-                                        _ => {
-                                            *debug_state = DebugState::Stopped {
-                                                thread_id: body.thread_id,
-                                                breakpoint: None,
-                                                file: None,
-                                                lineno: None,
-                                                step_stackframe_request_seq: None,
-                                            };
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        ProtocolMessage::Event(DapEvent::Terminated { .. }) => {}
-                        _ => {}
-                    }
-                }
-            }
-        }
+    /// What the debuggee exited with, if it did. It's kept after the session ends, until the
+    /// next one starts.
+    pub fn exit_code(&self) -> Option<i64> {
+        *self.exit_code.lock().unwrap()
+    }
 
-        if configuration_done {
-            self.update_all_breakpoints()?;
+    /// Why the debugger could not start the debuggee, or attach to it, if it failed to.
+    pub fn launch_error(&self) -> Option<String> {
+        self.launch_error.lock().unwrap().clone()
+    }
 
-            {
-                let mut instance_w = self.instance.write().unwrap();
-                if let Some(dap_interface) = instance_w.as_mut() {
-                    if let Err(e) = dap_interface.flush_pending_launch_requests() {
-                        log::error!("Error while flushing pending launch request: {e}");
-                    }
+    /// Whether the session is looking at a core dump.
+    pub fn is_post_mortem(&self) -> bool {
+        self.post_mortem.load(Ordering::Acquire)
+    }
 
-                    let seq = dap_interface.next_seq();
+    /// Ends the session, leaving the debuggee running. Once the debugger answers, or fails to,
+    /// the connection to it is closed.
+    pub fn detach(&self) -> Result<(), DapError> {
+        self.disconnect(Some(false))
+    }
 
-                    dap_interface.send_message(&ProtocolMessage::Request(
-                        RequestMessage::ConfigurationDone {
-                            seq,
-                            arguments: Some(serde_json::json!({})),
-                        },
-                    ))?;
-                }
-            }
-        }
+    /// Ends the session. Whether the debuggee is terminated is up to `terminate_debuggee`, or to
+    /// the debugger if it's None. Once the debugger answers, or fails to, the connection to it
+    /// is closed.
+    fn disconnect(&self, terminate_debuggee: Option<bool>) -> Result<(), DapError> {
+        self.send_request(
+            |seq| RequestMessage::Disconnect {
+                seq,
+                arguments: DisconnectArguments {
+                    terminate_debuggee,
+                    ..Default::default()
+                },
+            },
+            Box::new(|session, result| {
+                session.end_session();
+                result.map(|_| ())
+            }),
+        )?;
 
         Ok(())
     }
 
-    pub fn update_all_breakpoints(&self) -> Result<(), DapError> {
-        let mut files = Vec::new();
-        self.breakpoints.get_files(&mut files);
-
-        for file in &files {
-            self.update_breakpoints_for_file(file)?
+    /// Stops debugging, terminating the debuggee. If the debugger can, the debuggee is first
+    /// asked to terminate by itself, and the session ends once it's gone. Asking again, or the
+    /// debugger refusing, terminates it forcefully.
+    pub fn terminate(&self) -> Result<(), DapError> {
+        if !self.supports_terminate_request() || self.terminating.swap(true, Ordering::AcqRel) {
+            return self.disconnect(Some(true));
         }
 
+        self.send_request(
+            |seq| RequestMessage::Terminate {
+                seq,
+                arguments: TerminateArguments::default(),
+            },
+            on_response(|session: &DebugSession, result: Result<(), _>| {
+                if let Err(e) = result {
+                    log::warn!("{e}, terminating forcefully");
+                    session.disconnect(Some(true))?;
+                }
+                Ok(())
+            }),
+        )?;
+
         Ok(())
     }
 
-    fn update_breakpoints_for_file(&self, file: &Path) -> Result<(), DapError> {
-        let mut instance_w = self.instance.write().unwrap();
-        tracy_client::Client::start().message("update_breakpoints_for_file_instance_w", 0);
-        if let Some(instance) = instance_w.as_mut() {
-            let mut list = Vec::new();
-            self.breakpoints.get_file_breakpoints(file, &mut list);
-            let source = message_types::Source {
-                path: Some(file.to_string_lossy().into()),
-                ..Default::default()
-            };
-            let breakpoints = list
-                .iter()
-                .map(|bp| match bp {
-                    Breakpoint::Source(b) => b,
-                })
-                .map(|bp| message_types::SourceBreakpoint {
-                    line: bp.lineno,
-                    ..Default::default()
-                })
-                .collect();
+    /// Restarts the session in place, with `arguments` as the new launch or attach arguments.
+    /// See [Self::supports_restart_request], otherwise the session has to be ended and started
+    /// again.
+    pub fn restart(&self, arguments: serde_json::Value) -> Result<(), DapError> {
+        if !self.supports_restart_request() {
+            return Err(DapError::Unsupported("restarting"));
+        }
 
-            let seq = instance.next_seq();
-            let msg = ProtocolMessage::Request(RequestMessage::SetBreakpoints {
+        self.send_request(
+            |seq| RequestMessage::Restart {
                 seq,
-                arguments: SetBreakpointsArguments {
-                    source,
-                    breakpoints: Some(breakpoints),
-                    ..Default::default()
+                arguments: RestartArguments {
+                    arguments: Some(arguments),
                 },
-            });
-
-            instance.send_message(&msg)?;
-        }
+            },
+            on_response(|session: &DebugSession, result: Result<(), _>| {
+                result?;
+                session.on_restarted();
+                session.request_threads()
+            }),
+        )?;
 
         Ok(())
     }
 
-    pub fn get_files_with_breakpoints(&self, out: &mut Vec<PathBuf>) {
-        self.breakpoints.get_files(out);
+    /// The debuggee runs again from the start, nothing we knew about it holds anymore.
+    fn on_restarted(&self) {
+        self.forget_debuggee();
+        *self.exit_code.lock().unwrap() = None;
+        self.terminating.store(false, Ordering::Release);
+        *self.debug_state.lock().unwrap() = DebugState::Running;
     }
 
-    pub fn get_file_breakpoints(&self, file: impl AsRef<Path>, out: &mut Vec<Breakpoint>) {
-        self.breakpoints.get_file_breakpoints(file, out);
+    /// Whether the debugger asked for the session to be restarted when it ended. If so, returns
+    /// what goes in the `__restart` entry of the arguments of the new session. It's only
+    /// reported once.
+    pub fn take_restart_request(&self) -> Option<serde_json::Value> {
+        self.restart_requested.lock().unwrap().take()
     }
 
-    pub fn put_breakpoint(&self, breakpoint: Breakpoint) -> Result<(), DapError> {
-        self.breakpoints.add(breakpoint.clone());
-        match breakpoint {
-            Breakpoint::Source(code_bp) => self.update_breakpoints_for_file(code_bp.file.as_ref()),
-        }
+    /// Drops the connection to the debugger, and everything we knew about the debuggee.
+    fn end_session(&self) {
+        *self.instance.write().unwrap() = None;
+        *self.debug_state.lock().unwrap() = DebugState::NotInitialized;
+        self.forget_debuggee();
+        self.post_mortem.store(false, Ordering::Release);
+        self.terminating.store(false, Ordering::Release);
     }
 
-    pub fn remove_breakpoint(&self, breakpoint: &Breakpoint) -> Result<(), DapError> {
-        self.breakpoints.remove(breakpoint);
-        match breakpoint {
-            Breakpoint::Source(code_bp) => self.update_breakpoints_for_file(code_bp.file.as_ref()),
-        }
+    /// Drops everything we knew about the debuggee.
+    fn forget_debuggee(&self) {
+        self.threads.set_threads(Vec::new());
+        self.stack.reset(None);
+        self.memory.invalidate();
+        self.variables.invalidate();
+        *self.selected_frame_id.lock().unwrap() = None;
+        *self.exception.lock().unwrap() = None;
     }
 
-    pub fn request_next(&self) -> Result<(), DapError> {
-        let thread_id = {
-            let debug_state = self.get_debug_state();
-            if let DebugState::Stopped { thread_id, .. } = debug_state {
-                thread_id.unwrap_or(0)
-            } else {
-                0
+    fn on_initialized(&self, result: Result<Capabilities, DapError>) -> Result<(), DapError> {
+        let capabilities = result?;
+        if let Some(instance) = self.instance.write().unwrap().as_mut() {
+            instance.set_capabilities(capabilities);
+        }
+        *self.debug_state.lock().unwrap() = if self.is_post_mortem() {
+            DebugState::PostMortem {
+                thread_id: None,
+                file: None,
+                lineno: None,
             }
+        } else {
+            DebugState::Ready
         };
 
+        // Exception breakpoints are sent along with the rest before configurationDone, so the
+        // program can't panic before they are set. The project remembers the filters too, so
+        // one the user turned off stays off in the sessions started after this one.
+        let filters = self.exception_filters();
+        self.project_breakpoints
+            .add_default_exception_breakpoints(&filters);
+        self.breakpoints.add_default_exception_breakpoints(&filters);
+        self.update_all_breakpoints()?;
+
         let mut instance_w = self.instance.write().unwrap();
-        if let Some(instance) = instance_w.as_mut() {
-            let seq = instance.next_seq();
+        if let Some(dap_instance) = instance_w.as_mut() {
+            if let Err(e) =
+                dap_instance.flush_pending_launch_requests(on_response(Self::on_launched))
+            {
+                log::error!("Error while flushing pending launch request: {e}");
+            }
 
-            // If step single thread is supported, we'll use it
-            let single_thread = instance
+            if dap_instance
                 .get_capabilities()
-                .supports_single_thread_execution_requests;
+                .supports_configuration_done_request
+                .unwrap_or(false)
+            {
+                dap_instance.send_request(
+                    |seq| RequestMessage::ConfigurationDone {
+                        seq,
+                        arguments: Some(serde_json::json!({})),
+                    },
+                    ignore_response(),
+                )?;
+            }
+        }
+        drop(instance_w);
 
-            instance.send_message(&ProtocolMessage::Request(RequestMessage::Next {
-                seq,
-                arguments: NextArguments {
-                    thread_id,
-                    single_thread,
-                    stepping_granularity: Some(SteppingGranularity::Line),
-                },
-            }))
+        if self.is_post_mortem() {
+            self.request_post_mortem_threads()
         } else {
-            Err(DapError::NoDapInstance)
+            self.request_threads()
         }
     }
 
-    pub fn request_continue(&self, mode: ContinueMode) -> Result<(), DapError> {
-        let (thread_id, single_thread) = match mode {
-            ContinueMode::AllThreads => (0, false),
-            ContinueMode::SingleThread(thread_id) => (thread_id, true),
+    /// A debugger that failed to launch or attach has nothing to debug. Why is kept to be shown,
+    /// and the session is ended.
+    fn on_launched(&self, result: Result<(), DapError>) -> Result<(), DapError> {
+        let Err(e) = result else {
+            return Ok(());
         };
 
-        let mut instance_w = self.instance.write().unwrap();
-        if let Some(instance) = instance_w.as_mut() {
-            let seq = instance.next_seq();
+        log::error!("{e}");
+        *self.launch_error.lock().unwrap() = Some(e.to_string());
+        *self.debug_state.lock().unwrap() = DebugState::NotInitialized;
+        self.disconnect(None)
+    }
 
-            instance.send_message(&ProtocolMessage::Request(RequestMessage::Continue {
-                seq,
-                arguments: ContinueArguments {
-                    thread_id,
-                    single_thread: Some(single_thread),
+    /// Every thread of a core dump is stopped, and not every debugger sends a stopped event for
+    /// them. So once they are known, the first one is shown, unless a stopped event already
+    /// picked one.
+    fn request_post_mortem_threads(&self) -> Result<(), DapError> {
+        self.send_request(
+            |seq| RequestMessage::Threads { seq },
+            on_response(
+                |session: &DebugSession, result: Result<ThreadsResponseBody, _>| {
+                    let threads = result?.threads;
+                    let first_thread = threads.first().map(|thread| thread.id);
+                    session.threads.set_stopped(None, true, None);
+                    session.threads.set_threads(threads);
+
+                    match (session.get_debug_state().thread_id(), first_thread) {
+                        (None, Some(thread_id)) => session.select_thread(thread_id),
+                        _ => Ok(()),
+                    }
                 },
-            }))
+            ),
+        )?;
+
+        Ok(())
+    }
+
+    /// Takes the messages the debugger sent since the last call.
+    fn poll_messages(&self) -> Vec<ProtocolMessage> {
+        let mut messages = Vec::new();
+        let mut instance_w = self.instance.write().unwrap();
+        tracy_client::Client::start().message("process_dap_events_instance_w", 0);
+
+        if let Some(dap_instance) = &mut *instance_w {
+            while let Some(msg) = dap_instance.poll_message() {
+                messages.push(msg);
+            }
+        }
+
+        messages
+    }
+
+    /// Runs the callback of the request `response` answers.
+    fn complete_request(&self, response: ResponseMessage) {
+        let completion = self
+            .instance
+            .write()
+            .unwrap()
+            .as_mut()
+            .map(|dap_instance| dap_instance.complete_request(response));
+        match completion {
+            Some(Ok((callback, result))) => self.run_callback(callback, result),
+            Some(Err(response)) => {
+                log::warn!("Received response to no pending request: {response:?}")
+            }
+            None => log::debug!("Received response from a closed session"),
+        }
+    }
+
+    /// Gives up on the requests that took too long, and ends the session if the debug adapter
+    /// quit.
+    fn poll_adapter(&self) {
+        let mut timed_out = Vec::new();
+        if let Some(dap_instance) = self.instance.write().unwrap().as_mut() {
+            dap_instance.expire_requests(&mut timed_out);
+        }
+        for (callback, result) in timed_out {
+            self.run_callback(callback, result);
+        }
+
+        let adapter_exit = match self.instance.write().unwrap().as_mut() {
+            Some(dap_instance) => dap_instance
+                .poll_adapter_exit()
+                .map(|exit| (exit, dap_instance.is_disconnecting())),
+            None => None,
+        };
+        if let Some((exit, expected)) = adapter_exit {
+            if expected {
+                log::info!("The debug adapter of {} {exit}", self.name);
+            } else {
+                let message = format!("The debug adapter of {} {exit} unexpectedly", self.name);
+                log::error!("{message}");
+                self.output.push(
+                    OutputEventCategory::Important,
+                    &format!("{message}\n"),
+                    None,
+                );
+            }
+            self.end_session();
+        }
+    }
+
+    /// Whether we asked the debugger to end the session, or it's over already.
+    fn is_disconnecting(&self) -> bool {
+        self.instance
+            .read()
+            .unwrap()
+            .as_ref()
+            .is_none_or(DapInstance::is_disconnecting)
+    }
+
+    fn run_callback(&self, callback: ResponseCallback, result: Result<ResponseMessage, DapError>) {
+        if let Err(e) = callback(self, result) {
+            log::error!("{e}");
+        }
+    }
+
+    fn handle_event(&self, event: DapEvent) -> Result<(), DapError> {
+        match event {
+            DapEvent::Output {
+                body:
+                    OutputEvent {
+                        category,
+                        output,
+                        source,
+                        line,
+                        group,
+                        variables_reference,
+                    },
+                ..
+            } => {
+                let category = category.unwrap_or(OutputEventCategory::Console);
+                log::debug!("OutputEvent ({category:?}) says: {output}");
+                // Logpoints tell where they are
+                let location = source
+                    .and_then(|source| source.path)
+                    .zip(line)
+                    .map(|(path, line)| (PathBuf::from(path), line));
+                match (group, variables_reference.unwrap_or(0)) {
+                    (Some(OutputGroup::Start), _) => {
+                        self.output.start_group(category, &output, location, false);
+                    }
+                    (Some(OutputGroup::StartCollapsed), _) => {
+                        self.output.start_group(category, &output, location, true);
+                    }
+                    // The output of the end of a group goes after it
+                    (Some(OutputGroup::End), _) => {
+                        self.output.end_group();
+                        self.output.push(category, &output, location);
+                    }
+                    (None, 0) => self.output.push(category, &output, location),
+                    (None, variables_reference) => {
+                        self.output.push_structured(
+                            category,
+                            &output,
+                            variables_reference,
+                            location,
+                        );
+                    }
+                }
+            }
+            DapEvent::Breakpoint {
+                body:
+                    BreakpointEvent {
+                        reason: BreakpointEventReason::New,
+                        breakpoint,
+                    },
+                ..
+            } => {
+                log::debug!("Confirming addition of breakpoint {breakpoint:?}");
+                self.breakpoints.add_breakpoint_data(breakpoint);
+            }
+            DapEvent::Breakpoint {
+                body:
+                    BreakpointEvent {
+                        reason: BreakpointEventReason::Changed,
+                        breakpoint,
+                    },
+                ..
+            } => {
+                log::debug!("Breakpoint updated {breakpoint:?}");
+                self.breakpoints.update_breakpoint_data(breakpoint);
+            }
+            DapEvent::Breakpoint {
+                body:
+                    BreakpointEvent {
+                        reason: BreakpointEventReason::Removed,
+                        breakpoint: message_types::Breakpoint { id: Some(id), .. },
+                    },
+                ..
+            } => {
+                log::debug!("Breakpoint of id {id} removed");
+                self.breakpoints.delete_breakpoint_data(id);
+            }
+            DapEvent::Capabilities {
+                body: CapabilitiesEvent { capabilities },
+                ..
+            } => {
+                log::debug!("Capabilities changed: {capabilities:?}");
+                if let Some(dap_instance) = self.instance.write().unwrap().as_mut() {
+                    dap_instance.update_capabilities(&capabilities);
+                }
+            }
+            DapEvent::Stopped { body, .. } => self.on_stopped(body)?,
+            DapEvent::Continued { body, .. } => self.on_resumed(
+                self.threads.generation(),
+                Some(body.thread_id),
+                body.all_threads_continued.unwrap_or(false),
+            ),
+            DapEvent::Thread {
+                body:
+                    ThreadEvent {
+                        reason: ThreadEventReason::Started,
+                        thread_id,
+                    },
+                ..
+            } => {
+                self.threads.add(thread_id);
+                // For its name
+                self.request_threads()?;
+            }
+            DapEvent::Thread {
+                body:
+                    ThreadEvent {
+                        reason: ThreadEventReason::Exited,
+                        thread_id,
+                    },
+                ..
+            } => {
+                self.threads.remove(thread_id);
+                if self.stack.thread_id() == Some(thread_id) {
+                    self.stack.reset(None);
+                }
+            }
+            DapEvent::Exited { body, .. } => {
+                log::info!("The debuggee exited with code {}", body.exit_code);
+                *self.exit_code.lock().unwrap() = Some(body.exit_code);
+                self.output.push(
+                    OutputEventCategory::Console,
+                    &format!("Program exited with code {}\n", body.exit_code),
+                    None,
+                );
+            }
+            DapEvent::Terminated { body, .. } => {
+                *self.restart_requested.lock().unwrap() = body
+                    .and_then(|TerminatedEvent { restart }| restart)
+                    .filter(|restart| *restart != serde_json::Value::Bool(false));
+
+                // The debuggee is gone, the session is over too, unless we are ending it already
+                if !self.is_disconnecting() {
+                    self.disconnect(None)?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn on_stopped(&self, body: StoppedEvent) -> Result<(), DapError> {
+        // The program ran, whatever memory and variables we had are stale now
+        self.memory.invalidate();
+        self.variables.invalidate();
+        *self.selected_frame_id.lock().unwrap() = None;
+
+        self.threads.set_stopped(
+            body.thread_id,
+            body.all_threads_stopped.unwrap_or(false),
+            body.description.clone().or_else(|| body.text.clone()),
+        );
+
+        let breakpoint = match body.reason {
+            StoppedEventReason::Breakpoint
+            | StoppedEventReason::FunctionBreakpoint
+            | StoppedEventReason::DataBreakpoint
+            | StoppedEventReason::Exception
+            | StoppedEventReason::InstructionBreakpoint => body
+                .hit_breakpoint_ids
+                .as_ref()
+                .and_then(|list| list.first().copied())
+                .and_then(|b| self.breakpoints.get_breakpoint_for_dap_id(b)),
+            StoppedEventReason::Step | StoppedEventReason::Pause | StoppedEventReason::Restart => {
+                None
+            }
+            _ => {
+                log::warn!("Stopped for some unknown reason");
+                None
+            }
+        };
+
+        {
+            let mut debug_state = self.debug_state.lock().unwrap();
+            let (file, lineno) = match &breakpoint {
+                Some(Breakpoint::Source(CodeBreakpoint { file, lineno, .. })) => {
+                    (Some(file.as_ref().clone()), Some(*lineno))
+                }
+                // The debugger tells where it found the function or instruction
+                Some(bp @ (Breakpoint::Function(_) | Breakpoint::Instruction(_))) => self
+                    .breakpoints
+                    .get_breakpoint_data(bp.breakpoint_id())
+                    .and_then(|data| {
+                        let path = data.source?.path?;
+                        Some((PathBuf::from(path), data.line?))
+                    })
+                    .or_else(|| debug_state.location())
+                    .unzip(),
+                // Keep showing where it was stopped before until the stack trace tells us where
+                // it is now
+                Some(Breakpoint::Data(_) | Breakpoint::Exception(_)) | None => {
+                    debug_state.location().unzip()
+                }
+            };
+
+            *debug_state = if self.is_post_mortem() {
+                DebugState::PostMortem {
+                    thread_id: body.thread_id,
+                    file,
+                    lineno,
+                }
+            } else if body.reason == StoppedEventReason::Pause {
+                DebugState::Paused {
+                    thread_id: body.thread_id,
+                    file,
+                    lineno,
+                }
+            } else {
+                DebugState::Stopped {
+                    thread_id: body.thread_id,
+                    breakpoint,
+                    file,
+                    lineno,
+                }
+            };
+        }
+
+        if body.reason == StoppedEventReason::Exception {
+            self.on_exception(&body)?;
         } else {
-            Err(DapError::NoDapInstance)
+            *self.exception.lock().unwrap() = None;
         }
+
+        if body.thread_id.is_none() {
+            log::warn!("Stopped at some unknown thread");
+        }
+        self.stack.reset(body.thread_id);
+        self.request_stack_page()?;
+
+        // Threads may have come and gone while it ran
+        self.request_threads()
     }
 
-    pub fn request_variables(&self) -> Result<RequestId, DapError> {
-        let mut instance_w = self.instance.write().unwrap();
-        if let Some(instance) = instance_w.as_mut() {
-            let seq = instance.next_seq();
+    /// Keeps what the stopped event says about the exception, then asks the debugger for the
+    /// details if it can tell them.
+    fn on_exception(&self, body: &StoppedEvent) -> Result<(), DapError> {
+        let summary = ExceptionInfoResponseBody {
+            exception_id: body
+                .description
+                .clone()
+                .unwrap_or_else(|| "Exception".to_owned()),
+            description: body.text.clone(),
+            ..Default::default()
+        };
+        *self.exception.lock().unwrap() = Some((body.thread_id, summary));
 
-            // If step single thread is supported, we'll use it
-            let single_thread = instance
-                .get_capabilities()
-                .supports_single_thread_execution_requests;
+        let Some(thread_id) = body.thread_id else {
+            return Ok(());
+        };
+        if !self.supports_exception_info() {
+            return Ok(());
+        }
 
-            instance.send_message(&ProtocolMessage::Request(RequestMessage::Next {
+        let generation = self.threads.generation();
+        self.send_request(
+            |seq| RequestMessage::ExceptionInfo {
                 seq,
-                arguments: NextArguments {
-                    single_thread,
-                    stepping_granularity: Some(SteppingGranularity::Statement),
-                    ..Default::default()
+                arguments: ExceptionInfoArguments { thread_id },
+            },
+            on_response(
+                move |session: &DebugSession, result: Result<ExceptionInfoResponseBody, _>| {
+                    let info = result?;
+                    // It ran since, this is about some other exception
+                    if session.threads.generation() == generation {
+                        *session.exception.lock().unwrap() = Some((Some(thread_id), info));
+                    }
+                    Ok(())
                 },
-            }))?;
+            ),
+        )?;
 
-            Ok(RequestId::new(seq))
-        } else {
-            Err(DapError::NoDapInstance)
+        Ok(())
+    }
+
+    /// The exception the shown thread stopped at, if it stopped at one.
+    pub fn get_exception(&self) -> Option<ExceptionInfoResponseBody> {
+        let exception = self.exception.lock().unwrap();
+        match &*exception {
+            Some((thread_id, info)) if *thread_id == self.stack.thread_id() => Some(info.clone()),
+            _ => None,
         }
     }
 
-    pub fn get_debug_state(&self) -> DebugState {
-        self.debug_state.lock().unwrap().clone()
+    /// Marks threads as running, unless the program stopped again after `generation`. If the
+    /// thread being shown is one of them, its stack is gone until it stops again.
+    fn on_resumed(&self, generation: u64, thread_id: Option<u64>, all: bool) {
+        if !self.threads.set_running(generation, thread_id, all) {
+            return;
+        }
+
+        {
+            let mut exception = self.exception.lock().unwrap();
+            if exception
+                .as_ref()
+                .is_some_and(|(exception_thread, _)| all || *exception_thread == thread_id)
+            {
+                *exception = None;
+            }
+        }
+
+        let shown_thread_resumed = all || self.stack.thread_id() == thread_id;
+        if shown_thread_resumed {
+            self.stack.reset(None);
+            self.variables.invalidate();
+            *self.selected_frame_id.lock().unwrap() = None;
+        }
+
+        let mut debug_state = self.debug_state.lock().unwrap();
+        if !self.threads.any_stopped() {
+            self.memory.invalidate();
+            *debug_state = DebugState::Running;
+        } else if shown_thread_resumed {
+            debug_state.set_stop(None, None, None);
+        }
     }
-}
-impl Default for DapInterface {
-    fn default() -> Self {
-        Self::new()
+
+    fn request_threads(&self) -> Result<(), DapError> {
+        self.send_request(
+            |seq| RequestMessage::Threads { seq },
+            on_response(
+                |session: &DebugSession, result: Result<ThreadsResponseBody, _>| {
+                    session.threads.set_threads(result?.threads);
+                    Ok(())
+                },
+            ),
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_threads(&self) -> Vec<ThreadInfo> {
+        self.threads.get()
+    }
+
+    /// The thread whose stack is shown, and which is stepped and continued by default.
+    pub fn selected_thread_id(&self) -> Option<u64> {
+        self.stack.thread_id()
+    }
+
+    /// Shows the stack of the given thread, which should be stopped, and makes it the one
+    /// stepped and continued by default.
+    pub fn select_thread(&self, thread_id: u64) -> Result<(), DapError> {
+        self.variables.invalidate();
+        *self.selected_frame_id.lock().unwrap() = None;
+        {
+            let mut debug_state = self.debug_state.lock().unwrap();
+            let (file, lineno) = debug_state.location().unzip();
+            debug_state.set_stop(Some(thread_id), file, lineno);
+        }
+
+        self.stack.reset(Some(thread_id));
+        self.request_stack_page()
+    }
+
+    /// Fetches the next page of frames of the stack, if there is any left and none is being
+    /// fetched already.
+    fn request_stack_page(&self) -> Result<(), DapError> {
+        let Some((generation, thread_id, start_frame)) = self.stack.set_loading() else {
+            return Ok(());
+        };
+        let levels = if start_frame == 0 {
+            FIRST_STACK_PAGE_SIZE
+        } else {
+            STACK_PAGE_SIZE
+        };
+
+        self.send_request(
+            |seq| RequestMessage::StackTrace {
+                seq,
+                arguments: StackTraceArguments {
+                    thread_id,
+                    start_frame: Some(start_frame as u64),
+                    levels: Some(levels),
+                    ..Default::default()
+                },
+            },
+            on_response(move |session: &DebugSession, result| {
+                session.on_stack_page(generation, start_frame, levels, result)
+            }),
+        )
+        .inspect_err(|e| {
+            self.stack.fail(generation, e.to_string());
+        })?;
+
+        Ok(())
+    }
+
+    fn on_stack_page(
+        &self,
+        generation: u64,
+        start_frame: usize,
+        levels: u64,
+        result: Result<StackTraceResponseBody, DapError>,
+    ) -> Result<(), DapError> {
+        let body = result.inspect_err(|e| {
+            self.stack.fail(generation, e.to_string());
+        })?;
+        let top_frame = body
+            .stack_frames
+            .first()
+            .filter(|_| start_frame == 0)
+            .cloned();
+
+        if !self
+            .stack
+            .add_page(generation, body.stack_frames, levels, body.total_frames)
+        {
+            // It ran again since
+            return Ok(());
+        }
+
+        // The top frame tells where it stopped, and is the one selected at first
+        if let Some(frame) = top_frame {
+            if let Some(path) = frame
+                .source
+                .as_ref()
+                .and_then(|source| source.path.as_ref())
+            {
+                let mut debug_state = self.debug_state.lock().unwrap();
+                let thread_id = debug_state.thread_id();
+                let lineno = (frame.line > 0).then_some(frame.line);
+                debug_state.set_stop(thread_id, Some(PathBuf::from(path)), lineno);
+            }
+
+            self.select_frame(frame.id)?;
+        }
+
+        if self.stack.wants_more() {
+            self.request_stack_page()?;
+        }
+
+        Ok(())
+    }
+
+    /// Fetches the frames past [crate::data::stack::MAX_AUTO_LOADED_FRAMES], which are not fetched on their own.
+    pub fn load_more_frames(&self) -> Result<(), DapError> {
+        self.request_stack_page()
+    }
+
+    pub fn get_call_stack(&self) -> CallStack {
+        self.stack.get()
+    }
+
+    /// Makes variables and evaluations be looked up in the given frame.
+    pub fn select_frame(&self, frame_id: u64) -> Result<(), DapError> {
+        *self.selected_frame_id.lock().unwrap() = Some(frame_id);
+        self.send_scopes_request(frame_id)
+    }
+
+    /// The source location of the selected frame. Until the stack arrives, it's where the
+    /// program stopped as far as we know.
+    pub fn get_current_location(&self) -> Option<(PathBuf, usize)> {
+        if let Some(frame) = self.selected_frame_id().and_then(|id| self.stack.frame(id)) {
+            let path = frame.source.and_then(|source| source.path)?;
+            return (frame.line > 0).then(|| (PathBuf::from(path), frame.line));
+        }
+
+        self.get_debug_state().location()
+    }
+
+    /// Sends a request through the current instance, see [DapInstance::send_request].
+    fn send_request(
+        &self,
+        request: impl FnOnce(u64) -> RequestMessage,
+        on_response: ResponseCallback,
+    ) -> Result<RequestId, DapError> {
+        let mut instance_w = self.instance.write().unwrap();
+        let instance = instance_w.as_mut().ok_or(DapError::NoDapInstance)?;
+
+        instance.send_request(request, on_response)
+    }
+
+    /// Sends a request whose typed response is picked up through the returned handle.
+    fn send_awaited_request<T>(
+        &self,
+        request: impl FnOnce(u64) -> RequestMessage,
+    ) -> Result<RequestHandle<T>, DapError>
+    where
+        T: FromResponse + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let request_id = self.send_request(
+            request,
+            on_response(move |_, result| {
+                // The handle may have been dropped already, nothing to do then
+                let _ = sender.send(result);
+                Ok(())
+            }),
+        )?;
+
+        Ok(RequestHandle::new(request_id, receiver))
+    }
+
+    /// Looks at what the debugger can do, without copying it. Until there is a debugger, it
+    /// can't do anything.
+    fn with_capabilities<T>(&self, f: impl FnOnce(&Capabilities) -> T) -> T {
+        let instance_r = self.instance.read().unwrap();
+        match instance_r.as_ref() {
+            Some(instance) => f(instance.get_capabilities()),
+            None => f(&Capabilities::default()),
+        }
+    }
+
+    /// Whether the debugger says it supports what `flag` picks out of its capabilities.
+    fn supports(&self, flag: impl FnOnce(&Capabilities) -> Option<bool>) -> bool {
+        self.with_capabilities(flag).unwrap_or(false)
+    }
+
+    pub fn update_all_breakpoints(&self) -> Result<(), DapError> {
+        let mut files = Vec::new();
+        self.breakpoints.get_files(&mut files);
+
+        for file in &files {
+            self.update_breakpoints_for_file(file)?
+        }
+
+        if self.supports_function_breakpoints() {
+            self.update_function_breakpoints()?;
+        }
+
+        if self.supports_data_breakpoints() {
+            self.update_data_breakpoints()?;
+        }
+
+        if !self.exception_filters().is_empty() {
+            self.update_exception_breakpoints()?;
+        }
+
+        if self.supports_instruction_breakpoints() {
+            self.update_instruction_breakpoints()?;
+        }
+
+        Ok(())
+    }
+
+    /// Sends every instruction breakpoint to the debugger, which replaces the ones it had.
+    fn update_instruction_breakpoints(&self) -> Result<(), DapError> {
+        if self.instance.read().unwrap().is_none() {
+            return Ok(());
+        }
+
+        let mut list = Vec::new();
+        self.breakpoints.get_instruction_breakpoints(&mut list);
+        let addresses: Vec<DebugPointer> = list.iter().map(|bp| bp.address).collect();
+        let breakpoints = addresses
+            .iter()
+            .map(|address| message_types::InstructionBreakpoint {
+                instruction_reference: *address,
+                ..Default::default()
+            })
+            .collect();
+
+        self.send_request(
+            |seq| RequestMessage::SetInstructionBreakpoints {
+                seq,
+                arguments: SetInstructionBreakpointsArguments { breakpoints },
+            },
+            on_response(
+                move |session: &DebugSession,
+                      result: Result<SetInstructionBreakpointsResponseBody, _>| {
+                    let breakpoints = result?.breakpoints;
+                    log::debug!("Confirming instruction breakpoints {breakpoints:?}");
+                    session
+                        .breakpoints
+                        .set_instruction_breakpoint_data(&addresses, breakpoints);
+                    Ok(())
+                },
+            ),
+        )?;
+
+        Ok(())
+    }
+
+    /// Sends the exception filters that are enabled to the debugger, which replaces the ones it
+    /// had. Filters it does not offer, enabled in some other session, are left out.
+    fn update_exception_breakpoints(&self) -> Result<(), DapError> {
+        if self.instance.read().unwrap().is_none() {
+            return Ok(());
+        }
+
+        let filters = self.exception_filters();
+        let mut list = Vec::new();
+        self.breakpoints.get_exception_breakpoints(&mut list);
+        let list: Vec<(ExceptionBreakpoint, &ExceptionBreakpointsFilter)> = list
+            .into_iter()
+            .filter_map(|bp| {
+                let filter = filters
+                    .iter()
+                    .find(|filter| filter.filter == bp.filter_id)?;
+                Some((bp, filter))
+            })
+            .collect();
+        let filter_ids: Vec<String> = list.iter().map(|(bp, _)| bp.filter_id.clone()).collect();
+
+        let arguments = if self.supports_exception_filter_options() {
+            let filter_options = list
+                .iter()
+                .map(|(bp, filter)| ExceptionFilterOptions {
+                    filter_id: bp.filter_id.clone(),
+                    condition: bp
+                        .condition
+                        .clone()
+                        .filter(|_| filter.supports_condition.unwrap_or(false)),
+                })
+                .collect();
+            SetExceptionBreakpointsArguments {
+                filters: Vec::new(),
+                filter_options: Some(filter_options),
+            }
+        } else {
+            SetExceptionBreakpointsArguments {
+                filters: filter_ids.clone(),
+                filter_options: None,
+            }
+        };
+
+        self.send_request(
+            |seq| RequestMessage::SetExceptionBreakpoints { seq, arguments },
+            on_response(
+                move |session: &DebugSession,
+                      result: Result<SetExceptionBreakpointsResponseBody, _>| {
+                    let breakpoints = result?.breakpoints.unwrap_or_default();
+                    log::debug!("Confirming exception breakpoints {breakpoints:?}");
+                    session
+                        .breakpoints
+                        .set_exception_breakpoint_data(&filter_ids, breakpoints);
+                    Ok(())
+                },
+            ),
+        )?;
+
+        Ok(())
+    }
+
+    /// Sends every data breakpoint to the debugger, which replaces the ones it had.
+    fn update_data_breakpoints(&self) -> Result<(), DapError> {
+        if self.instance.read().unwrap().is_none() {
+            return Ok(());
+        }
+
+        let mut list = Vec::new();
+        self.breakpoints.get_data_breakpoints(&mut list);
+        let data_ids: Vec<String> = list.iter().map(|bp| bp.data_id.clone()).collect();
+        let breakpoints = list
+            .iter()
+            .map(|bp| message_types::DataBreakpoint {
+                data_id: bp.data_id.clone(),
+                access_type: Some(bp.access_type),
+                ..Default::default()
+            })
+            .collect();
+
+        self.send_request(
+            |seq| RequestMessage::SetDataBreakpoints {
+                seq,
+                arguments: SetDataBreakpointsArguments { breakpoints },
+            },
+            on_response(
+                move |session: &DebugSession, result: Result<SetDataBreakpointsResponseBody, _>| {
+                    let breakpoints = result?.breakpoints;
+                    log::debug!("Confirming data breakpoints {breakpoints:?}");
+                    session
+                        .breakpoints
+                        .set_data_breakpoint_data(&data_ids, breakpoints);
+                    Ok(())
+                },
+            ),
+        )?;
+
+        Ok(())
+    }
+
+    /// Asks the debugger whether `target` can be watched, and for the data id to watch it with.
+    pub fn request_data_breakpoint_info(
+        &self,
+        target: &DataBreakpointTarget,
+    ) -> Result<RequestHandle<DataBreakpointInfoResponseBody>, DapError> {
+        let arguments = match target {
+            DataBreakpointTarget::Variable {
+                variables_reference,
+                name,
+            } => DataBreakpointInfoArguments {
+                variables_reference: Some(*variables_reference),
+                name: name.clone(),
+                ..Default::default()
+            },
+            DataBreakpointTarget::Address { address, bytes } => DataBreakpointInfoArguments {
+                name: address.to_string(),
+                bytes: Some(*bytes),
+                as_address: Some(true),
+                ..Default::default()
+            },
+        };
+
+        self.send_awaited_request(|seq| RequestMessage::DataBreakpointInfo { seq, arguments })
+    }
+
+    /// Sends every function breakpoint to the debugger, which replaces the ones it had.
+    fn update_function_breakpoints(&self) -> Result<(), DapError> {
+        if self.instance.read().unwrap().is_none() {
+            return Ok(());
+        }
+
+        let mut list = Vec::new();
+        self.breakpoints.get_function_breakpoints(&mut list);
+        let conditions =
+            self.supports(|capabilities| capabilities.supports_conditional_breakpoints);
+        let hit_conditions =
+            self.supports(|capabilities| capabilities.supports_hit_conditional_breakpoints);
+        let supported =
+            |supported: bool, value: &Option<String>| value.clone().filter(|_| supported);
+        let names: Vec<String> = list.iter().map(|bp| bp.name.clone()).collect();
+        let breakpoints = list
+            .iter()
+            .map(|bp| message_types::FunctionBreakpoint {
+                name: bp.name.clone(),
+                condition: supported(conditions, &bp.condition),
+                hit_condition: supported(hit_conditions, &bp.hit_condition),
+            })
+            .collect();
+
+        self.send_request(
+            |seq| RequestMessage::SetFunctionBreakpoints {
+                seq,
+                arguments: SetFunctionBreakpointsArguments { breakpoints },
+            },
+            on_response(
+                move |session: &DebugSession,
+                      result: Result<SetFunctionBreakpointsResponseBody, _>| {
+                    let breakpoints = result?.breakpoints;
+                    log::debug!("Confirming function breakpoints {breakpoints:?}");
+                    session
+                        .breakpoints
+                        .set_function_breakpoint_data(&names, breakpoints);
+                    Ok(())
+                },
+            ),
+        )?;
+
+        Ok(())
+    }
+
+    fn update_breakpoints_for_file(&self, file: &Path) -> Result<(), DapError> {
+        if self.instance.read().unwrap().is_none() {
+            return Ok(());
+        }
+
+        let mut list = Vec::new();
+        self.breakpoints.get_file_breakpoints(file, &mut list);
+        // Attributes the debugger does not support are left out, rather than having the whole
+        // request fail
+        let conditions =
+            self.supports(|capabilities| capabilities.supports_conditional_breakpoints);
+        let hit_conditions =
+            self.supports(|capabilities| capabilities.supports_hit_conditional_breakpoints);
+        let log_points = self.supports(|capabilities| capabilities.supports_log_points);
+        let supported =
+            |supported: bool, value: &Option<String>| value.clone().filter(|_| supported);
+        let source = message_types::Source {
+            path: Some(file.to_string_lossy().into()),
+            ..Default::default()
+        };
+        let breakpoints = list
+            .iter()
+            .filter_map(|bp| match bp {
+                Breakpoint::Source(b) => Some(b),
+                _ => None,
+            })
+            .map(|bp| message_types::SourceBreakpoint {
+                line: bp.lineno,
+                condition: supported(conditions, &bp.condition),
+                hit_condition: supported(hit_conditions, &bp.hit_condition),
+                log_message: supported(log_points, &bp.log_message),
+                ..Default::default()
+            })
+            .collect();
+
+        self.send_request(
+            |seq| RequestMessage::SetBreakpoints {
+                seq,
+                arguments: SetBreakpointsArguments {
+                    source,
+                    breakpoints: Some(breakpoints),
+                    ..Default::default()
+                },
+            },
+            on_response(
+                |session: &DebugSession, result: Result<SetBreakpointsResponseBody, _>| {
+                    for breakpoint in result?.breakpoints {
+                        log::debug!("Confirming addition of breakpoint {breakpoint:?}");
+                        session.breakpoints.add_breakpoint_data(breakpoint);
+                    }
+                    Ok(())
+                },
+            ),
+        )?;
+
+        Ok(())
+    }
+
+    pub fn get_files_with_breakpoints(&self, out: &mut Vec<PathBuf>) {
+        self.breakpoints.get_files(out);
+    }
+
+    pub fn get_file_breakpoints(&self, file: impl AsRef<Path>, out: &mut Vec<Breakpoint>) {
+        self.breakpoints.get_file_breakpoints(file, out);
+    }
+
+    pub fn get_function_breakpoints(&self, out: &mut Vec<FunctionBreakpoint>) {
+        self.breakpoints.get_function_breakpoints(out);
+    }
+
+    pub fn get_data_breakpoints(&self, out: &mut Vec<DataBreakpoint>) {
+        self.breakpoints.get_data_breakpoints(out);
+    }
+
+    pub fn get_exception_breakpoints(&self, out: &mut Vec<ExceptionBreakpoint>) {
+        self.breakpoints.get_exception_breakpoints(out);
+    }
+
+    pub fn get_instruction_breakpoints(&self, out: &mut Vec<InstructionBreakpoint>) {
+        self.breakpoints.get_instruction_breakpoints(out);
+    }
+
+    pub fn supports_instruction_breakpoints(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_instruction_breakpoints)
+    }
+
+    pub fn supports_disassemble(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_disassemble_request)
+    }
+
+    /// Disassembles `instruction_count` instructions, starting `instruction_offset` instructions
+    /// away from `address`.
+    pub fn request_disassemble(
+        &self,
+        address: DebugPointer,
+        instruction_offset: i64,
+        instruction_count: u64,
+    ) -> Result<RequestHandle<DisassembleResponseBody>, DapError> {
+        self.send_awaited_request(|seq| RequestMessage::Disassemble {
+            seq,
+            arguments: DisassembleArguments {
+                memory_reference: address,
+                offset: None,
+                instruction_offset: Some(instruction_offset),
+                instruction_count,
+                resolve_symbols: Some(true),
+            },
+        })
+    }
+
+    /// Where the selected frame is in the code, as an address.
+    pub fn get_instruction_pointer(&self) -> Option<DebugPointer> {
+        let frame = self.stack.frame(self.selected_frame_id()?)?;
+        frame.instruction_pointer_reference
+    }
+
+    /// The kinds of exceptions the debugger can break on, empty until it's initialized.
+    pub fn exception_filters(&self) -> Vec<ExceptionBreakpointsFilter> {
+        self.with_capabilities(|capabilities| {
+            capabilities
+                .exception_breakpoint_filters
+                .clone()
+                .unwrap_or_default()
+        })
+    }
+
+    /// Whether exception filters can have conditions.
+    pub fn supports_exception_filter_options(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_exception_filter_options)
+    }
+
+    pub fn supports_exception_info(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_exception_info_request)
+    }
+
+    /// What the debugger said about a breakpoint, like whether it could be set.
+    pub fn get_breakpoint_data(&self, breakpoint_id: u64) -> Option<message_types::Breakpoint> {
+        self.breakpoints.get_breakpoint_data(breakpoint_id)
+    }
+
+    pub fn supports_function_breakpoints(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_function_breakpoints)
+    }
+
+    pub fn supports_conditional_breakpoints(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_conditional_breakpoints)
+    }
+
+    pub fn supports_hit_conditional_breakpoints(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_hit_conditional_breakpoints)
+    }
+
+    pub fn supports_log_points(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_log_points)
+    }
+
+    pub fn supports_data_breakpoints(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_data_breakpoints)
+    }
+
+    /// Whether data breakpoints can be put on addresses, rather than only on variables.
+    pub fn supports_data_breakpoint_bytes(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_data_breakpoint_bytes)
+    }
+
+    /// Adds a breakpoint to the session's copy of the project breakpoints, see
+    /// [DapInterface::put_breakpoint].
+    fn put_breakpoint(&self, breakpoint: Breakpoint) -> Result<(), DapError> {
+        self.breakpoints.add(breakpoint.clone());
+        self.update_breakpoints_like(&breakpoint)
+    }
+
+    fn remove_breakpoint(&self, breakpoint: &Breakpoint) -> Result<(), DapError> {
+        self.breakpoints.remove(breakpoint);
+        self.update_breakpoints_like(breakpoint)
+    }
+
+    /// Whether the debugger can set a breakpoint set on the whole project. Data and instruction
+    /// breakpoints are never shared, see [DapInterface::put_breakpoint].
+    fn can_share_breakpoint(&self, breakpoint: &Breakpoint) -> bool {
+        match breakpoint {
+            Breakpoint::Source(_) => true,
+            Breakpoint::Function(_) => self.supports_function_breakpoints(),
+            Breakpoint::Exception(exception_bp) => self.with_capabilities(|capabilities| {
+                capabilities
+                    .exception_breakpoint_filters
+                    .iter()
+                    .flatten()
+                    .any(|filter| filter.filter == exception_bp.filter_id)
+            }),
+            Breakpoint::Data(_) | Breakpoint::Instruction(_) => false,
+        }
+    }
+
+    /// Sends the debugger the breakpoints that are set along with `breakpoint`, which is every
+    /// breakpoint of its file, or every function, data or exception breakpoint.
+    fn update_breakpoints_like(&self, breakpoint: &Breakpoint) -> Result<(), DapError> {
+        match breakpoint {
+            Breakpoint::Source(code_bp) => self.update_breakpoints_for_file(code_bp.file.as_ref()),
+            Breakpoint::Function(_) if self.supports_function_breakpoints() => {
+                self.update_function_breakpoints()
+            }
+            Breakpoint::Function(_) => Ok(()),
+            Breakpoint::Data(_) if self.supports_data_breakpoints() => {
+                self.update_data_breakpoints()
+            }
+            Breakpoint::Data(_) => Ok(()),
+            Breakpoint::Exception(_) => self.update_exception_breakpoints(),
+            Breakpoint::Instruction(_) if self.supports_instruction_breakpoints() => {
+                self.update_instruction_breakpoints()
+            }
+            Breakpoint::Instruction(_) => Ok(()),
+        }
+    }
+
+    /// The thread stepped and continued when none is given.
+    fn default_thread_id(&self) -> u64 {
+        self.selected_thread_id()
+            .or_else(|| self.get_debug_state().thread_id())
+            .or_else(|| self.threads.get().first().map(|thread| thread.id))
+            .unwrap_or(0)
+    }
+
+    pub fn supports_single_thread_execution(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_single_thread_execution_requests)
+    }
+
+    pub fn supports_stepping_granularity(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_stepping_granularity)
+    }
+
+    pub fn supports_step_in_targets(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_step_in_targets_request)
+    }
+
+    pub fn stepping_granularity(&self) -> SteppingGranularity {
+        *self.stepping_granularity.lock().unwrap()
+    }
+
+    /// Sets the granularity of every kind of step from now on, in every session.
+    pub fn set_stepping_granularity(&self, granularity: SteppingGranularity) {
+        *self.stepping_granularity.lock().unwrap() = granularity;
+    }
+
+    /// Steps `thread_id`, or the selected thread if None.
+    pub fn request_step(&self, kind: StepKind, thread_id: Option<u64>) -> Result<(), DapError> {
+        self.request_step_by(kind, thread_id, self.stepping_granularity())
+    }
+
+    /// Steps the selected thread by a single instruction, whatever the stepping granularity is.
+    pub fn request_instruction_step(&self, kind: StepKind) -> Result<(), DapError> {
+        self.request_step_by(kind, None, SteppingGranularity::Instruction)
+    }
+
+    fn request_step_by(
+        &self,
+        kind: StepKind,
+        thread_id: Option<u64>,
+        granularity: SteppingGranularity,
+    ) -> Result<(), DapError> {
+        let thread_id = thread_id.unwrap_or_else(|| self.default_thread_id());
+
+        // If step single thread is supported, we'll use it
+        let single_thread = self.with_capabilities(|capabilities| {
+            capabilities.supports_single_thread_execution_requests
+        });
+        let granularity = self
+            .supports(|capabilities| capabilities.supports_stepping_granularity)
+            .then_some(granularity);
+        let generation = self.threads.generation();
+
+        self.send_request(
+            |seq| match kind {
+                StepKind::Over => RequestMessage::Next {
+                    seq,
+                    arguments: NextArguments {
+                        thread_id,
+                        single_thread,
+                        stepping_granularity: granularity,
+                    },
+                },
+                StepKind::In { target_id } => RequestMessage::StepIn {
+                    seq,
+                    arguments: StepInArguments {
+                        thread_id,
+                        single_thread,
+                        target_id,
+                        granularity,
+                    },
+                },
+                StepKind::Out => RequestMessage::StepOut {
+                    seq,
+                    arguments: StepOutArguments {
+                        thread_id,
+                        single_thread,
+                        granularity,
+                    },
+                },
+                StepKind::Back => RequestMessage::StepBack {
+                    seq,
+                    arguments: StepBackArguments {
+                        thread_id,
+                        single_thread,
+                        granularity,
+                    },
+                },
+            },
+            on_response(move |session: &DebugSession, result: Result<(), _>| {
+                result?;
+                session.on_resumed(generation, Some(thread_id), single_thread != Some(true));
+                Ok(())
+            }),
+        )?;
+
+        Ok(())
+    }
+
+    pub fn request_continue(&self, mode: ContinueMode) -> Result<(), DapError> {
+        let (thread_id, single_thread) = match mode {
+            ContinueMode::AllThreads => (self.default_thread_id(), false),
+            ContinueMode::SingleThread(thread_id) => (thread_id, true),
+        };
+        let generation = self.threads.generation();
+
+        self.send_request(
+            |seq| RequestMessage::Continue {
+                seq,
+                arguments: ContinueArguments {
+                    thread_id,
+                    single_thread: Some(single_thread),
+                },
+            },
+            on_response(
+                move |session: &DebugSession, result: Result<ContinueResponseBody, _>| {
+                    let body = result?;
+                    let all = !single_thread || body.all_threads_continued.unwrap_or(true);
+                    session.on_resumed(generation, Some(thread_id), all);
+                    Ok(())
+                },
+            ),
+        )?;
+
+        Ok(())
+    }
+
+    /// Runs backwards until something stops it, see [Self::supports_step_back].
+    pub fn request_reverse_continue(&self, mode: ContinueMode) -> Result<(), DapError> {
+        let (thread_id, single_thread) = match mode {
+            ContinueMode::AllThreads => (self.default_thread_id(), false),
+            ContinueMode::SingleThread(thread_id) => (thread_id, true),
+        };
+        let generation = self.threads.generation();
+
+        self.send_request(
+            |seq| RequestMessage::ReverseContinue {
+                seq,
+                arguments: ReverseContinueArguments {
+                    thread_id,
+                    single_thread: Some(single_thread),
+                },
+            },
+            on_response(move |session: &DebugSession, result: Result<(), _>| {
+                result?;
+                session.on_resumed(generation, Some(thread_id), !single_thread);
+                Ok(())
+            }),
+        )?;
+
+        Ok(())
+    }
+
+    /// Runs the frame `frame_id` again from its start, see [Self::supports_restart_frame]. The
+    /// debugger stops once it's done.
+    pub fn request_restart_frame(&self, frame_id: u64) -> Result<(), DapError> {
+        let thread_id = self.default_thread_id();
+        let generation = self.threads.generation();
+
+        self.send_request(
+            |seq| RequestMessage::RestartFrame {
+                seq,
+                arguments: RestartFrameArguments { frame_id },
+            },
+            on_response(move |session: &DebugSession, result: Result<(), _>| {
+                result?;
+                session.on_resumed(generation, Some(thread_id), false);
+                Ok(())
+            }),
+        )?;
+
+        Ok(())
+    }
+
+    /// The functions that can be stepped into from the selected frame, to pick one for
+    /// [StepKind::In].
+    pub fn request_step_in_targets(
+        &self,
+    ) -> Result<RequestHandle<StepInTargetsResponseBody>, DapError> {
+        let frame_id = self.selected_frame_id().ok_or(DapError::NoSelectedFrame)?;
+
+        self.send_awaited_request(|seq| RequestMessage::StepInTargets {
+            seq,
+            arguments: StepInTargetsArguments { frame_id },
+        })
+    }
+
+    /// Suspends `thread_id`, or the selected thread if None. Some debuggers suspend every thread
+    /// anyway.
+    pub fn request_pause(&self, thread_id: Option<u64>) -> Result<(), DapError> {
+        let thread_id = thread_id.unwrap_or_else(|| self.default_thread_id());
+
+        self.send_request(
+            |seq| RequestMessage::Pause {
+                seq,
+                arguments: PauseArguments { thread_id },
+            },
+            ignore_response(),
+        )?;
+
+        Ok(())
+    }
+
+    /// Requests the children of the given reference, they can be retrieved with
+    /// [Self::get_variables] once they arrive.
+    pub fn request_variables(&self, variables_reference: u64) -> Result<RequestId, DapError> {
+        let generation = self.variables.set_variables_pending(variables_reference);
+
+        self.send_request(
+            |seq| RequestMessage::Variables {
+                seq,
+                arguments: VariablesArguments {
+                    variables_reference,
+                },
+            },
+            on_response(
+                move |session: &DebugSession, result: Result<VariablesResponseBody, _>| {
+                    let result = result.map(|body| body.variables).map_err(|e| e.to_string());
+                    session
+                        .variables
+                        .complete_variables(generation, variables_reference, result);
+                    Ok(())
+                },
+            ),
+        )
+        .inspect_err(|e| {
+            self.variables
+                .complete_variables(generation, variables_reference, Err(e.to_string()));
+        })
+    }
+
+    pub fn supports_set_variable(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_set_variable)
+    }
+
+    pub fn supports_set_expression(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_set_expression)
+    }
+
+    /// Whether the debugger can run backwards, see [StepKind::Back] and
+    /// [Self::request_reverse_continue].
+    pub fn supports_step_back(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_step_back)
+    }
+
+    pub fn supports_restart_frame(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_restart_frame)
+    }
+
+    /// Whether the debugger can be told to leave the debuggee running, or to terminate it, when
+    /// the session ends. Otherwise it decides by itself.
+    pub fn supports_terminate_debuggee(&self) -> bool {
+        self.supports(|capabilities| capabilities.support_terminate_debuggee)
+    }
+
+    pub fn supports_terminate_request(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_terminate_request)
+    }
+
+    /// Whether the session can be restarted with [Self::restart], without ending it.
+    pub fn supports_restart_request(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_restart_request)
+    }
+
+    /// Whether the value of `variable` can be changed. Nothing can be changed in a core dump.
+    pub fn can_set_variable(&self, variable: &Variable) -> bool {
+        !self.is_post_mortem()
+            && !variable.has_attribute(VariablePresentationHintAttribute::ReadOnly)
+            && (self.supports_set_variable()
+                || (variable.evaluate_name.is_some() && self.supports_set_expression()))
+    }
+
+    /// Changes the value of `variable`, a child of `variables_reference`. It's assigned through
+    /// its evaluate name if it has one and the debugger can do that, by name otherwise. Once the
+    /// debugger confirms it, the variable is updated with the value it reports.
+    pub fn set_variable(
+        &self,
+        variables_reference: u64,
+        variable: &Variable,
+        value: impl Into<String>,
+    ) -> Result<RequestHandle<()>, DapError> {
+        let generation = self.variables.generation();
+        let name = variable.name.clone();
+        let value = value.into();
+        let (sender, receiver) = mpsc::channel();
+
+        // Both responses carry the same new value
+        let update =
+            move |session: &DebugSession,
+                  result: Result<(String, Option<String>, Option<u64>), DapError>| {
+                let result = result.map(|(value, value_type, new_reference)| {
+                    session.variables.update_variable(
+                        generation,
+                        variables_reference,
+                        &name,
+                        |variable| {
+                            variable.value = value;
+                            if value_type.is_some() {
+                                variable.var_type = value_type;
+                            }
+                            if let Some(new_reference) = new_reference {
+                                variable.variables_reference = new_reference;
+                            }
+                        },
+                    );
+                    // Whatever memory is shown may be where the variable lives
+                    session.memory.invalidate();
+                });
+
+                let _ = sender.send(result);
+                Ok(())
+            };
+
+        let request_id = match &variable.evaluate_name {
+            Some(expression) if self.supports_set_expression() => {
+                let frame_id = self.selected_frame_id();
+                self.send_request(
+                    |seq| RequestMessage::SetExpression {
+                        seq,
+                        arguments: SetExpressionArguments {
+                            expression: expression.clone(),
+                            value,
+                            frame_id,
+                        },
+                    },
+                    on_response(
+                        move |session: &DebugSession, result: Result<SetExpressionResponseBody, _>| {
+                            let result = result.map(|body| {
+                                (body.value, body.value_type, body.variables_reference)
+                            });
+                            update(session, result)
+                        },
+                    ),
+                )?
+            }
+            _ if self.supports_set_variable() => self.send_request(
+                |seq| RequestMessage::SetVariable {
+                    seq,
+                    arguments: SetVariableArguments {
+                        variables_reference,
+                        name: variable.name.clone(),
+                        value,
+                    },
+                },
+                on_response(
+                    move |session: &DebugSession, result: Result<SetVariableResponseBody, _>| {
+                        let result = result
+                            .map(|body| (body.value, body.value_type, body.variables_reference));
+                        update(session, result)
+                    },
+                ),
+            )?,
+            _ => return Err(DapError::Unsupported("changing variables")),
+        };
+
+        Ok(RequestHandle::new(request_id, receiver))
+    }
+
+    fn send_scopes_request(&self, frame_id: u64) -> Result<(), DapError> {
+        let generation = self.variables.set_scopes_pending(frame_id);
+
+        self.send_request(
+            |seq| RequestMessage::Scopes {
+                seq,
+                arguments: ScopesArguments { frame_id },
+            },
+            on_response(
+                move |session: &DebugSession, result: Result<ScopesResponseBody, _>| {
+                    let result = result.map(|body| body.scopes).map_err(|e| e.to_string());
+                    // Cheap scopes get their variables right away, the expensive ones wait for the
+                    // user to expand them
+                    let cheap_scopes: Vec<u64> = result
+                        .iter()
+                        .flatten()
+                        .filter(|scope| !scope.expensive)
+                        .map(|scope| scope.variables_reference)
+                        .collect();
+
+                    if session.variables.complete_scopes(generation, result) {
+                        for variables_reference in cheap_scopes {
+                            session.request_variables(variables_reference)?;
+                        }
+                    }
+                    Ok(())
+                },
+            ),
+        )
+        .inspect_err(|e| {
+            self.variables
+                .complete_scopes(generation, Err(e.to_string()));
+        })?;
+
+        Ok(())
+    }
+
+    pub fn get_scopes(&self) -> ScopesState {
+        self.variables.get_scopes()
+    }
+
+    /// Returns the children of the given reference, or None if they were never requested.
+    pub fn get_variables(&self, variables_reference: u64) -> Option<VariablesState> {
+        self.variables.get_variables(variables_reference)
+    }
+
+    pub fn selected_frame_id(&self) -> Option<u64> {
+        *self.selected_frame_id.lock().unwrap()
+    }
+
+    /// Fills `out` with the memory starting at `address`. Whatever is not cached yet is
+    /// requested from the debugger, and shows up as [MemoryByte::Unknown] until it arrives.
+    /// Returns whether some of it is still on its way.
+    pub fn read_memory(
+        &self,
+        address: DebugPointer,
+        out: &mut [MemoryByte],
+    ) -> Result<bool, DapError> {
+        let mut missing = Vec::new();
+        let pending = self.memory.get(address, out, &mut missing);
+
+        if missing.is_empty()
+            || !self.get_debug_state().is_stopped()
+            || !self.supports_read_memory()
+        {
+            return Ok(pending);
+        }
+
+        for chunk_address in missing {
+            let generation = self.memory.set_pending(chunk_address);
+            self.send_read_memory(generation, chunk_address, chunk_address)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Asks for the memory of the chunk at `chunk_address`, from `address` to the end of the
+    /// chunk. If unreadable bytes stop the read before that, it's sent again from where they
+    /// end.
+    fn send_read_memory(
+        &self,
+        generation: u64,
+        chunk_address: usize,
+        address: usize,
+    ) -> Result<(), DapError> {
+        self.send_request(
+            |seq| RequestMessage::ReadMemory {
+                seq,
+                arguments: ReadMemoryArguments {
+                    memory_reference: DebugPointer::new(address),
+                    offset: None,
+                    count: (MEMORY_CHUNK_SIZE - (address - chunk_address)) as u64,
+                },
+            },
+            on_response(
+                move |session: &DebugSession, result: Result<ReadMemoryResponseBody, _>| {
+                    let data = result.and_then(|body| {
+                        let data = BASE64
+                            .decode(body.data.as_deref().unwrap_or_default())
+                            .map_err(|e| {
+                                DapError::UnexpectedResponse(format!("bad readMemory data: {e}"))
+                            })?;
+                        let unreadable_bytes = body.unreadable_bytes.unwrap_or(0) as usize;
+                        Ok((body.address, data, unreadable_bytes))
+                    });
+
+                    match data {
+                        Ok((address, data, unreadable_bytes)) => {
+                            if session.memory.complete_read(
+                                generation,
+                                chunk_address,
+                                address,
+                                &data,
+                                unreadable_bytes,
+                            ) && let Some(resume_address) =
+                                session.memory.resume_address(chunk_address)
+                            {
+                                session.send_read_memory(
+                                    generation,
+                                    chunk_address,
+                                    resume_address,
+                                )?;
+                            }
+                        }
+                        Err(e) => {
+                            log::debug!("Failed to read memory: {e}");
+                            session.memory.fail_read(generation, chunk_address);
+                        }
+                    }
+                    Ok(())
+                },
+            ),
+        )
+        .inspect_err(|_| {
+            self.memory
+                .invalidate_range(chunk_address, MEMORY_CHUNK_SIZE)
+        })?;
+
+        Ok(())
+    }
+
+    pub fn supports_read_memory(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_read_memory_request)
+    }
+
+    pub fn supports_write_memory(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_write_memory_request)
+    }
+
+    /// Writes `data` to memory at `address`. Once the debugger confirms it, the memory it reports
+    /// as written is fetched again. The handle resolves to the amount of bytes written.
+    pub fn write_memory(
+        &self,
+        address: DebugPointer,
+        data: &[u8],
+    ) -> Result<RequestHandle<usize>, DapError> {
+        let len = data.len();
+        let (sender, receiver) = mpsc::channel();
+
+        let request_id = self.send_request(
+            |seq| RequestMessage::WriteMemory {
+                seq,
+                arguments: WriteMemoryArguments {
+                    memory_reference: address,
+                    offset: None,
+                    allow_partial: Some(true),
+                    data: BASE64.encode(data),
+                },
+            },
+            on_response(
+                move |session: &DebugSession, result: Result<WriteMemoryResponseBody, _>| {
+                    // If the debugger does not tell what it wrote, the whole write is assumed to
+                    // have happened
+                    let result = result.map(|body| {
+                        let start = address
+                            .as_usize()
+                            .wrapping_add_signed(body.offset.unwrap_or(0) as isize);
+                        let written = body.bytes_written.map(|b| b as usize).unwrap_or(len);
+                        session.memory.invalidate_range(start, written);
+                        written
+                    });
+                    // It may have written part of it anyway, so the whole range is fetched again
+                    if result.is_err() {
+                        session.memory.invalidate_range(address.as_usize(), len);
+                    }
+
+                    let _ = sender.send(result);
+                    Ok(())
+                },
+            ),
+        )?;
+
+        Ok(RequestHandle::new(request_id, receiver))
+    }
+
+    /// Changes every time the program stops, so whatever was evaluated before can be told stale.
+    pub fn stop_generation(&self) -> u64 {
+        self.threads.generation()
+    }
+
+    /// Evaluates `expression` in the selected frame.
+    pub fn request_evaluate(
+        &self,
+        expression: impl Into<String>,
+        context: EvaluateContext,
+    ) -> Result<RequestHandle<EvaluateResponseBody>, DapError> {
+        let frame_id = self.selected_frame_id();
+
+        self.send_awaited_request(|seq| RequestMessage::Evaluate {
+            seq,
+            arguments: EvaluateArguments {
+                expression: expression.into(),
+                frame_id,
+                context: Some(context),
+            },
+        })
+    }
+
+    /// Evaluates an expression typed in the console, in the selected frame. The expression and
+    /// its result go to the output.
+    pub fn evaluate_in_console(&self, expression: &str) -> Result<(), DapError> {
+        let frame_id = self.selected_frame_id();
+        self.output.push_input(expression);
+
+        let result = self.send_request(
+            |seq| RequestMessage::Evaluate {
+                seq,
+                arguments: EvaluateArguments {
+                    expression: expression.to_owned(),
+                    frame_id,
+                    context: Some(EvaluateContext::Repl),
+                },
+            },
+            on_response(
+                |session: &DebugSession, result: Result<EvaluateResponseBody, _>| {
+                    match result {
+                        Ok(body) => session
+                            .output
+                            .push_result(&body.result, body.variables_reference),
+                        Err(e) => session.output.push(
+                            OutputEventCategory::Stderr,
+                            &format!("{e}\n"),
+                            None,
+                        ),
+                    }
+                    Ok(())
+                },
+            ),
+        );
+        if let Err(e) = &result {
+            self.output
+                .push(OutputEventCategory::Stderr, &format!("{e}\n"), None);
+        }
+
+        result.map(|_| ())
+    }
+
+    pub fn supports_completions(&self) -> bool {
+        self.supports(|capabilities| capabilities.supports_completions_request)
+    }
+
+    /// Asks for the completions of `text` typed in the console, at `column` counted in UTF-16
+    /// code units from 1.
+    pub fn request_completions(
+        &self,
+        text: impl Into<String>,
+        column: usize,
+    ) -> Result<RequestHandle<CompletionsResponseBody>, DapError> {
+        let frame_id = self.selected_frame_id();
+
+        self.send_awaited_request(|seq| RequestMessage::Completions {
+            seq,
+            arguments: CompletionsArguments {
+                frame_id,
+                text: text.into(),
+                column,
+            },
+        })
+    }
+
+    pub fn get_debug_state(&self) -> DebugState {
+        self.debug_state.lock().unwrap().clone()
+    }
+}
+
+impl DapInterface {
+    pub fn new() -> Self {
+        let breakpoints = Arc::new(BreakpointStore::new());
+        let output = Arc::new(OutputStore::new());
+        let stepping_granularity = Arc::new(Mutex::new(SteppingGranularity::Line));
+        let idle_session = Arc::new(DebugSession::new(
+            "",
+            None,
+            None,
+            None,
+            Arc::clone(&breakpoints),
+            Arc::clone(&output),
+            Arc::clone(&stepping_granularity),
+        ));
+
+        Self {
+            sessions: RwLock::new(Vec::new()),
+            current_session: RwLock::new(idle_session),
+            breakpoints,
+            output,
+            stepping_granularity,
+            terminals: TerminalStore::new(),
+            connecting: Mutex::new(Vec::new()),
+            start_error: Mutex::new(None),
+        }
+    }
+
+    /// Connects to a new debugger in the background, for a session called `name`. Once it's
+    /// connected, the session becomes the current one and is launched by `on_connected`, with
+    /// [DebugSession::launch] or [DebugSession::open_core_dump]. Failing to connect is reported
+    /// by [Self::take_start_error]. `terminal_id` is the terminal opened for the debuggee, if
+    /// there's one, it's closed if the session fails to start.
+    pub fn start_dap(
+        &self,
+        transport: DapTransport,
+        name: &str,
+        terminal_id: Option<u64>,
+        on_connected: impl FnOnce(&DebugSession) -> Result<(), DapError> + Send + 'static,
+    ) {
+        self.connect(transport, name, None, terminal_id, Box::new(on_connected));
+    }
+
+    fn connect(
+        &self,
+        transport: DapTransport,
+        name: &str,
+        parent_id: Option<u64>,
+        terminal_id: Option<u64>,
+        on_connected: OnConnected,
+    ) {
+        let (tx, rx) = mpsc::channel();
+        let worker_transport = transport.clone();
+        std::thread::spawn(move || {
+            // Nobody is waiting for the adapter anymore if we quit in the meantime
+            if let Err(mpsc::SendError(Ok(mut connection))) = tx.send(worker_transport.open())
+                && let Some(process) = connection.process.as_mut()
+            {
+                let _ = process.kill();
+            }
+        });
+
+        self.connecting.lock().unwrap().push(PendingConnection {
+            name: name.to_owned(),
+            parent_id,
+            terminal_id,
+            transport,
+            receiver: rx,
+            on_connected,
+        });
+    }
+
+    /// Whether some debugger is still being connected to.
+    pub fn is_connecting(&self) -> bool {
+        !self.connecting.lock().unwrap().is_empty()
+    }
+
+    /// Why a session failed to start since the last call, if one did.
+    pub fn take_start_error(&self) -> Option<String> {
+        self.start_error.lock().unwrap().take()
+    }
+
+    /// Starts the sessions whose debugger was connected to since the last call.
+    fn poll_connections(&self) {
+        let connections = std::mem::take(&mut *self.connecting.lock().unwrap());
+        for connection in connections {
+            let error = match connection.receiver.try_recv() {
+                Ok(Ok(dap_connection)) => {
+                    let instance = DapInstance::new(&connection.transport, dap_connection);
+                    tracy_client::Client::start().message("load_target_instance_w", 0);
+                    let session = self.add_session(
+                        &connection.name,
+                        connection.parent_id,
+                        connection.terminal_id,
+                        instance,
+                    );
+                    (connection.on_connected)(&session).err()
+                }
+                Ok(Err(e)) => Some(e),
+                Err(TryRecvError::Empty) => {
+                    self.connecting.lock().unwrap().push(connection);
+                    continue;
+                }
+                Err(TryRecvError::Disconnected) => Some(DapError::ConnectionFailed(
+                    "the connection attempt was abandoned".into(),
+                )),
+            };
+
+            if let Some(e) = error {
+                let message = format!("Start DAP error: {e}");
+                log::error!("{message}");
+                *self.start_error.lock().unwrap() = Some(message);
+                if let Some(terminal_id) = connection.terminal_id {
+                    self.terminals.remove(terminal_id);
+                }
+            }
+        }
+    }
+
+    /// Adds a session talking to `instance`, and makes it the current one. The sessions that
+    /// ended are dropped.
+    fn add_session(
+        &self,
+        name: &str,
+        parent_id: Option<u64>,
+        terminal_id: Option<u64>,
+        instance: DapInstance,
+    ) -> Arc<DebugSession> {
+        let session = Arc::new(DebugSession::new(
+            name,
+            parent_id,
+            terminal_id,
+            Some(instance),
+            Arc::clone(&self.breakpoints),
+            Arc::clone(&self.output),
+            Arc::clone(&self.stepping_granularity),
+        ));
+
+        let mut sessions = self.sessions.write().unwrap();
+        sessions.retain(|session| session.is_connected());
+        sessions.push(Arc::clone(&session));
+        *self.current_session.write().unwrap() = Arc::clone(&session);
+
+        session
+    }
+
+    /// The session the views show and the controls act on. Until one starts, it's an idle one
+    /// with nothing to show.
+    pub fn session(&self) -> Arc<DebugSession> {
+        Arc::clone(&self.current_session.read().unwrap())
+    }
+
+    /// Every session, in the order they started, for the session switcher.
+    pub fn sessions(&self) -> Vec<Arc<DebugSession>> {
+        self.sessions.read().unwrap().clone()
+    }
+
+    /// Makes the session `id` the current one.
+    pub fn select_session(&self, id: u64) {
+        let sessions = self.sessions.read().unwrap();
+        if let Some(session) = sessions.iter().find(|session| session.id == id) {
+            *self.current_session.write().unwrap() = Arc::clone(session);
+        }
+    }
+
+    /// Whether the session `id` is still talking to its debugger.
+    pub fn is_session_connected(&self, id: u64) -> bool {
+        let sessions = self.sessions.read().unwrap();
+        sessions
+            .iter()
+            .any(|session| session.id == id && session.is_connected())
+    }
+
+    /// How the session `id` was launched, to launch it again. A child session is launched by
+    /// the session that started it.
+    pub fn launch_arguments(&self, id: u64) -> Option<(LaunchRequest, serde_json::Value)> {
+        let sessions = self.sessions.read().unwrap();
+        let mut session = sessions.iter().find(|session| session.id == id)?;
+        loop {
+            if let Some(arguments) = session.launch_arguments.lock().unwrap().clone() {
+                return Some(arguments);
+            }
+            let parent_id = session.parent_id?;
+            session = sessions.iter().find(|session| session.id == parent_id)?;
+        }
+    }
+
+    /// Whether the debugger of some session asked for it to be restarted when it ended. If so,
+    /// returns that session, along with what goes in the `__restart` entry of the arguments of
+    /// the new one. Each request is only reported once.
+    pub fn take_restart_request(&self) -> Option<(u64, serde_json::Value)> {
+        let sessions = self.sessions.read().unwrap();
+        sessions
+            .iter()
+            .find_map(|session| Some((session.id, session.take_restart_request()?)))
+    }
+
+    /// Ends every session before quitting. The debuggers decide what happens to the debuggees,
+    /// and are given a moment to clean up before they are killed.
+    pub fn shutdown(&self) {
+        let sessions = self.sessions();
+        for session in &sessions {
+            match session.disconnect(None) {
+                Ok(()) | Err(DapError::NoDapInstance) => {}
+                Err(e) => log::error!("Failed to disconnect {}: {e}", session.name),
+            }
+        }
+
+        for session in &sessions {
+            let instance = session.instance.write().unwrap().take();
+            if let Some(instance) = instance {
+                instance.close(ADAPTER_EXIT_TIMEOUT);
+            }
+            session.end_session();
+        }
+    }
+
+    pub fn process_dap_events(&self) -> Result<(), DapError> {
+        self.poll_connections();
+        for session in self.sessions() {
+            if !session.is_connected() {
+                continue;
+            }
+
+            // Response callbacks and event handlers run without the instance locked, since they
+            // may send requests of their own
+            for msg in session.poll_messages() {
+                log::trace!("Received message: {msg:?}");
+                match msg {
+                    ProtocolMessage::Response(response) => session.complete_request(response),
+                    ProtocolMessage::Request(request) => {
+                        self.handle_reverse_request(&session, request)
+                    }
+                    ProtocolMessage::Event(event) => {
+                        if let Err(e) = session.handle_event(event) {
+                            log::error!("Failed to handle event: {e}");
+                        }
+                    }
+                    ProtocolMessage::Unknown => {}
+                }
+            }
+            session.poll_adapter();
+
+            if !session.is_connected() {
+                self.on_session_ended(&session);
+            }
+        }
+        self.terminals.read_output();
+
+        Ok(())
+    }
+
+    /// A parent session is only there for its children, it's ended along with the last of them.
+    /// If the session was the current one, the last one still running takes its place. If the
+    /// debuggee failed to launch, the terminal opened for it has nothing to show.
+    fn on_session_ended(&self, session: &DebugSession) {
+        if session.launch_error().is_some()
+            && let Some(terminal_id) = session.terminal_id
+        {
+            self.terminals.remove(terminal_id);
+        }
+        let sessions = self.sessions.read().unwrap();
+        if let Some(parent_id) = session.parent_id
+            && !sessions
+                .iter()
+                .any(|child| child.parent_id == Some(parent_id) && child.is_connected())
+            && !self
+                .connecting
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|child| child.parent_id == Some(parent_id))
+            && let Some(parent) = sessions.iter().find(|parent| parent.id == parent_id)
+            && !parent.is_disconnecting()
+            && let Err(e) = parent.disconnect(None)
+        {
+            log::warn!("Failed to disconnect a parent session: {e}");
+        }
+
+        let mut current_session = self.current_session.write().unwrap();
+        if current_session.id == session.id
+            && let Some(running) = sessions.iter().rev().find(|other| other.is_connected())
+        {
+            *current_session = Arc::clone(running);
+        }
+    }
+
+    /// Answers a request the debugger of `session` sent. Even the ones we can't handle are
+    /// answered, the debugger may be waiting on them.
+    fn handle_reverse_request(&self, session: &DebugSession, request: RequestMessage) {
+        let (seq, command, result) = match request {
+            RequestMessage::RunInTerminal { seq, arguments } => (
+                seq,
+                "runInTerminal".to_owned(),
+                self.run_in_terminal(arguments).map_err(|e| e.to_string()),
+            ),
+            RequestMessage::StartDebugging { seq, arguments } => (
+                seq,
+                "startDebugging".to_owned(),
+                self.start_child_session(session, arguments)
+                    .map(|()| None)
+                    .map_err(|e| e.to_string()),
+            ),
+            RequestMessage::Unsupported { seq, command } => {
+                let message = format!("MemVisor does not handle {command} requests");
+                (seq, command, Err(message))
+            }
+            request => {
+                log::warn!("Ignoring request only clients are supposed to send: {request:?}");
+                return;
+            }
+        };
+
+        if let Err(message) = &result {
+            log::warn!("Failed to handle {command} request: {message}");
+        }
+        let sent = session
+            .instance
+            .write()
+            .unwrap()
+            .as_mut()
+            .map(|dap_instance| dap_instance.send_response(seq, &command, result));
+        if let Some(Err(e)) = sent {
+            log::error!("Failed to answer {command} request: {e}");
+        }
+    }
+
+    /// Runs the command the debugger asked for in a terminal, see
+    /// [RequestMessage::RunInTerminal]. Returns the body of the response.
+    fn run_in_terminal(
+        &self,
+        arguments: RunInTerminalArguments,
+    ) -> Result<Option<serde_json::Value>, DapError> {
+        let command = TerminalCommand {
+            args: arguments.args,
+            cwd: arguments.cwd.into(),
+            env: arguments.env.unwrap_or_default(),
+            through_shell: arguments.args_can_be_interpreted_by_shell.unwrap_or(false),
+        };
+        log::info!("Running {:?} in a terminal", command.args);
+
+        let body = match arguments.kind.unwrap_or_default() {
+            RunInTerminalKind::Integrated => {
+                let pty = Pty::spawn(&command)?;
+                let pid = pty.pid();
+                let title = arguments.title.as_deref().unwrap_or(DEFAULT_TERMINAL_TITLE);
+                self.terminals.add(title, pty);
+
+                // The shell may run the command in a process of its own
+                if command.through_shell {
+                    RunInTerminalResponseBody {
+                        shell_process_id: pid,
+                        ..Default::default()
+                    }
+                } else {
+                    RunInTerminalResponseBody {
+                        process_id: pid,
+                        ..Default::default()
+                    }
+                }
+            }
+            // The command is run by the terminal emulator, we don't get to know its pid
+            RunInTerminalKind::External => RunInTerminalResponseBody {
+                shell_process_id: Some(pty::spawn_external(&command)?),
+                ..Default::default()
+            },
+        };
+
+        Ok(Some(serde_json::to_value(body)?))
+    }
+
+    /// Opens a terminal with nothing running in it, called `title`, for the debugger to run the
+    /// debuggee in. Returns the id of the terminal and its path.
+    pub fn open_terminal(&self, title: &str) -> Result<(u64, PathBuf), DapError> {
+        let pty = Pty::open()?;
+        let tty_path = pty.tty_path().to_owned();
+        log::info!("Opened terminal {}", tty_path.display());
+        let id = self.terminals.add(title, pty);
+
+        Ok((id, tty_path))
+    }
+
+    pub fn get_terminal(&self, id: u64) -> Option<Arc<Terminal>> {
+        self.terminals.get(id)
+    }
+
+    /// The ids of the terminals opened since the last call, to be shown.
+    pub fn take_new_terminals(&self) -> Vec<u64> {
+        self.terminals.take_new()
+    }
+
+    /// Closes the terminal `id`, hanging up the program in it if it's still running.
+    pub fn close_terminal(&self, id: u64) {
+        self.terminals.remove(id);
+    }
+
+    /// Starts the session the debugger of `parent` asked for, see
+    /// [RequestMessage::StartDebugging]. The child becomes the current session, while the
+    /// parent stays connected until its last child ends.
+    fn start_child_session(
+        &self,
+        parent: &DebugSession,
+        arguments: StartDebuggingArguments,
+    ) -> Result<(), DapError> {
+        let transport = parent
+            .instance
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|parent| parent.child_transport().clone())
+            .ok_or(DapError::NoDapInstance)?;
+        log::info!("Starting a child session ({:?})", arguments.request);
+
+        let name = launch::config_name(&arguments.configuration)
+            .unwrap_or(&parent.name)
+            .to_owned();
+        let adapter_id = launch::adapter_id(&arguments.configuration).to_owned();
+        self.connect(
+            transport,
+            &name,
+            Some(parent.id),
+            None,
+            Box::new(move |child| {
+                child.initialize(
+                    &adapter_id,
+                    Some((arguments.request, arguments.configuration)),
+                )
+            }),
+        );
+
+        Ok(())
+    }
+
+    /// Adds a breakpoint to the project, or replaces the one on the same line, function or
+    /// exception filter. It's sent to every session whose debugger can set it. Data and
+    /// instruction breakpoints are about the memory of one debuggee, they are only set on the
+    /// current session.
+    pub fn put_breakpoint(&self, mut breakpoint: Breakpoint) -> Result<(), DapError> {
+        // The id is the one the debugger of some session gave it, each session gets its own
+        breakpoint.clear_breakpoint_id();
+        if matches!(breakpoint, Breakpoint::Data(_) | Breakpoint::Instruction(_)) {
+            return self.session().put_breakpoint(breakpoint);
+        }
+        self.breakpoints.add(breakpoint.clone());
+
+        let mut result = Ok(());
+        for session in self.sessions() {
+            if session.can_share_breakpoint(&breakpoint) {
+                let session_result = session.put_breakpoint(breakpoint.clone());
+                result = result.and(session_result);
+            }
+        }
+        result
+    }
+
+    /// Removes a breakpoint from the project, and from every session. Data and instruction
+    /// breakpoints are only removed from the current session, the one they were set on.
+    pub fn remove_breakpoint(&self, breakpoint: &Breakpoint) -> Result<(), DapError> {
+        if matches!(breakpoint, Breakpoint::Data(_) | Breakpoint::Instruction(_)) {
+            return self.session().remove_breakpoint(breakpoint);
+        }
+        self.breakpoints.remove(breakpoint);
+
+        let mut result = Ok(());
+        for session in self.sessions() {
+            let session_result = session.remove_breakpoint(breakpoint);
+            result = result.and(session_result);
+        }
+        result
+    }
+
+    /// Collapses a group of output if it's expanded, expands it otherwise.
+    pub fn toggle_output_group(&self, id: u64) {
+        self.output.toggle_group(id);
+    }
+
+    pub fn output_len(&self) -> usize {
+        self.output.num_lines()
+    }
+
+    /// Fills `out` with the lines of output in `range`.
+    pub fn get_output(&self, range: Range<usize>, out: &mut Vec<OutputLine>) {
+        self.output.get(range, out);
+    }
+
+    pub fn clear_output(&self) {
+        self.output.clear();
+    }
+}
+
+impl Default for DapInterface {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DapInterface;
+    use crate::dap::DapInstance;
+    use crate::dap::message_types::{Capabilities, ExceptionBreakpointsFilter};
+    use crate::dap::transport::{DapConnection, DapTransport};
+    use crate::data::breakpoints::{Breakpoint, ExceptionBreakpoint};
+
+    /// A debugger that never says anything, and takes whatever it's sent.
+    fn silent_instance() -> DapInstance {
+        let connection = DapConnection {
+            process: None,
+            reader: Box::new(std::io::empty()),
+            writer: Box::new(std::io::sink()),
+            port: None,
+        };
+        DapInstance::new(&DapTransport::default_adapter(), connection)
+    }
+
+    #[test]
+    fn test_disabled_exception_filter_stays_off() {
+        let dap_interface = DapInterface::new();
+        let capabilities = Capabilities {
+            exception_breakpoint_filters: Some(vec![ExceptionBreakpointsFilter {
+                filter: "rust_panic".into(),
+                label: "Rust panic".into(),
+                default: Some(true),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+        let first = dap_interface.add_session("first", None, None, silent_instance());
+        first.on_initialized(Ok(capabilities.clone())).unwrap();
+        let mut exceptions = Vec::new();
+        first.get_exception_breakpoints(&mut exceptions);
+        assert_eq!(exceptions.len(), 1);
+
+        let rust_panic = Breakpoint::Exception(ExceptionBreakpoint::new("rust_panic"));
+        dap_interface.remove_breakpoint(&rust_panic).unwrap();
+
+        // Nor is it turned back on for the next session, or the same one restarted
+        let second = dap_interface.add_session("second", None, None, silent_instance());
+        second.on_initialized(Ok(capabilities)).unwrap();
+        exceptions.clear();
+        second.get_exception_breakpoints(&mut exceptions);
+        assert!(exceptions.is_empty());
     }
 }
-unsafe impl Sync for DapInterface {}
-unsafe impl Send for DapInterface {}
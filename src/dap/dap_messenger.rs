@@ -27,15 +27,23 @@ where
             loop {
                 let res =
                     Self::worker_receive_message(&mut reader, &mut read_buf, &mut json_scratchpad, trace_enabled);
-                if let Ok(msg) = res {
-                    if let Err(e) = tx.send(msg) {
-                        log::error!("Channel broken: {e}");
+                match res {
+                    Ok(msg) => {
+                        if let Err(e) = tx.send(msg) {
+                            log::error!("Channel broken: {e}");
+                            break; // Out of the loop
+                        }
+                    }
+                    // The whole message was read, so we can just skip it and keep going
+                    Err(
+                        e @ (DapError::JsonEncodingError(_) | DapError::BadCharacterEncoding(_)),
+                    ) => {
+                        log::error!("Failed to decode message: {e}");
+                    }
+                    Err(e) => {
+                        log::error!("Receive message error: {e}");
                         break; // Out of the loop
                     }
-                } else {
-                    let msg_err = res.unwrap_err();
-                    log::error!("Receive message error: {msg_err}");
-                    break; // Out of the loop
                 }
             }
 
@@ -89,7 +97,7 @@ where
             println!("RECEIVED: {json_str}");
         }
 
-        let message = serde_json::from_str(json_str)?;
+        let message = ProtocolMessage::decode(json_str)?;
 
         Ok(message)
     }
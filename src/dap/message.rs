@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 
 use super::message_types::*;
+use crate::data::launch::LaunchRequest;
+use crate::data::types::DebugPointer;
+use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "type")]
@@ -61,6 +64,15 @@ pub enum RequestMessage {
         /// Just send None for now.
         arguments: Option<serde_json::Value>,
     },
+    /// Returns possible completions for a fragment of text typed in the console.
+    ///
+    /// Clients should only call this request if the corresponding capability
+    /// supportsCompletionsRequest is true.
+    #[serde(rename = "completions")]
+    Completions {
+        seq: u64,
+        arguments: CompletionsArguments,
+    },
     /// This requests the debugger to resume execution of all threads.
     ///
     /// In case the debugger supports single thread execution, setting the `single_thread` argument
@@ -73,6 +85,48 @@ pub enum RequestMessage {
         seq: u64,
         arguments: ContinueArguments,
     },
+    /// Obtains information on a possible data breakpoint that could be set on an expression or
+    /// variable.
+    ///
+    /// Clients should only call this request if the corresponding capability
+    /// supportsDataBreakpoints is true.
+    #[serde(rename = "dataBreakpointInfo")]
+    DataBreakpointInfo {
+        seq: u64,
+        arguments: DataBreakpointInfoArguments,
+    },
+    /// Disassembles code stored at the provided location.
+    ///
+    /// Clients should only call this request if the corresponding capability
+    /// supportsDisassembleRequest is true.
+    #[serde(rename = "disassemble")]
+    Disassemble {
+        seq: u64,
+        arguments: DisassembleArguments,
+    },
+    /// Asks the debugger to end the session. Unless `terminate_debuggee` says otherwise, a
+    /// launched debuggee is terminated, while one that was attached to is left running.
+    #[serde(rename = "disconnect")]
+    Disconnect {
+        seq: u64,
+        arguments: DisconnectArguments,
+    },
+    /// Evaluates the given expression in the context of a stack frame, or in the global scope if
+    /// no frame is given.
+    #[serde(rename = "evaluate")]
+    Evaluate {
+        seq: u64,
+        arguments: EvaluateArguments,
+    },
+    /// Retrieves the details of the exception that caused the stopped event.
+    ///
+    /// Clients should only call this request if the corresponding capability
+    /// supportsExceptionInfoRequest is true.
+    #[serde(rename = "exceptionInfo")]
+    ExceptionInfo {
+        seq: u64,
+        arguments: ExceptionInfoArguments,
+    },
     /// This launch request is sent from the client to the debug adapter to start
     /// the debuggee with or without debugging (if noDebug is true).
     ///
@@ -84,6 +138,59 @@ pub enum RequestMessage {
     },
     #[serde(rename = "next")]
     Next { seq: u64, arguments: NextArguments },
+    /// Suspends the given thread. Once it's suspended, the debug adapter sends a stopped event
+    /// with the reason `pause`.
+    #[serde(rename = "pause")]
+    Pause { seq: u64, arguments: PauseArguments },
+    /// Reads bytes from memory at the provided location.
+    ///
+    /// Clients should only call this request if the corresponding capability
+    /// supportsReadMemoryRequest is true.
+    #[serde(rename = "readMemory")]
+    ReadMemory {
+        seq: u64,
+        arguments: ReadMemoryArguments,
+    },
+    /// Restarts the debug session, without ending it. The debugger does it by itself, the client
+    /// does not go through the launch sequence again.
+    ///
+    /// Clients should only call this request if the corresponding capability
+    /// supportsRestartRequest is true.
+    #[serde(rename = "restart")]
+    Restart {
+        seq: u64,
+        arguments: RestartArguments,
+    },
+    /// Restarts execution of the given stack frame, from its start. The debugger sends a stopped
+    /// event with the reason `restart` once done.
+    ///
+    /// Clients should only call this request if the corresponding capability
+    /// supportsRestartFrame is true.
+    #[serde(rename = "restartFrame")]
+    RestartFrame {
+        seq: u64,
+        arguments: RestartFrameArguments,
+    },
+    /// Resumes backwards execution of all threads, or only of the given one if `single_thread`
+    /// is true.
+    ///
+    /// Clients should only call this request if the corresponding capability supportsStepBack
+    /// is true.
+    #[serde(rename = "reverseContinue")]
+    ReverseContinue {
+        seq: u64,
+        arguments: ReverseContinueArguments,
+    },
+    /// Asks the client to run a command in a terminal, usually the debuggee, so it can be
+    /// interacted with. It's a reverse request, sent by the debugger to the client.
+    ///
+    /// Debuggers only send it if the client capability supportsRunInTerminalRequest is true.
+    #[serde(rename = "runInTerminal")]
+    RunInTerminal {
+        seq: u64,
+        arguments: RunInTerminalArguments,
+    },
+    /// The request returns the variable scopes for a given stack frame.
     #[serde(rename = "scopes")]
     Scopes {
         seq: u64,
@@ -98,15 +205,144 @@ pub enum RequestMessage {
         seq: u64,
         arguments: SetBreakpointsArguments,
     },
+    /// Replaces all existing data breakpoints with new data breakpoints.
+    ///
+    /// To clear all data breakpoints, specify an empty array.
+    ///
+    /// Clients should only call this request if the corresponding capability
+    /// supportsDataBreakpoints is true.
+    #[serde(rename = "setDataBreakpoints")]
+    SetDataBreakpoints {
+        seq: u64,
+        arguments: SetDataBreakpointsArguments,
+    },
+    /// Configures the debugger's response to thrown exceptions, replacing the previous
+    /// configuration. Each of the filters is one of the exceptionBreakpointFilters capability.
+    #[serde(rename = "setExceptionBreakpoints")]
+    SetExceptionBreakpoints {
+        seq: u64,
+        arguments: SetExceptionBreakpointsArguments,
+    },
+    /// Replaces all existing instruction breakpoints. Typically, instruction breakpoints would
+    /// be set from a disassembly window.
+    ///
+    /// Clients should only call this request if the corresponding capability
+    /// supportsInstructionBreakpoints is true.
+    #[serde(rename = "setInstructionBreakpoints")]
+    SetInstructionBreakpoints {
+        seq: u64,
+        arguments: SetInstructionBreakpointsArguments,
+    },
+    /// Replaces all existing function breakpoints with new function breakpoints.
+    ///
+    /// To clear all function breakpoints, specify an empty array.
+    ///
+    /// Clients should only call this request if the corresponding capability
+    /// supportsFunctionBreakpoints is true.
+    #[serde(rename = "setFunctionBreakpoints")]
+    SetFunctionBreakpoints {
+        seq: u64,
+        arguments: SetFunctionBreakpointsArguments,
+    },
+    /// Assigns the value of an expression to the location it refers to, like a variable or a
+    /// field, in the scope of a stack frame.
+    ///
+    /// Clients should only call this request if the corresponding capability
+    /// supportsSetExpression is true.
+    #[serde(rename = "setExpression")]
+    SetExpression {
+        seq: u64,
+        arguments: SetExpressionArguments,
+    },
+    /// Sets the value of a variable, a child of the given `variablesReference`.
+    ///
+    /// Clients should only call this request if the corresponding capability
+    /// supportsSetVariable is true.
+    #[serde(rename = "setVariable")]
+    SetVariable {
+        seq: u64,
+        arguments: SetVariableArguments,
+    },
     #[serde(rename = "stackTrace")]
     StackTrace {
         seq: u64,
         arguments: StackTraceArguments,
     },
+    /// Asks the client to start a new session of the same debugger, a child of the current one.
+    /// It's a reverse request, sent by the debugger to the client.
+    ///
+    /// Debuggers only send it if the client capability supportsStartDebuggingRequest is true.
+    #[serde(rename = "startDebugging")]
+    StartDebugging {
+        seq: u64,
+        arguments: StartDebuggingArguments,
+    },
+    /// Steps backwards, like [RequestMessage::Next] in reverse.
+    ///
+    /// Clients should only call this request if the corresponding capability supportsStepBack
+    /// is true.
+    #[serde(rename = "stepBack")]
+    StepBack {
+        seq: u64,
+        arguments: StepBackArguments,
+    },
+    /// Steps into a function if possible, otherwise it's just like [RequestMessage::Next].
+    ///
+    /// If there are several functions to step into on the current line, `target_id` picks one of
+    /// the targets returned by [RequestMessage::StepInTargets].
+    #[serde(rename = "stepIn")]
+    StepIn {
+        seq: u64,
+        arguments: StepInArguments,
+    },
+    /// Lists the functions that can be stepped into from the given frame.
+    ///
+    /// Clients should only call this request if the corresponding capability
+    /// supportsStepInTargetsRequest is true.
+    #[serde(rename = "stepInTargets")]
+    StepInTargets {
+        seq: u64,
+        arguments: StepInTargetsArguments,
+    },
+    /// Runs until the current function returns.
+    #[serde(rename = "stepOut")]
+    StepOut {
+        seq: u64,
+        arguments: StepOutArguments,
+    },
+    /// Asks the debuggee to terminate itself, giving it a chance to clean up. Once it's gone,
+    /// the debugger sends a terminated event.
+    ///
+    /// Clients should only call this request if the corresponding capability
+    /// supportsTerminateRequest is true.
+    #[serde(rename = "terminate")]
+    Terminate {
+        seq: u64,
+        arguments: TerminateArguments,
+    },
+    /// Retrieves a list of all threads.
+    #[serde(rename = "threads")]
+    Threads { seq: u64 },
+    /// Retrieves all child variables for the given variable reference.
+    #[serde(rename = "variables")]
     Variables {
         seq: u64,
         arguments: VariablesArguments,
     },
+    /// Writes bytes to memory at the provided location.
+    ///
+    /// Clients should only call this request if the corresponding capability
+    /// supportsWriteMemoryRequest is true.
+    #[serde(rename = "writeMemory")]
+    WriteMemory {
+        seq: u64,
+        arguments: WriteMemoryArguments,
+    },
+    /// Requests the debugger sent that we don't handle, or whose arguments we could not make
+    /// sense of. They still have to be answered. These are never deserialized directly, see
+    /// [ProtocolMessage::decode].
+    #[serde(skip)]
+    Unsupported { seq: u64, command: String },
     #[serde(other)]
     Unknown,
 }
@@ -118,27 +354,86 @@ pub enum ResponseMessage {
     Cancelled,
     #[serde(rename = "initialize")]
     Initialize {
+        seq: u64,
+        request_seq: u64,
+        success: bool,
+        /// Boxed, there are too many of them to move around with every response
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<Box<Capabilities>>,
+    },
+    #[serde(rename = "completions")]
+    Completions {
+        seq: u64,
+        request_seq: u64,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<CompletionsResponseBody>,
+    },
+    #[serde(rename = "continue")]
+    Continue {
+        seq: u64,
+        request_seq: u64,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<ContinueResponseBody>,
+    },
+    #[serde(rename = "dataBreakpointInfo")]
+    DataBreakpointInfo {
+        seq: u64,
+        request_seq: u64,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<DataBreakpointInfoResponseBody>,
+    },
+    #[serde(rename = "disassemble")]
+    Disassemble {
+        seq: u64,
+        request_seq: u64,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<DisassembleResponseBody>,
+    },
+    #[serde(rename = "evaluate")]
+    Evaluate {
         seq: u64,
         request_seq: u64,
         success: bool,
         #[serde(skip_serializing_if = "Option::is_none")]
-        body: Option<Capabilities>,
+        body: Option<EvaluateResponseBody>,
+    },
+    #[serde(rename = "exceptionInfo")]
+    ExceptionInfo {
+        seq: u64,
+        request_seq: u64,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<ExceptionInfoResponseBody>,
     },
     #[serde(rename = "notStopped")]
     NotStopped,
+    #[serde(rename = "readMemory")]
+    ReadMemory {
+        seq: u64,
+        request_seq: u64,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<ReadMemoryResponseBody>,
+    },
     #[serde(rename = "scopes")]
     Scopes {
         seq: u64,
-        request_req: u64,
+        request_seq: u64,
         success: bool,
-        body: ScopesResponseBody,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<ScopesResponseBody>,
     },
     #[serde(rename = "variables")]
     Variables {
         seq: u64,
-        request_req: u64,
+        request_seq: u64,
         success: bool,
-        body: VariablesResponseBody,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<VariablesResponseBody>,
     },
     #[serde(rename = "setBreakpoints")]
     SetBreakpoints {
@@ -147,6 +442,51 @@ pub enum ResponseMessage {
         success: bool,
         body: SetBreakpointsResponseBody,
     },
+    #[serde(rename = "setDataBreakpoints")]
+    SetDataBreakpoints {
+        seq: u64,
+        request_seq: u64,
+        success: bool,
+        body: SetDataBreakpointsResponseBody,
+    },
+    #[serde(rename = "setExceptionBreakpoints")]
+    SetExceptionBreakpoints {
+        seq: u64,
+        request_seq: u64,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<SetExceptionBreakpointsResponseBody>,
+    },
+    #[serde(rename = "setInstructionBreakpoints")]
+    SetInstructionBreakpoints {
+        seq: u64,
+        request_seq: u64,
+        success: bool,
+        body: SetInstructionBreakpointsResponseBody,
+    },
+    #[serde(rename = "setFunctionBreakpoints")]
+    SetFunctionBreakpoints {
+        seq: u64,
+        request_seq: u64,
+        success: bool,
+        body: SetFunctionBreakpointsResponseBody,
+    },
+    #[serde(rename = "setExpression")]
+    SetExpression {
+        seq: u64,
+        request_seq: u64,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<SetExpressionResponseBody>,
+    },
+    #[serde(rename = "setVariable")]
+    SetVariable {
+        seq: u64,
+        request_seq: u64,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<SetVariableResponseBody>,
+    },
     #[serde(rename = "stackTrace")]
     StackTrace {
         seq: u64,
@@ -154,10 +494,176 @@ pub enum ResponseMessage {
         success: bool,
         body: StackTraceResponseBody,
     },
+    #[serde(rename = "stepInTargets")]
+    StepInTargets {
+        seq: u64,
+        request_seq: u64,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<StepInTargetsResponseBody>,
+    },
+    #[serde(rename = "threads")]
+    Threads {
+        seq: u64,
+        request_seq: u64,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<ThreadsResponseBody>,
+    },
+    #[serde(rename = "writeMemory")]
+    WriteMemory {
+        seq: u64,
+        request_seq: u64,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        body: Option<WriteMemoryResponseBody>,
+    },
+    /// Responses without a variant of their own, and the ones to failed requests. These are
+    /// never deserialized directly, see [ProtocolMessage::decode].
+    #[serde(skip)]
+    Generic {
+        request_seq: u64,
+        success: bool,
+        command: String,
+        message: Option<String>,
+        body: Option<serde_json::Value>,
+    },
     #[serde(other)]
     Unknown,
 }
 
+impl ResponseMessage {
+    /// The command of the request this is a response to.
+    pub fn command(&self) -> Option<&str> {
+        match self {
+            ResponseMessage::Initialize { .. } => Some("initialize"),
+            ResponseMessage::Completions { .. } => Some("completions"),
+            ResponseMessage::Continue { .. } => Some("continue"),
+            ResponseMessage::DataBreakpointInfo { .. } => Some("dataBreakpointInfo"),
+            ResponseMessage::Disassemble { .. } => Some("disassemble"),
+            ResponseMessage::Evaluate { .. } => Some("evaluate"),
+            ResponseMessage::ExceptionInfo { .. } => Some("exceptionInfo"),
+            ResponseMessage::ReadMemory { .. } => Some("readMemory"),
+            ResponseMessage::Scopes { .. } => Some("scopes"),
+            ResponseMessage::Variables { .. } => Some("variables"),
+            ResponseMessage::SetBreakpoints { .. } => Some("setBreakpoints"),
+            ResponseMessage::SetDataBreakpoints { .. } => Some("setDataBreakpoints"),
+            ResponseMessage::SetExceptionBreakpoints { .. } => Some("setExceptionBreakpoints"),
+            ResponseMessage::SetInstructionBreakpoints { .. } => Some("setInstructionBreakpoints"),
+            ResponseMessage::SetFunctionBreakpoints { .. } => Some("setFunctionBreakpoints"),
+            ResponseMessage::SetExpression { .. } => Some("setExpression"),
+            ResponseMessage::SetVariable { .. } => Some("setVariable"),
+            ResponseMessage::StackTrace { .. } => Some("stackTrace"),
+            ResponseMessage::StepInTargets { .. } => Some("stepInTargets"),
+            ResponseMessage::Threads { .. } => Some("threads"),
+            ResponseMessage::WriteMemory { .. } => Some("writeMemory"),
+            ResponseMessage::Generic { command, .. } => Some(command),
+            ResponseMessage::Cancelled | ResponseMessage::NotStopped | ResponseMessage::Unknown => {
+                None
+            }
+        }
+    }
+
+    /// The seq of the request this is a response to.
+    pub fn request_seq(&self) -> Option<u64> {
+        match self {
+            ResponseMessage::Initialize { request_seq, .. }
+            | ResponseMessage::Completions { request_seq, .. }
+            | ResponseMessage::Continue { request_seq, .. }
+            | ResponseMessage::DataBreakpointInfo { request_seq, .. }
+            | ResponseMessage::Disassemble { request_seq, .. }
+            | ResponseMessage::Evaluate { request_seq, .. }
+            | ResponseMessage::ExceptionInfo { request_seq, .. }
+            | ResponseMessage::ReadMemory { request_seq, .. }
+            | ResponseMessage::Scopes { request_seq, .. }
+            | ResponseMessage::Variables { request_seq, .. }
+            | ResponseMessage::SetBreakpoints { request_seq, .. }
+            | ResponseMessage::SetDataBreakpoints { request_seq, .. }
+            | ResponseMessage::SetExceptionBreakpoints { request_seq, .. }
+            | ResponseMessage::SetInstructionBreakpoints { request_seq, .. }
+            | ResponseMessage::SetFunctionBreakpoints { request_seq, .. }
+            | ResponseMessage::SetExpression { request_seq, .. }
+            | ResponseMessage::SetVariable { request_seq, .. }
+            | ResponseMessage::StackTrace { request_seq, .. }
+            | ResponseMessage::StepInTargets { request_seq, .. }
+            | ResponseMessage::Threads { request_seq, .. }
+            | ResponseMessage::WriteMemory { request_seq, .. }
+            | ResponseMessage::Generic { request_seq, .. } => Some(*request_seq),
+            ResponseMessage::Cancelled | ResponseMessage::NotStopped | ResponseMessage::Unknown => {
+                None
+            }
+        }
+    }
+}
+
+impl ProtocolMessage {
+    /// Decodes a message sent by the debug adapter.
+    ///
+    /// Failed responses, and the ones we don't know how to decode, come out as
+    /// [ResponseMessage::Generic], so they can still be matched with their request. Likewise,
+    /// requests we can't handle come out as [RequestMessage::Unsupported], so they can still be
+    /// answered. Any other message we don't understand is [ProtocolMessage::Unknown].
+    pub fn decode(json: &str) -> Result<Self, serde_json::Error> {
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let message_type = value.get("type").and_then(serde_json::Value::as_str);
+        let is_response = message_type == Some("response");
+        let is_request = message_type == Some("request");
+        let success = value
+            .get("success")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+
+        if !is_response || success {
+            match Self::deserialize(&value) {
+                Ok(ProtocolMessage::Response(ResponseMessage::Unknown)) => {}
+                Ok(ProtocolMessage::Request(RequestMessage::Unknown)) => {
+                    return Ok(Self::unsupported_request(&value));
+                }
+                Ok(message) => return Ok(message),
+                Err(e) if is_response => log::warn!("Could not decode response: {e}"),
+                Err(e) if is_request => {
+                    log::warn!("Could not decode request: {e}");
+                    return Ok(Self::unsupported_request(&value));
+                }
+                Err(e) => {
+                    log::warn!("Could not decode message: {e}");
+                    return Ok(ProtocolMessage::Unknown);
+                }
+            }
+        }
+
+        #[derive(Deserialize)]
+        struct GenericResponse {
+            request_seq: u64,
+            success: bool,
+            command: String,
+            message: Option<String>,
+            body: Option<serde_json::Value>,
+        }
+
+        let response: GenericResponse = serde_json::from_value(value)?;
+        Ok(ProtocolMessage::Response(ResponseMessage::Generic {
+            request_seq: response.request_seq,
+            success: response.success,
+            command: response.command,
+            message: response.message,
+            body: response.body,
+        }))
+    }
+
+    fn unsupported_request(value: &serde_json::Value) -> Self {
+        let seq = value.get("seq").and_then(serde_json::Value::as_u64);
+        let command = value.get("command").and_then(serde_json::Value::as_str);
+        match seq.zip(command) {
+            Some((seq, command)) => ProtocolMessage::Request(RequestMessage::Unsupported {
+                seq,
+                command: command.to_owned(),
+            }),
+            None => ProtocolMessage::Unknown,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct CancelArguments {
     #[serde(rename = "requestId")]
@@ -180,6 +686,80 @@ pub struct InitializeArguments {
     pub adapter_id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub locale: Option<String>,
+    /// If true all line numbers are 1-based, which is the default.
+    #[serde(rename = "linesStartAt1")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines_start_at1: Option<bool>,
+    /// If true all column numbers are 1-based, which is the default.
+    #[serde(rename = "columnsStartAt1")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub columns_start_at1: Option<bool>,
+    /// Whether paths are given as `path`, the default, or as `uri`.
+    #[serde(rename = "pathFormat")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path_format: Option<String>,
+    /// The client shows the types of variables.
+    #[serde(rename = "supportsVariableType")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_variable_type: Option<bool>,
+    /// The client fetches the children of variables a page at a time.
+    #[serde(rename = "supportsVariablePaging")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_variable_paging: Option<bool>,
+    /// The client handles the runInTerminal reverse request.
+    #[serde(rename = "supportsRunInTerminalRequest")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_run_in_terminal_request: Option<bool>,
+    /// The client makes use of memory references, like reading the memory they point to.
+    #[serde(rename = "supportsMemoryReferences")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_memory_references: Option<bool>,
+    /// The client shows progress events.
+    #[serde(rename = "supportsProgressReporting")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_progress_reporting: Option<bool>,
+    /// The client handles the invalidated event.
+    #[serde(rename = "supportsInvalidatedEvent")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_invalidated_event: Option<bool>,
+    /// The client handles the memory event.
+    #[serde(rename = "supportsMemoryEvent")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_memory_event: Option<bool>,
+    /// The client can run the arguments of runInTerminal through a shell, if asked to.
+    #[serde(rename = "supportsArgsCanBeInterpretedByShell")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_args_can_be_interpreted_by_shell: Option<bool>,
+    /// The client handles the startDebugging reverse request.
+    #[serde(rename = "supportsStartDebuggingRequest")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_start_debugging_request: Option<bool>,
+    /// The client renders ANSI escape sequences in output and variable values.
+    #[serde(rename = "supportsANSIStyling")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_ansi_styling: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct CompletionsArguments {
+    /// Completions are looked for in the scope of this stack frame. If not specified, they are
+    /// looked for in the global scope.
+    #[serde(rename = "frameId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_id: Option<u64>,
+    /// The text typed so far.
+    pub text: String,
+    /// The position in `text` completions are requested for, in UTF-16 code units. Starts at 1,
+    /// unless the initialize request says otherwise.
+    pub column: usize,
+}
+
+/// The body of a failed response may not match the one of a successful one, so every field here
+/// has a default.
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct CompletionsResponseBody {
+    pub targets: Vec<CompletionItem>,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug)]
@@ -195,6 +775,111 @@ pub struct ContinueArguments {
     pub single_thread: Option<bool>,
 }
 
+/// The body of a failed response may not match the one of a successful one, so every field here
+/// has a default.
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct ContinueResponseBody {
+    /// If omitted or set to true, every thread was resumed. If false, only the thread in the
+    /// request was.
+    #[serde(rename = "allThreadsContinued")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub all_threads_continued: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct DataBreakpointInfoArguments {
+    /// Reference to the variable container if the data breakpoint is requested for a child of
+    /// the container.
+    #[serde(rename = "variablesReference")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables_reference: Option<u64>,
+    /// The name of the variable's child to obtain data breakpoint information for. If
+    /// `variables_reference` isn't specified, this can be an expression, or an address if
+    /// `as_address` is true.
+    pub name: String,
+    /// When `name` is an expression, evaluate it in the scope of this stack frame.
+    #[serde(rename = "frameId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_id: Option<u64>,
+    /// If specified, a debug adapter should return information for the range of memory extending
+    /// `bytes` number of bytes from the address or variable specified by `name`.
+    ///
+    /// Clients should only use this if the capability supportsDataBreakpointBytes is true.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+    /// If true, `name` is a memory address.
+    #[serde(rename = "asAddress")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub as_address: Option<bool>,
+}
+
+/// The body of a failed response may not match the one of a successful one, so every field here
+/// has a default.
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct DataBreakpointInfoResponseBody {
+    /// An identifier for the data on which a data breakpoint can be registered with the
+    /// setDataBreakpoints request, or None if no data breakpoint is available.
+    #[serde(rename = "dataId")]
+    pub data_id: Option<String>,
+    /// UI string that describes on what data the breakpoint is set on or why a data breakpoint
+    /// is not available.
+    pub description: String,
+    /// Attribute lists the available access types for a potential data breakpoint.
+    #[serde(rename = "accessTypes")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_types: Option<Vec<DataBreakpointAccessType>>,
+    /// Attribute indicates that a potential data breakpoint could be persisted across sessions.
+    #[serde(rename = "canPersist")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub can_persist: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct EvaluateArguments {
+    /// The expression to evaluate.
+    pub expression: String,
+    /// Evaluate the expression in the scope of this stack frame. If not specified, the
+    /// expression is evaluated in the global scope.
+    #[serde(rename = "frameId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_id: Option<u64>,
+    /// The context in which the evaluate request is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<EvaluateContext>,
+}
+
+/// The body of a failed response may not match the one of a successful one, so every field here
+/// has a default.
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct EvaluateResponseBody {
+    /// The result of the evaluate request.
+    pub result: String,
+    /// The type of the evaluate result.
+    #[serde(rename = "type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_type: Option<String>,
+    /// If this is > 0, the result is structured, and its children can be retrieved with the
+    /// variables request.
+    #[serde(rename = "variablesReference")]
+    pub variables_reference: u64,
+    /// The number of named child variables.
+    #[serde(rename = "namedVariables")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_variables: Option<u64>,
+    /// The number of indexed child variables.
+    #[serde(rename = "indexedVariables")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_variables: Option<u64>,
+    /// A memory reference to a location appropriate for this result. For pointer type eval
+    /// results, this is generally a reference to the memory address contained in the pointer.
+    #[serde(rename = "memoryReference")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_reference: Option<DebugPointer>,
+}
+
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct NextArguments {
     /// Specifies the thread to resume execution for one step
@@ -206,11 +891,111 @@ pub struct NextArguments {
     pub single_thread: Option<bool>,
     /// Stepping granularity. If none is specified, a default of [SteppingGranularity::Statement]
     /// is assumed.
-    #[serde(rename = "steppingGranularity")]
+    #[serde(rename = "granularity")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stepping_granularity: Option<SteppingGranularity>,
 }
 
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct PauseArguments {
+    /// Pause execution for this thread.
+    #[serde(rename = "threadId")]
+    pub thread_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct ReadMemoryArguments {
+    /// Memory reference to the base location from which data should be read.
+    #[serde(rename = "memoryReference")]
+    pub memory_reference: DebugPointer,
+    /// Offset (in bytes) to be applied to the reference location before reading data. Can be
+    /// negative.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    /// Number of bytes to read at the specified location and offset.
+    pub count: u64,
+}
+
+/// The body of a failed response may not match the one of a successful one, so every field here
+/// has a default.
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct ReadMemoryResponseBody {
+    /// The address of the first byte of data returned. Treated as a hex value if prefixed with
+    /// `0x`, or as a decimal value otherwise.
+    pub address: DebugPointer,
+    /// The number of unreadable bytes encountered after the last successfully read byte.
+    #[serde(rename = "unreadableBytes")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unreadable_bytes: Option<u64>,
+    /// The bytes read from memory, encoded using base64. If the decoded length of `data` is less
+    /// than the requested `count` in the original request, and `unreadable_bytes` is zero or
+    /// omitted, then the client should assume it's reached the end of readable memory.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct RestartArguments {
+    /// The latest version of the launch or attach arguments, if they changed since the session
+    /// started.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct RestartFrameArguments {
+    /// Restart the stack frame identified by this.
+    #[serde(rename = "frameId")]
+    pub frame_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct ReverseContinueArguments {
+    /// Specifies the active thread. If `single_thread` is true, only this thread is resumed.
+    #[serde(rename = "threadId")]
+    pub thread_id: u64,
+    /// If set to true, only the thread specified by `thread_id` should be resumed.
+    #[serde(rename = "singleThread")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub single_thread: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct RunInTerminalArguments {
+    /// Whether to use a terminal of the client, or one of its own. Integrated if not given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<RunInTerminalKind>,
+    /// The title of the terminal.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The working directory of the command.
+    pub cwd: String,
+    /// The command to run, followed by its arguments.
+    pub args: Vec<String>,
+    /// Environment variables to add to the one of the client. A variable set to null is removed
+    /// from it instead.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<HashMap<String, Option<String>>>,
+    /// Whether the arguments can be given to a shell as they are, without escaping them. Only
+    /// sent if the client capability supportsArgsCanBeInterpretedByShell is true.
+    #[serde(rename = "argsCanBeInterpretedByShell")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub args_can_be_interpreted_by_shell: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct RunInTerminalResponseBody {
+    /// The process id of the command that was run.
+    #[serde(rename = "processId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_id: Option<u32>,
+    /// The process id of the terminal shell, if the command runs in one.
+    #[serde(rename = "shellProcessId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shell_process_id: Option<u32>,
+}
+
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct ScopesArguments {
     /// Id of the stack frame to retrieve scope.
@@ -218,7 +1003,10 @@ pub struct ScopesArguments {
     pub frame_id: u64,
 }
 
+/// The body of a failed response may not match the one of a successful one, so every field here
+/// has a default.
 #[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
 pub struct ScopesResponseBody {
     pub scopes: Vec<Scope>,
 }
@@ -242,6 +1030,136 @@ pub struct SetBreakpointsResponseBody {
     pub breakpoints: Vec<Breakpoint>,
 }
 
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct SetDataBreakpointsArguments {
+    /// The contents of this array replaces all existing data breakpoints.
+    pub breakpoints: Vec<DataBreakpoint>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct SetDataBreakpointsResponseBody {
+    /// Information about the data breakpoints, in the same order as in the request.
+    pub breakpoints: Vec<Breakpoint>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct SetExceptionBreakpointsArguments {
+    /// Set of exception filters specified by their ID.
+    pub filters: Vec<String>,
+    /// Set of exception filters and their options. This attribute is only honored by a debug
+    /// adapter if the capability supportsExceptionFilterOptions is true.
+    #[serde(rename = "filterOptions")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_options: Option<Vec<ExceptionFilterOptions>>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct SetExceptionBreakpointsResponseBody {
+    /// Information about the exception breakpoints or filters, in the same order as
+    /// `filters` and then `filter_options` in the request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breakpoints: Option<Vec<Breakpoint>>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct ExceptionInfoArguments {
+    /// Thread for which exception information should be retrieved.
+    #[serde(rename = "threadId")]
+    pub thread_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct ExceptionInfoResponseBody {
+    /// ID of the exception that was thrown.
+    #[serde(rename = "exceptionId")]
+    pub exception_id: String,
+    /// Descriptive text for the exception.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Mode that caused the exception notification to be raised.
+    #[serde(rename = "breakMode")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub break_mode: Option<ExceptionBreakMode>,
+    /// Detailed information about the exception.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Box<ExceptionDetails>>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct DisconnectArguments {
+    /// Whether the session is going to be restarted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart: Option<bool>,
+    /// Whether the debuggee should be terminated. Only honored if the debugger supports
+    /// supportTerminateDebuggee.
+    #[serde(rename = "terminateDebuggee")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminate_debuggee: Option<bool>,
+    /// Whether the debuggee should be left suspended. Only honored if the debugger supports
+    /// supportSuspendDebuggee.
+    #[serde(rename = "suspendDebuggee")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suspend_debuggee: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct DisassembleArguments {
+    /// Memory reference to the base location containing the instructions to disassemble.
+    #[serde(rename = "memoryReference")]
+    pub memory_reference: DebugPointer,
+    /// Offset (in bytes) to be applied to the reference location before disassembling.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    /// Offset (in instructions) to be applied after the byte offset (if any) before
+    /// disassembling. Can be negative.
+    #[serde(rename = "instructionOffset")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_offset: Option<i64>,
+    /// Number of instructions to disassemble starting at the specified location and offset.
+    /// An adapter must return exactly this number of instructions, any unavailable
+    /// instructions should be replaced with an implementation-defined 'invalid instruction'
+    /// value.
+    #[serde(rename = "instructionCount")]
+    pub instruction_count: u64,
+    /// If true, the adapter should attempt to resolve memory addresses and other values to
+    /// symbolic names.
+    #[serde(rename = "resolveSymbols")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolve_symbols: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct DisassembleResponseBody {
+    /// The list of disassembled instructions.
+    pub instructions: Vec<DisassembledInstruction>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct SetInstructionBreakpointsArguments {
+    /// The instruction references of the breakpoints.
+    pub breakpoints: Vec<InstructionBreakpoint>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct SetInstructionBreakpointsResponseBody {
+    /// Information about the breakpoints, in the same order as in the request.
+    pub breakpoints: Vec<Breakpoint>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct SetFunctionBreakpointsArguments {
+    /// The function names of the breakpoints.
+    pub breakpoints: Vec<FunctionBreakpoint>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct SetFunctionBreakpointsResponseBody {
+    /// Information about the breakpoints, in the same order as in the request.
+    pub breakpoints: Vec<Breakpoint>,
+}
+
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct StackTraceResponseBody {
     /// If empty, it means there are no stack frames available.
@@ -257,6 +1175,74 @@ pub struct StackTraceResponseBody {
     pub total_frames: Option<usize>,
 }
 
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct SetExpressionArguments {
+    /// The l-value expression to assign to.
+    pub expression: String,
+    /// The value expression to assign to the l-value expression.
+    pub value: String,
+    /// Evaluate the expressions in the scope of this stack frame. If not specified, the
+    /// expressions are evaluated in the global scope.
+    #[serde(rename = "frameId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_id: Option<u64>,
+}
+
+/// The body of a failed response may not match the one of a successful one, so every field here
+/// has a default.
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct SetExpressionResponseBody {
+    /// The new value of the expression.
+    pub value: String,
+    /// The type of the new value.
+    #[serde(rename = "type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_type: Option<String>,
+    /// If this is > 0, the new value is structured, and its children can be retrieved with the
+    /// variables request.
+    #[serde(rename = "variablesReference")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables_reference: Option<u64>,
+    /// A memory reference to a location appropriate for this result.
+    #[serde(rename = "memoryReference")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_reference: Option<DebugPointer>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct SetVariableArguments {
+    /// The reference of the variable container.
+    #[serde(rename = "variablesReference")]
+    pub variables_reference: u64,
+    /// The name of the variable in the container.
+    pub name: String,
+    /// The value of the variable.
+    pub value: String,
+}
+
+/// The body of a failed response may not match the one of a successful one, so every field here
+/// has a default.
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct SetVariableResponseBody {
+    /// The new value of the variable.
+    pub value: String,
+    /// The type of the new value.
+    #[serde(rename = "type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_type: Option<String>,
+    /// If this is > 0, the new value is structured, and its children can be retrieved with the
+    /// variables request.
+    #[serde(rename = "variablesReference")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables_reference: Option<u64>,
+    /// A memory reference to a location appropriate for this result.
+    #[serde(rename = "memoryReference")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_reference: Option<DebugPointer>,
+}
+
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct StackTraceArguments {
     /// Thread from which to retrieve frames.
@@ -275,6 +1261,94 @@ pub struct StackTraceArguments {
     pub format: Option<StackFrameFormat>,
 }
 
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct StepInArguments {
+    /// Specifies the thread for which to resume execution for one step-into
+    #[serde(rename = "threadId")]
+    pub thread_id: u64,
+    /// If this flag is true, all other suspended threads are not resumed
+    #[serde(rename = "singleThread")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub single_thread: Option<bool>,
+    /// Id of the target to step into
+    #[serde(rename = "targetId")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_id: Option<u64>,
+    /// Stepping granularity. If none is specified, a default of [SteppingGranularity::Statement]
+    /// is assumed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub granularity: Option<SteppingGranularity>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct StepInTargetsArguments {
+    /// The stack frame for which to retrieve the possible step-in targets
+    #[serde(rename = "frameId")]
+    pub frame_id: u64,
+}
+
+/// The body of a failed response may not match the one of a successful one, so every field here
+/// has a default.
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct StepInTargetsResponseBody {
+    pub targets: Vec<StepInTarget>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct StepBackArguments {
+    /// Specifies the thread for which to resume execution for one step backwards
+    #[serde(rename = "threadId")]
+    pub thread_id: u64,
+    /// If this flag is true, all other suspended threads are not resumed
+    #[serde(rename = "singleThread")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub single_thread: Option<bool>,
+    /// Stepping granularity. If none is specified, a default of [SteppingGranularity::Statement]
+    /// is assumed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub granularity: Option<SteppingGranularity>,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct StepOutArguments {
+    /// Specifies the thread for which to resume execution for one step-out
+    #[serde(rename = "threadId")]
+    pub thread_id: u64,
+    /// If this flag is true, all other suspended threads are not resumed
+    #[serde(rename = "singleThread")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub single_thread: Option<bool>,
+    /// Stepping granularity. If none is specified, a default of [SteppingGranularity::Statement]
+    /// is assumed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub granularity: Option<SteppingGranularity>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StartDebuggingArguments {
+    /// The body of the launch or attach request of the new session, like a launch
+    /// configuration.
+    pub configuration: serde_json::Value,
+    /// Whether the new session is started with a launch or an attach request.
+    pub request: LaunchRequest,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct TerminateArguments {
+    /// Whether the session is going to be restarted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart: Option<bool>,
+}
+
+/// The body of a failed response may not match the one of a successful one, so every field here
+/// has a default.
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct ThreadsResponseBody {
+    pub threads: Vec<Thread>,
+}
+
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct VariablesArguments {
     /// The variable for which to retrieve it's children
@@ -282,22 +1356,73 @@ pub struct VariablesArguments {
     pub variables_reference: u64,
 }
 
+/// The body of a failed response may not match the one of a successful one, so every field here
+/// has a default.
 #[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
 pub struct VariablesResponseBody {
     pub variables: Vec<Variable>,
 }
 
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct WriteMemoryArguments {
+    /// Memory reference to the base location to which data should be written.
+    #[serde(rename = "memoryReference")]
+    pub memory_reference: DebugPointer,
+    /// Offset (in bytes) to be applied to the reference location before writing data. Can be
+    /// negative.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    /// If true, the debugger should attempt to write memory even if the entire memory region is
+    /// not writable. In such a case the debugger should stop after hitting the first byte of
+    /// memory that cannot be written and return the number of bytes written in the response
+    /// via the `offset` and `bytes_written` properties.
+    #[serde(rename = "allowPartial")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_partial: Option<bool>,
+    /// Bytes to write, encoded using base64.
+    pub data: String,
+}
+
+/// The body of a failed response may not match the one of a successful one, so every field here
+/// has a default.
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct WriteMemoryResponseBody {
+    /// Property that should be returned when `allow_partial` is true to indicate the offset of
+    /// the first byte of data successfully written. Can be negative.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    /// Property that should be returned when `allow_partial` is true to indicate the number of
+    /// bytes starting from address that were successfully written.
+    #[serde(rename = "bytesWritten")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_written: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "event")]
+#[allow(clippy::large_enum_variant)]
 pub enum DapEvent {
     #[serde(rename = "breakpoint")]
     Breakpoint { seq: u64, body: BreakpointEvent },
+    #[serde(rename = "capabilities")]
+    Capabilities { seq: u64, body: CapabilitiesEvent },
+    #[serde(rename = "continued")]
+    Continued { seq: u64, body: ContinuedEvent },
+    #[serde(rename = "exited")]
+    Exited { seq: u64, body: ExitedEvent },
     #[serde(rename = "output")]
     Output { seq: u64, body: OutputEvent },
     #[serde(rename = "stopped")]
     Stopped { seq: u64, body: StoppedEvent },
     #[serde(rename = "terminated")]
-    Terminated { seq: u64 },
+    Terminated {
+        seq: u64,
+        body: Option<TerminatedEvent>,
+    },
+    #[serde(rename = "thread")]
+    Thread { seq: u64, body: ThreadEvent },
     #[serde(other)]
     Unknown,
 }
@@ -320,11 +1445,49 @@ pub enum BreakpointEventReason {
     Unknown,
 }
 
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CapabilitiesEvent {
+    /// Only the capabilities that changed
+    pub capabilities: Capabilities,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ContinuedEvent {
+    /// The thread which was continued
+    #[serde(rename = "threadId")]
+    pub thread_id: u64,
+    /// If true, all threads have been resumed. If omitted or false, only the thread in
+    /// `thread_id` was.
+    #[serde(rename = "allThreadsContinued")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub all_threads_continued: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ExitedEvent {
+    /// The exit code returned from the debuggee
+    #[serde(rename = "exitCode")]
+    pub exit_code: i64,
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct OutputEvent {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub category: Option<OutputEventCategory>,
     pub output: String,
+    /// The source location that produced the output, like the logpoint that printed it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Whether the output starts or ends a group of output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<OutputGroup>,
+    /// If this is > 0, the output is structured, and its children can be retrieved with the
+    /// variables request.
+    #[serde(rename = "variablesReference")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variables_reference: Option<u64>,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -357,9 +1520,39 @@ pub struct StoppedEvent {
     pub hit_breakpoint_ids: Option<Vec<u64>>,
 }
 
+#[derive(Deserialize, Serialize, Debug)]
+pub struct TerminatedEvent {
+    /// If present and not false, the debugger asks for the session to be restarted. Whatever
+    /// it is, it should be passed back in the `__restart` entry of the launch or attach
+    /// arguments.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ThreadEvent {
+    pub reason: ThreadEventReason,
+    #[serde(rename = "threadId")]
+    pub thread_id: u64,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ThreadEventReason {
+    #[serde(rename = "started")]
+    Started,
+    #[serde(rename = "exited")]
+    Exited,
+    #[serde(other)]
+    Unknown,
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::dap::message::{CancelArguments, ProtocolMessage, RequestMessage};
+    use crate::dap::message::{
+        CancelArguments, DapEvent, NextArguments, ProtocolMessage, RequestMessage,
+    };
+    use crate::dap::message_types::{Capabilities, RunInTerminalKind, SteppingGranularity};
+    use crate::data::launch::LaunchRequest;
 
     #[test]
     fn test_serialize_request() {
@@ -388,4 +1581,106 @@ mod tests {
 
     #[test]
     fn test_serialize_response() {}
+
+    #[test]
+    fn test_serialize_instruction_step() {
+        // Stepping over an instruction is a next request, it only differs by its granularity
+        let msg = ProtocolMessage::Request(RequestMessage::Next {
+            seq: 12,
+            arguments: NextArguments {
+                thread_id: 1,
+                single_thread: None,
+                stepping_granularity: Some(SteppingGranularity::Instruction),
+            },
+        });
+
+        let encoded = serde_json::to_string(&msg).unwrap();
+        assert!(encoded.contains(r#""command":"next""#));
+        assert!(encoded.contains(r#""granularity":"instruction""#));
+    }
+
+    #[test]
+    fn test_decode_lifecycle_events() {
+        let exited = r#"{"seq": 7, "type": "event", "event": "exited", "body": {"exitCode": 3}}"#;
+        assert!(matches!(
+            ProtocolMessage::decode(exited).unwrap(),
+            ProtocolMessage::Event(DapEvent::Exited { body, .. }) if body.exit_code == 3
+        ));
+
+        // The body is optional
+        let terminated = r#"{"seq": 8, "type": "event", "event": "terminated"}"#;
+        assert!(matches!(
+            ProtocolMessage::decode(terminated).unwrap(),
+            ProtocolMessage::Event(DapEvent::Terminated { body: None, .. })
+        ));
+
+        let restart =
+            r#"{"seq": 9, "type": "event", "event": "terminated", "body": {"restart": true}}"#;
+        assert!(matches!(
+            ProtocolMessage::decode(restart).unwrap(),
+            ProtocolMessage::Event(DapEvent::Terminated { body: Some(body), .. })
+                if body.restart == Some(serde_json::Value::Bool(true))
+        ));
+    }
+
+    #[test]
+    fn test_capabilities_event() {
+        let mut capabilities: Capabilities = serde_json::from_str(
+            r#"{"supportsStepBack": true, "supportsRestartRequest": true, "completionTriggerCharacters": [".", ":"]}"#,
+        )
+        .unwrap();
+
+        // Only what changed is sent
+        let event = r#"{"seq": 3, "type": "event", "event": "capabilities", "body": {"capabilities": {"supportsRestartRequest": false, "supportsSetVariable": true}}}"#;
+        let ProtocolMessage::Event(DapEvent::Capabilities { body, .. }) =
+            ProtocolMessage::decode(event).unwrap()
+        else {
+            panic!("not a capabilities event");
+        };
+        capabilities.update(&body.capabilities);
+
+        assert_eq!(capabilities.supports_step_back, Some(true));
+        assert_eq!(capabilities.supports_restart_request, Some(false));
+        assert_eq!(capabilities.supports_set_variable, Some(true));
+        assert_eq!(
+            capabilities.completion_trigger_characters.as_deref(),
+            Some(&[".".to_owned(), ":".to_owned()][..])
+        );
+    }
+
+    #[test]
+    fn test_decode_reverse_requests() {
+        let run = r#"{"seq": 4, "type": "request", "command": "runInTerminal", "arguments": {"kind": "integrated", "cwd": "/tmp", "args": ["./prog", "--flag"], "env": {"RUST_LOG": "debug", "TERM": null}}}"#;
+        let ProtocolMessage::Request(RequestMessage::RunInTerminal { seq, arguments }) =
+            ProtocolMessage::decode(run).unwrap()
+        else {
+            panic!("not a runInTerminal request");
+        };
+        assert_eq!(seq, 4);
+        assert_eq!(arguments.kind, Some(RunInTerminalKind::Integrated));
+        assert_eq!(arguments.args, ["./prog", "--flag"]);
+        let env = arguments.env.unwrap();
+        assert_eq!(env["RUST_LOG"].as_deref(), Some("debug"));
+        assert_eq!(env["TERM"], None);
+
+        let start = r#"{"seq": 5, "type": "request", "command": "startDebugging", "arguments": {"request": "attach", "configuration": {"type": "pwa-node"}}}"#;
+        assert!(matches!(
+            ProtocolMessage::decode(start).unwrap(),
+            ProtocolMessage::Request(RequestMessage::StartDebugging { seq: 5, arguments })
+                if arguments.request == LaunchRequest::Attach
+        ));
+
+        // Still answerable, even when they make no sense to us
+        let unknown = r#"{"seq": 6, "type": "request", "command": "pickColor", "arguments": {}}"#;
+        let bad = r#"{"seq": 7, "type": "request", "command": "runInTerminal", "arguments": {}}"#;
+        for (json, expected_seq, expected_command) in
+            [(unknown, 6, "pickColor"), (bad, 7, "runInTerminal")]
+        {
+            assert!(matches!(
+                ProtocolMessage::decode(json).unwrap(),
+                ProtocolMessage::Request(RequestMessage::Unsupported { seq, command })
+                    if seq == expected_seq && command == expected_command
+            ));
+        }
+    }
 }
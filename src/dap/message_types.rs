@@ -56,17 +56,416 @@ pub enum BreakpointUnverifiedReason {
     Failed,
 }
 
-// We'll do it little by little
-/// Set of DAP capabilities. Not all are defined here. Too many
-#[derive(Clone, Copy, Deserialize, Serialize, Default, Debug)]
+/// What the debugger can do, as told by the initialize response and the capabilities event.
+/// Anything not given is not supported.
+#[derive(Clone, Deserialize, Serialize, Default, Debug)]
 pub struct Capabilities {
     #[serde(rename = "supportsConfigurationDoneRequest")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub supports_configuration_done_request: Option<bool>,
 
+    #[serde(rename = "supportsFunctionBreakpoints")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_function_breakpoints: Option<bool>,
+
+    #[serde(rename = "supportsConditionalBreakpoints")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_conditional_breakpoints: Option<bool>,
+
+    #[serde(rename = "supportsHitConditionalBreakpoints")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_hit_conditional_breakpoints: Option<bool>,
+
+    #[serde(rename = "supportsEvaluateForHovers")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_evaluate_for_hovers: Option<bool>,
+
+    /// The kinds of exceptions the debugger can break on, for the setExceptionBreakpoints
+    /// request.
+    #[serde(rename = "exceptionBreakpointFilters")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exception_breakpoint_filters: Option<Vec<ExceptionBreakpointsFilter>>,
+
+    /// Whether the debugger can step back and continue in reverse.
+    #[serde(rename = "supportsStepBack")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_step_back: Option<bool>,
+
+    #[serde(rename = "supportsSetVariable")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_set_variable: Option<bool>,
+
+    #[serde(rename = "supportsRestartFrame")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_restart_frame: Option<bool>,
+
+    #[serde(rename = "supportsGotoTargetsRequest")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_goto_targets_request: Option<bool>,
+
+    #[serde(rename = "supportsStepInTargetsRequest")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_step_in_targets_request: Option<bool>,
+
+    #[serde(rename = "supportsCompletionsRequest")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_completions_request: Option<bool>,
+
+    /// The characters that should trigger completion in the console. If not given, only `.`
+    /// does.
+    #[serde(rename = "completionTriggerCharacters")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion_trigger_characters: Option<Vec<String>>,
+
+    #[serde(rename = "supportsModulesRequest")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_modules_request: Option<bool>,
+
+    /// Columns the debugger wants shown for modules, along with the usual ones.
+    #[serde(rename = "additionalModuleColumns")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_module_columns: Option<Vec<ColumnDescriptor>>,
+
+    #[serde(rename = "supportedChecksumAlgorithms")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supported_checksum_algorithms: Option<Vec<ChecksumAlgorithm>>,
+
+    #[serde(rename = "supportsRestartRequest")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_restart_request: Option<bool>,
+
+    #[serde(rename = "supportsExceptionOptions")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_exception_options: Option<bool>,
+
+    /// Whether values can be formatted, like shown in hex, in the requests that take a `format`.
+    #[serde(rename = "supportsValueFormattingOptions")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_value_formatting_options: Option<bool>,
+
+    #[serde(rename = "supportsExceptionInfoRequest")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_exception_info_request: Option<bool>,
+
+    /// Whether the disconnect request honors `terminateDebuggee`.
+    #[serde(rename = "supportTerminateDebuggee")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub support_terminate_debuggee: Option<bool>,
+
+    /// Whether the disconnect request honors `suspendDebuggee`.
+    #[serde(rename = "supportSuspendDebuggee")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub support_suspend_debuggee: Option<bool>,
+
+    /// Whether the stack can be fetched a page at a time. Otherwise `startFrame` and `levels`
+    /// are ignored.
+    #[serde(rename = "supportsDelayedStackTraceLoading")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_delayed_stack_trace_loading: Option<bool>,
+
+    #[serde(rename = "supportsLoadedSourcesRequest")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_loaded_sources_request: Option<bool>,
+
+    #[serde(rename = "supportsLogPoints")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_log_points: Option<bool>,
+
+    #[serde(rename = "supportsTerminateThreadsRequest")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_terminate_threads_request: Option<bool>,
+
+    #[serde(rename = "supportsSetExpression")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_set_expression: Option<bool>,
+
+    #[serde(rename = "supportsTerminateRequest")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_terminate_request: Option<bool>,
+
+    #[serde(rename = "supportsDataBreakpoints")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_data_breakpoints: Option<bool>,
+
+    #[serde(rename = "supportsReadMemoryRequest")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_read_memory_request: Option<bool>,
+
+    #[serde(rename = "supportsWriteMemoryRequest")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_write_memory_request: Option<bool>,
+
+    #[serde(rename = "supportsDisassembleRequest")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_disassemble_request: Option<bool>,
+
+    #[serde(rename = "supportsCancelRequest")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_cancel_request: Option<bool>,
+
+    #[serde(rename = "supportsBreakpointLocationsRequest")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_breakpoint_locations_request: Option<bool>,
+
+    /// Whether evaluate can be asked for what should be copied, with the `clipboard` context.
+    #[serde(rename = "supportsClipboardContext")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_clipboard_context: Option<bool>,
+
+    #[serde(rename = "supportsSteppingGranularity")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_stepping_granularity: Option<bool>,
+
+    #[serde(rename = "supportsInstructionBreakpoints")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_instruction_breakpoints: Option<bool>,
+
+    #[serde(rename = "supportsExceptionFilterOptions")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_exception_filter_options: Option<bool>,
+
     #[serde(rename = "supportsSingleThreadExecutionRequests")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub supports_single_thread_execution_requests: Option<bool>,
+
+    #[serde(rename = "supportsDataBreakpointBytes")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_data_breakpoint_bytes: Option<bool>,
+
+    /// The modes breakpoints can be set in, like hardware or software breakpoints.
+    #[serde(rename = "breakpointModes")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breakpoint_modes: Option<Vec<BreakpointMode>>,
+
+    /// Whether the output and variable values may have ANSI escape sequences.
+    #[serde(rename = "supportsANSIStyling")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_ansi_styling: Option<bool>,
+}
+
+impl Capabilities {
+    /// Takes the capabilities `changed` has, keeping the rest. The capabilities event only sends
+    /// the ones that changed.
+    pub fn update(&mut self, changed: &Capabilities) {
+        let (Ok(serde_json::Value::Object(mut merged)), Ok(serde_json::Value::Object(changed))) =
+            (serde_json::to_value(&*self), serde_json::to_value(changed))
+        else {
+            return;
+        };
+        merged.extend(changed);
+
+        match serde_json::from_value(serde_json::Value::Object(merged)) {
+            Ok(updated) => *self = updated,
+            Err(e) => log::error!("Failed to update capabilities: {e}"),
+        }
+    }
+}
+
+/// A mode breakpoints can be set in, see [Capabilities::breakpoint_modes].
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct BreakpointMode {
+    /// The internal id of the mode, passed along with the breakpoints set in it
+    pub mode: String,
+    /// The name of the mode, shown in the UI
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The kinds of breakpoints the mode applies to, like `source` or `instruction`
+    #[serde(rename = "appliesTo")]
+    pub applies_to: Vec<String>,
+}
+
+/// A column of the modules view, see [Capabilities::additional_module_columns].
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ColumnDescriptor {
+    /// The name of the module attribute shown in the column
+    #[serde(rename = "attributeName")]
+    pub attribute_name: String,
+    /// The header of the column
+    pub label: String,
+    /// How the value is formatted, with `{0}` standing for it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// One of `string`, `number`, `boolean` or `unixTimestampUTC`
+    #[serde(rename = "type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column_type: Option<String>,
+    /// The width of the column in characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+}
+
+/// A suggestion of the completions request.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CompletionItem {
+    /// Shown in the list of completions, and inserted if there's no `text`
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Shown along with the label, like the type of the completion
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// The column the text that gets replaced starts at, counted like the column of the
+    /// request. If not given, the text is inserted at the column of the request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    /// How many UTF-16 code units get replaced by the completion, starting at `start`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<usize>,
+}
+
+/// A machine instruction, as returned by the disassemble request.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct DisassembledInstruction {
+    /// The address of the instruction.
+    pub address: DebugPointer,
+    /// Raw bytes representing the instruction and its operands, in an implementation-defined
+    /// format.
+    #[serde(rename = "instructionBytes")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_bytes: Option<String>,
+    /// Text representing the instruction and its operands, in an implementation-defined format.
+    pub instruction: String,
+    /// Name of the symbol that corresponds with the location of this instruction, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// Source location that corresponds to this instruction, if any. If omitted, it's the same
+    /// as the one of the previous instruction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Source>,
+    /// The line within the source location that corresponds to this instruction, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// A hint for how to present the instruction in the UI, like `invalid` for addresses that
+    /// could not be disassembled.
+    #[serde(rename = "presentationHint")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+pub struct InstructionBreakpoint {
+    /// The instruction reference of the breakpoint. This should be a memory or instruction
+    /// pointer reference from an EvaluateResponse, Variable, StackFrame, GotoTarget, or
+    /// Breakpoint.
+    #[serde(rename = "instructionReference")]
+    pub instruction_reference: DebugPointer,
+    /// The offset from the instruction reference in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    /// An expression for conditional breakpoints.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    /// An expression that controls how many hits of the breakpoint are ignored.
+    #[serde(rename = "hitCondition")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_condition: Option<String>,
+}
+
+/// A kind of exception the debugger can break on, like Rust panics or C++ throws.
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+pub struct ExceptionBreakpointsFilter {
+    /// The internal id of the filter option. This value is passed to the setExceptionBreakpoints
+    /// request.
+    pub filter: String,
+    /// The name of the filter option. This is shown in the UI.
+    pub label: String,
+    /// A help text providing additional information about the exception filter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Initial value of the filter option. If not specified a value false is assumed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<bool>,
+    /// Controls whether a condition can be specified for this filter option.
+    #[serde(rename = "supportsCondition")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_condition: Option<bool>,
+    /// A help text providing information about the condition.
+    #[serde(rename = "conditionDescription")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition_description: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+pub struct ExceptionFilterOptions {
+    /// ID of an exception filter returned by the `exceptionBreakpointFilters` capability.
+    #[serde(rename = "filterId")]
+    pub filter_id: String,
+    /// An expression for conditional exceptions. The exception breaks into the debugger if the
+    /// result of the condition is true.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+}
+
+/// When an exception breaks into the debugger.
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExceptionBreakMode {
+    #[serde(rename = "never")]
+    Never,
+    #[serde(rename = "always")]
+    Always,
+    #[serde(rename = "unhandled")]
+    Unhandled,
+    #[serde(rename = "userUnhandled")]
+    UserUnhandled,
+}
+
+/// Detailed information about an exception that has occurred.
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+pub struct ExceptionDetails {
+    /// Message contained in the exception.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Short type name of the exception object.
+    #[serde(rename = "typeName")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_name: Option<String>,
+    /// Fully-qualified type name of the exception object.
+    #[serde(rename = "fullTypeName")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full_type_name: Option<String>,
+    /// An expression that can be evaluated in the current scope to obtain the exception object.
+    #[serde(rename = "evaluateName")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evaluate_name: Option<String>,
+    /// Stack trace at the time the exception was thrown.
+    #[serde(rename = "stackTrace")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stack_trace: Option<String>,
+    /// Details of the exception contained by this exception, if any.
+    #[serde(rename = "innerException")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inner_exception: Option<Vec<ExceptionDetails>>,
+}
+
+/// The access a data breakpoint triggers on.
+#[derive(Deserialize, Serialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DataBreakpointAccessType {
+    #[serde(rename = "read")]
+    Read,
+    #[default]
+    #[serde(rename = "write")]
+    Write,
+    #[serde(rename = "readWrite")]
+    ReadWrite,
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+pub struct DataBreakpoint {
+    /// An id representing the data. This id is returned from the `dataBreakpointInfo` request.
+    #[serde(rename = "dataId")]
+    pub data_id: String,
+    /// The access type of the data.
+    #[serde(rename = "accessType")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_type: Option<DataBreakpointAccessType>,
+    /// An expression for conditional breakpoints.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    /// An expression that controls how many hits of the breakpoint are ignored.
+    #[serde(rename = "hitCondition")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_condition: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -87,7 +486,40 @@ pub enum ChecksumAlgorithm {
     Timestamp,
 }
 
-#[derive(Deserialize, Serialize, Copy, Clone, Debug)]
+/// The context in which an evaluate request is issued.
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum EvaluateContext {
+    /// Evaluate is called from a variables view context.
+    #[serde(rename = "variables")]
+    Variables,
+    /// Evaluate is called from a watch view context.
+    #[serde(rename = "watch")]
+    Watch,
+    /// Evaluate is called from a REPL context.
+    #[serde(rename = "repl")]
+    Repl,
+    /// Evaluate is called to generate the debug hover contents.
+    #[serde(rename = "hover")]
+    Hover,
+    /// Evaluate is called to generate clipboard contents.
+    #[serde(rename = "clipboard")]
+    Clipboard,
+}
+
+#[derive(Deserialize, Serialize, Default, Clone, Debug)]
+pub struct FunctionBreakpoint {
+    /// The name of the function.
+    pub name: String,
+    /// An expression for conditional breakpoints.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    /// An expression that controls how many hits of the breakpoint are ignored.
+    #[serde(rename = "hitCondition")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_condition: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Debug)]
 pub enum OutputEventCategory {
     #[serde(rename = "console")]
     Console,
@@ -103,7 +535,21 @@ pub enum OutputEventCategory {
     Unknown,
 }
 
-#[derive(Deserialize, Serialize, Copy, Clone, Debug)]
+/// How an output event nests the output around it.
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum OutputGroup {
+    /// The output is the header of a group, the output after it goes in it
+    #[serde(rename = "start")]
+    Start,
+    /// Same as `Start`, but the group is shown collapsed
+    #[serde(rename = "startCollapsed")]
+    StartCollapsed,
+    /// Ends the group, the output goes after it
+    #[serde(rename = "end")]
+    End,
+}
+
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Debug)]
 pub enum PresentationHint {
     #[serde(rename = "arguments")]
     Arguments,
@@ -121,45 +567,45 @@ pub enum PresentationHint {
 #[derive(Deserialize, Serialize, Default, Clone, Debug)]
 pub struct Scope {
     /// Eg: 'Arguments', 'Locals' or 'Registers'. Should be shown in the UI as is.
-    name: String,
+    pub name: String,
     /// A hint of how to present this on the UI
     #[serde(rename = "presentationHint")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    presentation_hint: Option<PresentationHint>,
+    pub presentation_hint: Option<PresentationHint>,
     /// A reference to be able to retrieve variables with the variables request.
     #[serde(rename = "variablesReference")]
-    variables_reference: u64,
+    pub variables_reference: u64,
     /// The number of named variables in this scope. The client can use this number for
     /// paging.
     #[serde(rename = "namedVariables")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    named_variables: Option<u64>,
+    pub named_variables: Option<u64>,
     /// The number of indexed variables in this scope. The client can use this number for
     /// paging.
     #[serde(rename = "indexedVariables")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    indexed_variables: Option<u32>,
+    pub indexed_variables: Option<u64>,
     /// If true, the number of variables in this scope is large or expensive to retrieve.
-    expensive: bool,
+    pub expensive: bool,
     /// The source for this scope,
     #[serde(skip_serializing_if = "Option::is_none")]
-    source: Option<Source>,
+    pub source: Option<Source>,
     /// The start line of the range covered by this scope
     #[serde(skip_serializing_if = "Option::is_none")]
-    line: Option<u64>,
+    pub line: Option<u64>,
     /// Start position of the range covered by the scope (measured in UTF-16, depends on the
     /// client capability config).
     #[serde(skip_serializing_if = "Option::is_none")]
-    column: Option<u64>,
+    pub column: Option<u64>,
     /// The end line of the range covered by this scope
     #[serde(rename = "endLine")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    end_line: Option<u64>,
+    pub end_line: Option<u64>,
     /// End position of the range covered by the scope (measured in UTF-16, depends on the
     /// client capability config).
     #[serde(rename = "endColumn")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    end_column: Option<u64>,
+    pub end_column: Option<u64>,
 }
 
 /// A [Source] is a descriptor for source code
@@ -262,7 +708,22 @@ pub struct StackFrame {
     /// A hint for how to present this frame in the UI
     #[serde(rename = "presentationHint")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub presentation_hint: Option<PresentationHint>,
+    pub presentation_hint: Option<StackFramePresentationHint>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StackFramePresentationHint {
+    #[serde(rename = "normal")]
+    Normal,
+    /// The frame is not an actual frame, but a label separating frames, like "Frames below are
+    /// from another thread".
+    #[serde(rename = "label")]
+    Label,
+    /// The frame is not interesting, like a library frame.
+    #[serde(rename = "subtle")]
+    Subtle,
+    #[serde(other)]
+    Unknown,
 }
 
 #[derive(Deserialize, Serialize, Default, Clone, Copy, Debug)]
@@ -294,8 +755,21 @@ pub struct StackFrameFormat {
     pub include_all: Option<bool>, 
 }
 
-#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
+/// Where the debugger wants a command run, see the runInTerminal request.
+#[derive(Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum RunInTerminalKind {
+    /// In a terminal of the client
+    #[default]
+    #[serde(rename = "integrated")]
+    Integrated,
+    /// In a terminal window of its own
+    #[serde(rename = "external")]
+    External,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
 pub enum SteppingGranularity {
+    #[default]
     #[serde(rename = "statement")]
     Statement,
     #[serde(rename = "line")]
@@ -304,7 +778,21 @@ pub enum SteppingGranularity {
     Instruction,
 }
 
-#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
+/// A function that can be stepped into from a frame, as listed by the stepInTargets request.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct StepInTarget {
+    pub id: u64,
+    /// The name of the step-in target (shown in the UI).
+    pub label: String,
+    /// The line of the step-in target.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Start position of the range covered by the step in target.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
 pub enum StoppedEventReason {
     #[serde(rename = "step")]
     Step,
@@ -324,42 +812,103 @@ pub enum StoppedEventReason {
     DataBreakpoint,
     #[serde(rename = "instruction breakpoint")]
     InstructionBreakpoint,
+    /// After a frame was restarted
+    #[serde(rename = "restart")]
+    Restart,
     #[serde(other)]
     Unknown,
 }
 
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Thread {
+    pub id: u64,
+    /// The name of the thread.
+    pub name: String,
+}
+
 #[derive(Deserialize, Serialize, Default, Clone, Debug)]
 pub struct Variable {
     /// The variable's name. Yay.
-    name: String,
+    pub name: String,
     /// The variables's value. This is a bit tricky. It can be multi-line, can be empty.
     /// Is intended to be used when showing the value on the UI.
-    value: String,
+    pub value: String,
     /// The type of the variable's value. Typically shown in the UI when hovering over the value.
     #[serde(rename = "type")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    var_type: Option<String>,
+    pub var_type: Option<String>,
+    /// Properties of the variable that can be used to determine how to render it in the UI.
+    #[serde(rename = "presentationHint")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<VariablePresentationHint>,
+    /// The evaluatable name of this variable which can be passed to the evaluate request to
+    /// fetch the variable's value.
+    #[serde(rename = "evaluateName")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evaluate_name: Option<String>,
+    /// If this is > 0, the variable is structured and its children can be retrieved by passing
+    /// it to the variables request.
+    #[serde(rename = "variablesReference")]
+    pub variables_reference: u64,
+    /// The number of named child variables. The client can use this information to present the
+    /// children in a paged UI and fetch them in chunks.
+    #[serde(rename = "namedVariables")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub named_variables: Option<u64>,
+    /// The number of indexed child variables. The client can use this information to present
+    /// the children in a paged UI and fetch them in chunks.
+    #[serde(rename = "indexedVariables")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub indexed_variables: Option<u64>,
+    /// A memory reference associated with this variable. For pointer type variables, this is
+    /// generally a reference to the memory address contained in the pointer.
+    #[serde(rename = "memoryReference")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_reference: Option<DebugPointer>,
+    /// A reference that allows the client to request the location where the variable is
+    /// declared.
+    #[serde(rename = "declarationLocationReference")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub declaration_location_reference: Option<u64>,
+    /// A reference that allows the client to request the location where the variable's value
+    /// is declared. For example, if the variable contains a function pointer, the adapter may be
+    /// able to look up the function's location.
+    #[serde(rename = "valueLocationReference")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_location_reference: Option<u64>,
 }
 
+impl Variable {
+    /// Whether the variable has a given presentation attribute.
+    pub fn has_attribute(&self, attribute: VariablePresentationHintAttribute) -> bool {
+        self.presentation_hint
+            .as_ref()
+            .and_then(|hint| hint.attributes.as_ref())
+            .map(|attributes| attributes.contains(&attribute))
+            .unwrap_or(false)
+    }
+}
+
+/// Properties of a variable that can be used to determine how to render the variable in the UI.
 #[derive(Deserialize, Serialize, Default, Clone, Debug)]
 pub struct VariablePresentationHint {
     /// The kind of the variable
     #[serde(skip_serializing_if = "Option::is_none")]
-    kind: Option<VariablePresentationHintKind>,
+    pub kind: Option<VariablePresentationHintKind>,
     /// Set of attributes represented as an array.
     #[serde(skip_serializing_if = "Option::is_none")]
-    attributes: Option<Vec<VariablePresentationHintAttribute>>,
+    pub attributes: Option<Vec<VariablePresentationHintAttribute>>,
     /// Visibility of the variable.
     #[serde(skip_serializing_if = "Option::is_none")]
-    visibility: Option<VariablePresentationHintVisibility>,
+    pub visibility: Option<VariablePresentationHintVisibility>,
     /// If true, clients can present teh variable with a UI that supports a specific gesture to
     /// trigger its evaluation. An example is a property based on a getter function, which might
     /// be expensive or have side effects.
     #[serde(skip_serializing_if = "Option::is_none")]
-    lazy: Option<bool>,
+    pub lazy: Option<bool>,
 }
 
-#[derive(Deserialize, Serialize, Clone, Copy, Debug)]
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
 pub enum VariablePresentationHintAttribute {
     #[serde(rename = "static")]
     Static,
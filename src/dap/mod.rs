@@ -3,14 +3,19 @@ pub mod message;
 pub mod dap_interface;
 pub mod message_types;
 pub mod requests;
+pub mod transport;
 
 use crate::dap::dap_messenger::DapMessenger;
-use crate::dap::message::{InitializeArguments, ProtocolMessage, RequestMessage};
-use std::io::BufReader;
-use std::path::{Path, PathBuf};
-use std::process::{Child, ChildStdin, Stdio};
+use crate::dap::message::{InitializeArguments, ProtocolMessage, RequestMessage, ResponseMessage};
+use crate::dap::requests::{Completion, PendingRequests, RequestId, ResponseCallback};
+use crate::dap::transport::{DapConnection, DapTransport};
+use crate::data::launch::LaunchRequest;
+use std::io::Write;
+use std::process::Child;
 use std::str::Utf8Error;
+use std::sync::Mutex;
 use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant};
 use crate::dap::message_types::Capabilities;
 
 #[derive(thiserror::Error, Debug)]
@@ -33,55 +38,72 @@ pub enum DapError {
     InvalidContentLength(String),
     #[error("Failed to decode string because of invalid UTF-8")]
     BadCharacterEncoding(#[from] Utf8Error),
+    #[error("{command} request failed: {message}")]
+    RequestFailed { command: String, message: String },
+    #[error("{0} request timed out")]
+    RequestTimedOut(String),
+    #[error("The request was dropped before getting a response")]
+    RequestAbandoned,
+    #[error("Unexpected response: {0}")]
+    UnexpectedResponse(String),
+    #[error("Invalid debug adapter configuration: {0}")]
+    BadAdapterConfig(String),
+    #[error("Failed to connect to the debug adapter: {0}")]
+    ConnectionFailed(String),
+    #[error("No stack frame is selected")]
+    NoSelectedFrame,
+    #[error("The debugger does not support {0}")]
+    Unsupported(&'static str),
 }
 
+/// How long the adapter gets to quit by itself once the session is over, before it's killed.
+pub const ADAPTER_EXIT_TIMEOUT: Duration = Duration::from_secs(2);
+
+const ADAPTER_EXIT_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
 pub struct DapInstance {
-    #[allow(unused)]
-    exec_path: PathBuf,
-    #[allow(unused)]
-    process: Child,
+    /// How child sessions reach the same debugger, see [DapTransport::for_child_session]
+    child_transport: DapTransport,
+    /// The adapter process, if we spawned it
+    process: Option<Child>,
+    /// Whether the messenger stopped, because the adapter closed the connection or quit
+    connection_lost: bool,
+    /// Whether we asked the debugger to end the session, so losing the connection is expected
+    disconnecting: bool,
 
     last_seq: u64,
-    dap_messenger: DapMessenger<ChildStdin>,
-    receiver: Receiver<ProtocolMessage>,
+    // The mutexes are only there for the instance to be shared between threads, it's always
+    // borrowed mutably to use what's in them
+    dap_messenger: Mutex<DapMessenger<Box<dyn Write + Send>>>,
+    receiver: Mutex<Receiver<ProtocolMessage>>,
     capabilities: Capabilities,
+    pending_requests: Mutex<PendingRequests>,
 
-    pending_launch_req: Option<serde_json::Value>,
+    pending_launch_req: Option<(LaunchRequest, serde_json::Value)>,
 }
 
 impl DapInstance {
-    pub fn instance<TArgs, TArgStr>(path: impl AsRef<Path>, options: TArgs) -> Result<Self, DapError>
-    where
-        TArgs: IntoIterator<Item = TArgStr>,
-        TArgStr: AsRef<str>,
-    {
-        let args: Vec<String> = options.into_iter().map(|s| s.as_ref().into()).collect();
-        Self::_instance(path.as_ref(), &args)
-    }
-
-    fn _instance(path: &Path, args: &[String]) -> Result<Self, DapError> {
-        log::info!("Launching debugger {path:?} with arguments {args:?}");
-        let mut process = std::process::Command::new(path)
-            .args(args)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .spawn()?;
-
-        let stdin = process.stdin.take().ok_or(DapError::NoStdin)?;
-        let stdout = process.stdout.take().ok_or(DapError::NoStdout)?;
-
+    /// Talks to the debugger through `connection`, which was opened with `transport`.
+    pub fn new(transport: &DapTransport, connection: DapConnection) -> Self {
         let (tx, rx) = std::sync::mpsc::sync_channel(10);
-        let dap_messenger = DapMessenger::new(BufReader::new(stdout), stdin, tx);
+        let dap_messenger = DapMessenger::new(connection.reader, connection.writer, tx);
 
-        Ok(Self {
-            exec_path: path.into(),
-            process,
+        Self {
+            child_transport: transport.for_child_session(connection.port),
+            process: connection.process,
+            connection_lost: false,
+            disconnecting: false,
             last_seq: 0,
-            dap_messenger,
-            receiver: rx,
+            dap_messenger: Mutex::new(dap_messenger),
+            receiver: Mutex::new(rx),
             capabilities: Capabilities::default(),
+            pending_requests: Mutex::new(PendingRequests::new()),
             pending_launch_req: None,
-        })
+        }
+    }
+
+    pub fn child_transport(&self) -> &DapTransport {
+        &self.child_transport
     }
 
     pub fn next_seq(&mut self) -> u64 {
@@ -89,56 +111,179 @@ impl DapInstance {
         self.last_seq
     }
 
-    pub fn launch(&mut self, backend_args_json: &str) -> Result<(), DapError> {
-        let seq = self.next_seq();
-        let message = ProtocolMessage::Request(RequestMessage::Initialize {
-            seq,
-            arguments: InitializeArguments {
-                client_id: Some("memvisor".into()),
-                client_name: Some("MemVisor".into()),
-                adapter_id: "rust-gdb".into(),
-                ..Default::default()
+    /// Initializes the debugger, telling it it's `adapter_id` and what we can do. The launch or
+    /// attach request is only sent once that's done, with [Self::flush_pending_launch_requests].
+    /// Without a request, the debugger is only initialized.
+    pub fn launch(
+        &mut self,
+        adapter_id: &str,
+        launch_request: Option<(LaunchRequest, serde_json::Value)>,
+        on_initialized: ResponseCallback,
+    ) -> Result<(), DapError> {
+        self.send_request(
+            |seq| RequestMessage::Initialize {
+                seq,
+                arguments: InitializeArguments {
+                    client_id: Some("memvisor".into()),
+                    client_name: Some("MemVisor".into()),
+                    adapter_id: adapter_id.into(),
+                    lines_start_at1: Some(true),
+                    columns_start_at1: Some(true),
+                    path_format: Some("path".into()),
+                    supports_variable_type: Some(true),
+                    supports_run_in_terminal_request: Some(true),
+                    supports_memory_references: Some(true),
+                    supports_args_can_be_interpreted_by_shell: Some(true),
+                    supports_start_debugging_request: Some(true),
+                    ..Default::default()
+                },
             },
-        });
+            on_initialized,
+        )?;
 
-        log::debug!("Initialize message: {message:?}");
-        self.send_message(&message)?;
+        self.pending_launch_req = launch_request;
 
-        let arguments = serde_json::from_str(backend_args_json)?;
+        Ok(())
+    }
 
-        self.pending_launch_req = Some(arguments);
+    /// Sends the request `request` makes out of its seq. `on_response` gets called once the
+    /// response arrives, or when it's clear it won't, see [PendingRequests].
+    pub fn send_request(
+        &mut self,
+        request: impl FnOnce(u64) -> RequestMessage,
+        on_response: ResponseCallback,
+    ) -> Result<RequestId, DapError> {
+        let seq = self.next_seq();
+        let message = serde_json::to_value(ProtocolMessage::Request(request(seq)))?;
+        let command = message
+            .get("command")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or_default()
+            .to_owned();
 
-        Ok(())
+        log::debug!("Sending request {seq} ({command})");
+        self.send_message_json(&message.to_string())?;
+        if command == "disconnect" {
+            self.disconnecting = true;
+        }
+        self.pending_requests
+            .get_mut()
+            .unwrap()
+            .insert(seq, command, on_response);
+
+        Ok(RequestId::new(seq))
     }
 
-    pub fn send_message(&mut self, msg: &ProtocolMessage) -> Result<(), DapError> {
-        self.send_message_json(&serde_json::to_string(msg)?)
+    /// Answers the request the debugger sent with `request_seq`. The response has the body
+    /// `result` holds, or the error message if it's an error.
+    pub fn send_response(
+        &mut self,
+        request_seq: u64,
+        command: &str,
+        result: Result<Option<serde_json::Value>, String>,
+    ) -> Result<(), DapError> {
+        let seq = self.next_seq();
+        let mut response = serde_json::json!({
+            "seq": seq,
+            "type": "response",
+            "request_seq": request_seq,
+            "command": command,
+            "success": result.is_ok(),
+        });
+        match result {
+            Ok(Some(body)) => response["body"] = body,
+            Ok(None) => {}
+            Err(message) => response["message"] = message.into(),
+        }
+
+        log::debug!("Answering request {request_seq} ({command})");
+        self.send_message_json(&response.to_string())
     }
 
-    pub fn flush_pending_launch_requests(&mut self) -> Result<(), DapError> {
-        if let Some(launch_req) = self.pending_launch_req.take() {
-            let seq = self.next_seq();
-            let message = ProtocolMessage::Request(RequestMessage::Launch { seq, arguments: launch_req });
+    /// Takes the callback of the request `response` answers, along with what it should be called
+    /// with. Returns the response back if no request was waiting for it.
+    pub fn complete_request(
+        &mut self,
+        response: ResponseMessage,
+    ) -> Result<Completion, ResponseMessage> {
+        self.pending_requests.get_mut().unwrap().complete(response)
+    }
 
-            return self.send_message(&message);
+    /// Takes the callbacks of the requests that timed out.
+    pub fn expire_requests(&mut self, out: &mut Vec<Completion>) {
+        self.pending_requests
+            .get_mut()
+            .unwrap()
+            .expire(Instant::now(), out);
+    }
+
+    pub fn flush_pending_launch_requests(
+        &mut self,
+        on_response: ResponseCallback,
+    ) -> Result<(), DapError> {
+        if let Some((request, arguments)) = self.pending_launch_req.take() {
+            self.send_request(
+                |seq| match request {
+                    LaunchRequest::Launch => RequestMessage::Launch { seq, arguments },
+                    LaunchRequest::Attach => RequestMessage::Attach { seq, arguments },
+                },
+                on_response,
+            )?;
         }
 
         Ok(())
     }
 
     pub fn poll_message(&mut self) -> Option<ProtocolMessage> {
-        match self.receiver.try_recv() {
+        match self.receiver.get_mut().unwrap().try_recv() {
             Ok(v) => Some(v),
             Err(TryRecvError::Empty) => None,
             Err(TryRecvError::Disconnected) => {
-                log::error!("Can't poll message: messenger disconnected");
+                if !self.connection_lost {
+                    log::info!("Can't poll message: messenger disconnected");
+                    self.connection_lost = true;
+                }
                 None
             }
         }
     }
 
+    /// Whether the session is over on the adapter side, because it quit or closed the
+    /// connection. Returns a description of what happened if so. It's only noticed once every
+    /// message the adapter sent was polled.
+    pub fn poll_adapter_exit(&mut self) -> Option<String> {
+        if !self.connection_lost {
+            return None;
+        }
+
+        let exit_status = match &mut self.process {
+            Some(process) => process.try_wait().ok().flatten(),
+            None => None,
+        };
+        Some(match exit_status {
+            Some(status) => format!("quit ({status})"),
+            None => "closed the connection".into(),
+        })
+    }
+
+    /// Whether a disconnect request was sent, after which the adapter is expected to go away.
+    pub fn is_disconnecting(&self) -> bool {
+        self.disconnecting
+    }
+
+    /// Closes the connection and waits for the adapter to quit, killing it if it takes longer
+    /// than `timeout`.
+    pub fn close(mut self, timeout: Duration) {
+        let process = self.process.take();
+        // Closing its stdin is what tells some adapters to quit
+        drop(self);
+        if let Some(mut process) = process {
+            reap_process(&mut process, timeout);
+        }
+    }
+
     fn send_message_json(&mut self, msg: &str) -> Result<(), DapError> {
-        self.dap_messenger.send_message(msg)
+        self.dap_messenger.get_mut().unwrap().send_message(msg)
     }
 
     pub fn get_capabilities(&self) -> &Capabilities {
@@ -148,4 +293,41 @@ impl DapInstance {
     pub fn set_capabilities(&mut self, cap: Capabilities) {
         self.capabilities = cap;
     }
+
+    /// Takes the capabilities that changed, see [Capabilities::update].
+    pub fn update_capabilities(&mut self, changed: &Capabilities) {
+        self.capabilities.update(changed);
+    }
+}
+
+impl Drop for DapInstance {
+    /// The adapter is waited on in the background, so it does not linger as a zombie.
+    fn drop(&mut self) {
+        if let Some(mut process) = self.process.take() {
+            std::thread::spawn(move || reap_process(&mut process, ADAPTER_EXIT_TIMEOUT));
+        }
+    }
+}
+
+/// Waits for `process` to quit for up to `timeout`, killing it after that.
+fn reap_process(process: &mut Child, timeout: Duration) {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match process.try_wait() {
+            Ok(Some(status)) => {
+                log::info!("Debug adapter quit ({status})");
+                return;
+            }
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(ADAPTER_EXIT_POLL_INTERVAL),
+            Ok(None) => break,
+            Err(e) => {
+                log::error!("Failed to wait for the debug adapter: {e}");
+                break;
+            }
+        }
+    }
+
+    log::warn!("Killing the debug adapter, it did not quit in time");
+    let _ = process.kill();
+    let _ = process.wait();
 }
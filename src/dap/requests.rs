@@ -1,3 +1,22 @@
+use crate::dap::DapError;
+use crate::dap::dap_interface::DebugSession;
+use crate::dap::message::{
+    CompletionsResponseBody, ContinueResponseBody, DataBreakpointInfoResponseBody,
+    DisassembleResponseBody, EvaluateResponseBody, ExceptionInfoResponseBody,
+    ReadMemoryResponseBody, ResponseMessage, ScopesResponseBody, SetBreakpointsResponseBody,
+    SetDataBreakpointsResponseBody, SetExceptionBreakpointsResponseBody, SetExpressionResponseBody,
+    SetFunctionBreakpointsResponseBody, SetInstructionBreakpointsResponseBody,
+    SetVariableResponseBody, StackTraceResponseBody, StepInTargetsResponseBody,
+    ThreadsResponseBody, VariablesResponseBody, WriteMemoryResponseBody,
+};
+use crate::dap::message_types::Capabilities;
+use std::collections::BTreeMap;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+/// How long we wait for the response to a request before giving up on it.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 #[repr(transparent)]
 pub struct RequestId(u64);
@@ -6,4 +25,299 @@ impl RequestId {
     pub fn new(seq: u64) -> Self {
         Self(seq)
     }
-}
\ No newline at end of file
+}
+
+/// Called once with the response to a request, or with the reason there won't be one. It runs
+/// without the DAP instance locked, so it's free to send more requests.
+///
+/// An error returned from it is only logged.
+pub type ResponseCallback = Box<
+    dyn FnOnce(&DebugSession, Result<ResponseMessage, DapError>) -> Result<(), DapError> + Send,
+>;
+
+/// A callback along with what it should be called with.
+pub type Completion = (ResponseCallback, Result<ResponseMessage, DapError>);
+
+/// The typed body of a successful response.
+pub trait FromResponse: Sized {
+    fn from_response(response: ResponseMessage) -> Result<Self, DapError>;
+}
+
+/// For requests whose response carries nothing we care about.
+impl FromResponse for () {
+    fn from_response(_response: ResponseMessage) -> Result<Self, DapError> {
+        Ok(())
+    }
+}
+
+macro_rules! impl_from_response {
+    ($body:ty, $variant:ident) => {
+        impl FromResponse for $body {
+            fn from_response(response: ResponseMessage) -> Result<Self, DapError> {
+                match response {
+                    ResponseMessage::$variant { body, .. } => Ok(body),
+                    other => Err(DapError::UnexpectedResponse(format!("{other:?}"))),
+                }
+            }
+        }
+    };
+    ($body:ty, $variant:ident, optional) => {
+        impl FromResponse for $body {
+            fn from_response(response: ResponseMessage) -> Result<Self, DapError> {
+                match response {
+                    ResponseMessage::$variant { body, .. } => Ok(body.unwrap_or_default()),
+                    other => Err(DapError::UnexpectedResponse(format!("{other:?}"))),
+                }
+            }
+        }
+    };
+}
+
+impl_from_response!(CompletionsResponseBody, Completions, optional);
+impl_from_response!(ContinueResponseBody, Continue, optional);
+impl_from_response!(DataBreakpointInfoResponseBody, DataBreakpointInfo, optional);
+impl_from_response!(DisassembleResponseBody, Disassemble, optional);
+impl_from_response!(EvaluateResponseBody, Evaluate, optional);
+impl_from_response!(ExceptionInfoResponseBody, ExceptionInfo, optional);
+impl_from_response!(ReadMemoryResponseBody, ReadMemory, optional);
+impl_from_response!(ScopesResponseBody, Scopes, optional);
+impl_from_response!(SetBreakpointsResponseBody, SetBreakpoints);
+impl_from_response!(SetDataBreakpointsResponseBody, SetDataBreakpoints);
+impl_from_response!(
+    SetExceptionBreakpointsResponseBody,
+    SetExceptionBreakpoints,
+    optional
+);
+impl_from_response!(
+    SetInstructionBreakpointsResponseBody,
+    SetInstructionBreakpoints
+);
+impl_from_response!(SetExpressionResponseBody, SetExpression, optional);
+impl_from_response!(SetFunctionBreakpointsResponseBody, SetFunctionBreakpoints);
+impl_from_response!(SetVariableResponseBody, SetVariable, optional);
+impl_from_response!(StackTraceResponseBody, StackTrace);
+impl_from_response!(StepInTargetsResponseBody, StepInTargets, optional);
+impl_from_response!(ThreadsResponseBody, Threads, optional);
+impl_from_response!(VariablesResponseBody, Variables, optional);
+impl_from_response!(WriteMemoryResponseBody, WriteMemory, optional);
+
+/// The capabilities come boxed, see [ResponseMessage::Initialize].
+impl FromResponse for Capabilities {
+    fn from_response(response: ResponseMessage) -> Result<Self, DapError> {
+        match response {
+            ResponseMessage::Initialize { body, .. } => {
+                Ok(body.map(|body| *body).unwrap_or_default())
+            }
+            other => Err(DapError::UnexpectedResponse(format!("{other:?}"))),
+        }
+    }
+}
+
+/// Makes a [ResponseCallback] out of one that takes the typed response body.
+pub fn on_response<T, F>(f: F) -> ResponseCallback
+where
+    T: FromResponse,
+    F: FnOnce(&DebugSession, Result<T, DapError>) -> Result<(), DapError> + Send + 'static,
+{
+    Box::new(move |dap, result| f(dap, result.and_then(T::from_response)))
+}
+
+/// A callback for requests we don't need anything back from. Failures still get logged.
+pub fn ignore_response() -> ResponseCallback {
+    Box::new(|_, result| result.map(|_| ()))
+}
+
+/// Lets whoever sent a request pick up its result later, without blocking.
+pub struct RequestHandle<T> {
+    id: RequestId,
+    receiver: Receiver<Result<T, DapError>>,
+}
+
+impl<T> RequestHandle<T> {
+    /// The result is expected to be sent through the other end of `receiver`, usually from the
+    /// request callback.
+    pub fn new(id: RequestId, receiver: Receiver<Result<T, DapError>>) -> Self {
+        Self { id, receiver }
+    }
+
+    pub fn id(&self) -> RequestId {
+        self.id
+    }
+
+    /// Returns the result of the request if it's done. It's only returned once, so the handle
+    /// should be dropped after that.
+    pub fn poll(&self) -> Option<Result<T, DapError>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            // The callback was dropped without being called, which happens when the instance
+            // goes away
+            Err(TryRecvError::Disconnected) => Some(Err(DapError::RequestAbandoned)),
+        }
+    }
+}
+
+struct PendingRequest {
+    command: String,
+    sent_at: Instant,
+    timeout: Option<Duration>,
+    callback: ResponseCallback,
+}
+
+/// The requests sent to the debugger that were not answered yet, by seq.
+#[derive(Default)]
+pub struct PendingRequests {
+    requests: BTreeMap<u64, PendingRequest>,
+}
+
+impl PendingRequests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, seq: u64, command: String, callback: ResponseCallback) {
+        // Launching and attaching can take as long as the debugger needs to load the program
+        let timeout = match command.as_str() {
+            "launch" | "attach" => None,
+            _ => Some(REQUEST_TIMEOUT),
+        };
+
+        self.requests.insert(
+            seq,
+            PendingRequest {
+                command,
+                sent_at: Instant::now(),
+                timeout,
+                callback,
+            },
+        );
+    }
+
+    /// Takes the request `response` answers, returning its callback along with what it should be
+    /// called with. Failed responses and the ones that don't match the request command become
+    /// errors.
+    ///
+    /// Returns the response back if there is no request waiting for it.
+    pub fn complete(&mut self, response: ResponseMessage) -> Result<Completion, ResponseMessage> {
+        let Some(request) = response
+            .request_seq()
+            .and_then(|seq| self.requests.remove(&seq))
+        else {
+            return Err(response);
+        };
+
+        let result = match response {
+            ResponseMessage::Generic {
+                success: false,
+                message,
+                body,
+                ..
+            } => {
+                // The body may have a more detailed error than the message
+                let message = body
+                    .as_ref()
+                    .and_then(|body| body.pointer("/error/format"))
+                    .and_then(|format| format.as_str())
+                    .map(String::from)
+                    .or(message)
+                    .unwrap_or_else(|| "unknown error".into());
+
+                Err(DapError::RequestFailed {
+                    command: request.command,
+                    message,
+                })
+            }
+            response if response.command() != Some(request.command.as_str()) => {
+                Err(DapError::UnexpectedResponse(format!(
+                    "expected a {} response, got {response:?}",
+                    request.command
+                )))
+            }
+            response => Ok(response),
+        };
+
+        Ok((request.callback, result))
+    }
+
+    /// Takes every request that waited for its response for too long, pushing to `out` their
+    /// callbacks along with the timeout error.
+    pub fn expire(&mut self, now: Instant, out: &mut Vec<Completion>) {
+        let expired: Vec<u64> = self
+            .requests
+            .iter()
+            .filter(|(_, request)| {
+                request
+                    .timeout
+                    .is_some_and(|timeout| now.duration_since(request.sent_at) >= timeout)
+            })
+            .map(|(seq, _)| *seq)
+            .collect();
+
+        for seq in expired {
+            if let Some(request) = self.requests.remove(&seq) {
+                log::warn!("Request {seq} ({}) timed out", request.command);
+                out.push((
+                    request.callback,
+                    Err(DapError::RequestTimedOut(request.command)),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PendingRequests, REQUEST_TIMEOUT, ResponseCallback};
+    use crate::dap::DapError;
+    use crate::dap::message::{ProtocolMessage, ResponseMessage};
+    use std::time::{Duration, Instant};
+
+    fn noop() -> ResponseCallback {
+        Box::new(|_, _| Ok(()))
+    }
+
+    #[test]
+    fn test_complete_requests() {
+        let mut pending = PendingRequests::new();
+        pending.insert(1, "evaluate".into(), noop());
+        pending.insert(2, "next".into(), noop());
+        pending.insert(3, "launch".into(), noop());
+
+        let failure = r#"{"seq": 10, "type": "response", "request_seq": 2, "success": false,
+            "command": "next", "message": "notStopped",
+            "body": {"error": {"id": 1, "format": "The program is not stopped"}}}"#;
+        let Ok(ProtocolMessage::Response(response)) = ProtocolMessage::decode(failure) else {
+            panic!("Failed to decode response");
+        };
+        let Ok((_, result)) = pending.complete(response) else {
+            panic!("The response was not matched to its request");
+        };
+        assert!(matches!(
+            result,
+            Err(DapError::RequestFailed { command, message })
+                if command == "next" && message == "The program is not stopped"
+        ));
+
+        // Nobody is waiting for this one anymore
+        let late = ResponseMessage::Generic {
+            request_seq: 2,
+            success: true,
+            command: "next".into(),
+            message: None,
+            body: None,
+        };
+        assert!(pending.complete(late).is_err());
+
+        let mut expired = Vec::new();
+        let later = Instant::now() + REQUEST_TIMEOUT + Duration::from_secs(1);
+        pending.expire(later, &mut expired);
+        assert_eq!(expired.len(), 1);
+        assert!(matches!(
+            &expired[0].1,
+            Err(DapError::RequestTimedOut(command)) if command == "evaluate"
+        ));
+
+        // Launching never times out
+        assert!(pending.requests.contains_key(&3));
+    }
+}
@@ -0,0 +1,319 @@
+use crate::dap::DapError;
+use serde::Deserialize;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::process::{Child, Stdio};
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::{Duration, Instant};
+
+/// How long we wait for a spawned adapter to start listening.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+const CONNECT_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How we reach the debug adapter.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DapTransport {
+    /// Spawn the adapter and talk through its stdin and stdout.
+    Stdio { command: PathBuf, args: Vec<String> },
+    /// Spawn the adapter and connect to the port it listens on. If `port` is None, we wait for
+    /// the adapter to print it.
+    SpawnAndConnect {
+        command: PathBuf,
+        args: Vec<String>,
+        host: String,
+        port: Option<u16>,
+    },
+    /// Connect to an adapter that is already listening.
+    Connect { host: String, port: u16 },
+}
+
+/// The `adapter` entry of a launch configuration. Every field is optional, which transport is
+/// used depends on which of them are present, see [DapTransport::from_launch_config].
+#[derive(Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+struct AdapterConfig {
+    command: Option<PathBuf>,
+    #[serde(default)]
+    args: Vec<String>,
+    host: Option<String>,
+    port: Option<u16>,
+    /// Take the port from what the adapter prints once it starts listening
+    #[serde(default)]
+    port_from_output: bool,
+}
+
+/// An open channel to the debug adapter.
+pub struct DapConnection {
+    /// The adapter process, if we spawned it
+    pub process: Option<Child>,
+    pub reader: Box<dyn BufRead + Send>,
+    pub writer: Box<dyn Write + Send>,
+    /// The port we connected to, if it's through TCP
+    pub port: Option<u16>,
+}
+
+impl DapTransport {
+    /// What we use when the launch configuration does not say.
+    pub fn default_adapter() -> Self {
+        DapTransport::Stdio {
+            command: "rust-gdb".into(),
+            args: vec!["-i".into(), "dap".into()],
+        }
+    }
+
+    /// Picks the transport from a launch configuration. It's described by its `adapter` entry:
+    ///
+    /// - `command` and `args` alone spawn the adapter and use its stdio.
+    /// - With a `port` too, the adapter is spawned and then connected to on that port. With
+    ///   `portFromOutput` instead, the port is taken from what the adapter prints.
+    /// - `port` alone, or the `debugServer` entry VS Code uses, connects to a running adapter.
+    ///
+    /// `host` defaults to localhost.
+    pub fn from_launch_config(config: &serde_json::Value) -> Result<Self, DapError> {
+        let adapter = match config.get("adapter") {
+            Some(adapter) => AdapterConfig::deserialize(adapter)
+                .map_err(|e| DapError::BadAdapterConfig(e.to_string()))?,
+            None => match config
+                .get("debugServer")
+                .and_then(serde_json::Value::as_u64)
+            {
+                Some(port) => AdapterConfig {
+                    port: u16::try_from(port).ok(),
+                    ..Default::default()
+                },
+                None => return Ok(Self::default_adapter()),
+            },
+        };
+
+        let host = adapter.host.unwrap_or_else(|| "localhost".into());
+        match (adapter.command, adapter.port) {
+            (Some(command), port) if port.is_some() || adapter.port_from_output => {
+                Ok(DapTransport::SpawnAndConnect {
+                    command,
+                    args: adapter.args,
+                    host,
+                    port,
+                })
+            }
+            (Some(command), _) => Ok(DapTransport::Stdio {
+                command,
+                args: adapter.args,
+            }),
+            (None, Some(port)) => Ok(DapTransport::Connect { host, port }),
+            (None, None) => Err(DapError::BadAdapterConfig(
+                "either a command or a port is needed".into(),
+            )),
+        }
+    }
+
+    /// How a child session reaches the same debugger as the session that went through `self`,
+    /// given the port that one connected to. Adapters we talk to through stdio are spawned
+    /// again, the ones listening on a port are connected to again.
+    pub fn for_child_session(&self, port: Option<u16>) -> Self {
+        match (self, port) {
+            (DapTransport::SpawnAndConnect { host, .. }, Some(port)) => DapTransport::Connect {
+                host: host.clone(),
+                port,
+            },
+            _ => self.clone(),
+        }
+    }
+
+    pub fn open(&self) -> Result<DapConnection, DapError> {
+        match self {
+            DapTransport::Stdio { command, args } => {
+                log::info!("Launching debugger {command:?} with arguments {args:?}");
+                let mut process = std::process::Command::new(command)
+                    .args(args)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .spawn()?;
+
+                let stdin = process.stdin.take().ok_or(DapError::NoStdin)?;
+                let stdout = process.stdout.take().ok_or(DapError::NoStdout)?;
+
+                Ok(DapConnection {
+                    process: Some(process),
+                    reader: Box::new(BufReader::new(stdout)),
+                    writer: Box::new(stdin),
+                    port: None,
+                })
+            }
+            DapTransport::SpawnAndConnect {
+                command,
+                args,
+                host,
+                port,
+            } => {
+                log::info!("Launching debugger {command:?} with arguments {args:?}");
+                let mut process = std::process::Command::new(command)
+                    .args(args)
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::piped())
+                    .spawn()?;
+                let stdout = process.stdout.take().ok_or(DapError::NoStdout)?;
+
+                // Whatever the adapter prints has to be drained anyway, or it could block once
+                // the pipe is full
+                let (tx, rx) = std::sync::mpsc::channel();
+                std::thread::spawn(move || {
+                    for line in BufReader::new(stdout).lines() {
+                        let Ok(line) = line else {
+                            break;
+                        };
+                        log::info!("Debug adapter says: {line}");
+                        if let Some(port) = port_in_line(&line) {
+                            let _ = tx.send(port);
+                        }
+                    }
+                });
+
+                let port = match port {
+                    Some(port) => *port,
+                    None => match rx.recv_timeout(CONNECT_TIMEOUT) {
+                        Ok(port) => port,
+                        Err(RecvTimeoutError::Timeout) => {
+                            let _ = process.kill();
+                            return Err(DapError::ConnectionFailed(
+                                "the debug adapter did not print the port it listens on".into(),
+                            ));
+                        }
+                        Err(RecvTimeoutError::Disconnected) => {
+                            let _ = process.wait();
+                            return Err(DapError::ConnectionFailed(
+                                "the debug adapter quit before listening".into(),
+                            ));
+                        }
+                    },
+                };
+
+                let stream = match connect(host, port) {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        let _ = process.kill();
+                        return Err(e);
+                    }
+                };
+                let mut connection = DapConnection::tcp(stream)?;
+                connection.process = Some(process);
+
+                Ok(connection)
+            }
+            DapTransport::Connect { host, port } => DapConnection::tcp(connect(host, *port)?),
+        }
+    }
+}
+
+impl DapConnection {
+    fn tcp(stream: TcpStream) -> Result<Self, DapError> {
+        stream.set_nodelay(true)?;
+
+        Ok(Self {
+            process: None,
+            port: Some(stream.peer_addr()?.port()),
+            reader: Box::new(BufReader::new(stream.try_clone()?)),
+            writer: Box::new(stream),
+        })
+    }
+}
+
+/// Connects to `host:port`, retrying for a while since a freshly spawned adapter may not be
+/// listening yet.
+fn connect(host: &str, port: u16) -> Result<TcpStream, DapError> {
+    log::info!("Connecting to debugger at {host}:{port}");
+    let deadline = Instant::now() + CONNECT_TIMEOUT;
+    loop {
+        match TcpStream::connect((host, port)) {
+            Ok(stream) => return Ok(stream),
+            Err(e) if Instant::now() >= deadline => {
+                return Err(DapError::ConnectionFailed(format!("{host}:{port}: {e}")));
+            }
+            Err(_) => std::thread::sleep(CONNECT_RETRY_INTERVAL),
+        }
+    }
+}
+
+/// Finds the port in a line like `Listening on port 4711` or
+/// `Listening for: connection://[::1]:4711`, which is the last number in it.
+fn port_in_line(line: &str) -> Option<u16> {
+    let lowercase = line.to_lowercase();
+    if !lowercase.contains("listen") && !lowercase.contains("port") {
+        return None;
+    }
+
+    line.split(|c: char| !c.is_ascii_digit())
+        .rfind(|digits| !digits.is_empty())
+        .and_then(|digits| digits.parse().ok())
+        .filter(|port| *port != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DapTransport, port_in_line};
+    use serde_json::json;
+
+    #[test]
+    fn test_port_in_line() {
+        assert_eq!(port_in_line("Listening on port 4711"), Some(4711));
+        assert_eq!(
+            port_in_line("Listening for: connection://[::1]:54321"),
+            Some(54321)
+        );
+        assert_eq!(port_in_line("Starting adapter 1.10.0"), None);
+        assert_eq!(port_in_line("Listening on port 99999"), None);
+    }
+
+    #[test]
+    fn test_transport_from_launch_config() {
+        assert_eq!(
+            DapTransport::from_launch_config(&json!({"request": "launch"})).unwrap(),
+            DapTransport::default_adapter()
+        );
+        assert_eq!(
+            DapTransport::from_launch_config(&json!({"debugServer": 4711})).unwrap(),
+            DapTransport::Connect {
+                host: "localhost".into(),
+                port: 4711
+            }
+        );
+        assert_eq!(
+            DapTransport::from_launch_config(&json!({
+                "adapter": {
+                    "command": "lldb-dap",
+                    "args": ["--connection", "listen://localhost:0"],
+                    "portFromOutput": true,
+                },
+            }))
+            .unwrap(),
+            DapTransport::SpawnAndConnect {
+                command: "lldb-dap".into(),
+                args: vec!["--connection".into(), "listen://localhost:0".into()],
+                host: "localhost".into(),
+                port: None,
+            }
+        );
+        assert!(DapTransport::from_launch_config(&json!({"adapter": {}})).is_err());
+    }
+
+    #[test]
+    fn test_transport_for_child_session() {
+        let spawned = DapTransport::SpawnAndConnect {
+            command: "js-debug".into(),
+            args: vec!["0".into()],
+            host: "localhost".into(),
+            port: None,
+        };
+        assert_eq!(
+            spawned.for_child_session(Some(4711)),
+            DapTransport::Connect {
+                host: "localhost".into(),
+                port: 4711
+            }
+        );
+
+        let stdio = DapTransport::default_adapter();
+        assert_eq!(stdio.for_child_session(None), stdio);
+    }
+}
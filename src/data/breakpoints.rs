@@ -1,13 +1,20 @@
-use egui::ahash::HashMap;
+use egui::ahash::{HashMap, HashSet};
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
-use crate::dap::message_types::Breakpoint as DapBreakpoint;
+use crate::dap::message_types::{
+    Breakpoint as DapBreakpoint, DataBreakpointAccessType, ExceptionBreakpointsFilter,
+};
+use crate::data::types::DebugPointer;
 
 #[derive(Clone, Debug)]
 pub enum Breakpoint {
-    Source(CodeBreakpoint)
+    Source(CodeBreakpoint),
+    Function(FunctionBreakpoint),
+    Data(DataBreakpoint),
+    Exception(ExceptionBreakpoint),
+    Instruction(InstructionBreakpoint),
 }
 
 impl Breakpoint {
@@ -15,9 +22,31 @@ impl Breakpoint {
         Self::Source(CodeBreakpoint {
             file: Arc::new(file.into()),
             lineno,
-            breakpoint_id: 0,
+            ..Default::default()
         })
     }
+
+    /// The id the debugger gave it, 0 until it answers.
+    pub fn breakpoint_id(&self) -> u64 {
+        match self {
+            Self::Source(code_bp) => code_bp.breakpoint_id,
+            Self::Function(function_bp) => function_bp.breakpoint_id,
+            Self::Data(data_bp) => data_bp.breakpoint_id,
+            Self::Exception(exception_bp) => exception_bp.breakpoint_id,
+            Self::Instruction(instruction_bp) => instruction_bp.breakpoint_id,
+        }
+    }
+
+    /// Forgets the id the debugger gave it, see [Self::breakpoint_id].
+    pub fn clear_breakpoint_id(&mut self) {
+        match self {
+            Self::Source(code_bp) => code_bp.breakpoint_id = 0,
+            Self::Function(function_bp) => function_bp.breakpoint_id = 0,
+            Self::Data(data_bp) => data_bp.breakpoint_id = 0,
+            Self::Exception(exception_bp) => exception_bp.breakpoint_id = 0,
+            Self::Instruction(instruction_bp) => instruction_bp.breakpoint_id = 0,
+        }
+    }
 }
 
 #[derive(Clone, Default, Debug)]
@@ -26,6 +55,84 @@ pub struct CodeBreakpoint {
     pub file: Arc<PathBuf>,
     pub lineno: usize,
     pub breakpoint_id: u64,
+    /// Only stop if this expression is true
+    pub condition: Option<String>,
+    /// Only stop once the breakpoint is hit this many times. How it's interpreted, like whether
+    /// `>5` is allowed, depends on the debugger.
+    pub hit_condition: Option<String>,
+    /// Print this instead of stopping, expressions within `{}` are interpolated
+    pub log_message: Option<String>,
+}
+
+impl CodeBreakpoint {
+    /// Whether it logs instead of stopping.
+    pub fn is_logpoint(&self) -> bool {
+        self.log_message.is_some()
+    }
+
+    /// Whether it may not stop every time it's hit.
+    pub fn is_conditional(&self) -> bool {
+        self.condition.is_some() || self.hit_condition.is_some()
+    }
+}
+
+/// Stops when a function is called, the debugger finds where it is.
+#[derive(Clone, Default, Debug)]
+pub struct FunctionBreakpoint {
+    /// Like `rust_panic` or `my_crate::Foo::bar`
+    pub name: String,
+    pub breakpoint_id: u64,
+    /// Only stop if this expression is true
+    pub condition: Option<String>,
+    /// Only stop once the breakpoint is hit this many times
+    pub hit_condition: Option<String>,
+}
+
+impl FunctionBreakpoint {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Stops when some data, like a variable or a range of memory, is accessed. These are usually
+/// hardware watchpoints, so there can only be a few.
+#[derive(Clone, Default, Debug)]
+pub struct DataBreakpoint {
+    /// What the debugger gave us to identify the data, from a dataBreakpointInfo response
+    pub data_id: String,
+    /// What the debugger said the data is, to show to the user
+    pub description: String,
+    pub access_type: DataBreakpointAccessType,
+    pub breakpoint_id: u64,
+}
+
+/// Stops when an exception is thrown, or the program panics. Which exceptions is up to the
+/// filter, one of the ones the debugger offers.
+#[derive(Clone, Default, Debug)]
+pub struct ExceptionBreakpoint {
+    pub filter_id: String,
+    /// Only stop if this expression is true
+    pub condition: Option<String>,
+    pub breakpoint_id: u64,
+}
+
+impl ExceptionBreakpoint {
+    pub fn new(filter_id: impl Into<String>) -> Self {
+        Self {
+            filter_id: filter_id.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Stops when the instruction at an address is about to run, set from the disassembly.
+#[derive(Clone, Default, Debug)]
+pub struct InstructionBreakpoint {
+    pub address: DebugPointer,
+    pub breakpoint_id: u64,
 }
 
 /// For each line of the file (usize), we can have a breakpoint
@@ -41,6 +148,17 @@ type DapBreakpointTable = HashMap<u64, DapBreakpoint>;
 pub struct BreakpointStore {
     /// And all the breakpoints are also protected
     points: RwLock<ProjectBreakpoints>,
+    /// In the order they were added, there is only one per function name
+    functions: RwLock<Vec<FunctionBreakpoint>>,
+    /// In the order they were added, there is only one per data id
+    watchpoints: RwLock<Vec<DataBreakpoint>>,
+    /// The exception filters that are enabled, there is only one per filter id
+    exceptions: RwLock<Vec<ExceptionBreakpoint>>,
+    /// Sorted by address, there is only one per address
+    instructions: RwLock<Vec<InstructionBreakpoint>>,
+    /// Exception filters the debugger offered at some point. Those that are enabled by default
+    /// are only enabled the first time, after that it's up to the user.
+    known_exception_filters: RwLock<HashSet<String>>,
     data: RwLock<DapBreakpointTable>,
 }
 
@@ -48,10 +166,43 @@ impl BreakpointStore {
     pub fn new() -> Self {
         Self {
             points: RwLock::new(HashMap::default()),
+            functions: RwLock::new(Vec::new()),
+            watchpoints: RwLock::new(Vec::new()),
+            exceptions: RwLock::new(Vec::new()),
+            instructions: RwLock::new(Vec::new()),
+            known_exception_filters: RwLock::new(HashSet::default()),
             data: RwLock::new(HashMap::default()),
         }
     }
 
+    /// A store with the same breakpoints, for another debugger. What this one's debugger said
+    /// about them is left behind, along with the ids it gave them. Data and instruction
+    /// breakpoints are about the memory of this one's debuggee, so they are left behind too.
+    pub fn copy_breakpoints(&self) -> Self {
+        let mut breakpoints = Vec::new();
+        let mut files = Vec::new();
+        let mut file_breakpoints = Vec::new();
+        self.get_files(&mut files);
+        for file in &files {
+            self.get_file_breakpoints(file, &mut file_breakpoints);
+            breakpoints.append(&mut file_breakpoints);
+        }
+        let functions = self.functions.read().unwrap();
+        breakpoints.extend(functions.iter().cloned().map(Breakpoint::Function));
+        let exceptions = self.exceptions.read().unwrap();
+        breakpoints.extend(exceptions.iter().cloned().map(Breakpoint::Exception));
+
+        let copy = Self::new();
+        for mut breakpoint in breakpoints {
+            breakpoint.clear_breakpoint_id();
+            copy.add(breakpoint);
+        }
+        *copy.known_exception_filters.write().unwrap() =
+            self.known_exception_filters.read().unwrap().clone();
+
+        copy
+    }
+
     pub fn add(&self, breakpoint: Breakpoint) {
         match breakpoint {
             Breakpoint::Source(code_bp) => {
@@ -70,6 +221,44 @@ impl BreakpointStore {
                     w_project_breakpoints.insert(file, RwLock::new(file_breakpoints));
                 }
             }
+            Breakpoint::Function(function_bp) => {
+                let mut w_functions = self.functions.write().unwrap();
+                match w_functions
+                    .iter_mut()
+                    .find(|bp| bp.name == function_bp.name)
+                {
+                    Some(existing) => *existing = function_bp,
+                    None => w_functions.push(function_bp),
+                }
+            }
+            Breakpoint::Data(data_bp) => {
+                let mut w_watchpoints = self.watchpoints.write().unwrap();
+                match w_watchpoints
+                    .iter_mut()
+                    .find(|bp| bp.data_id == data_bp.data_id)
+                {
+                    Some(existing) => *existing = data_bp,
+                    None => w_watchpoints.push(data_bp),
+                }
+            }
+            Breakpoint::Exception(exception_bp) => {
+                let mut w_exceptions = self.exceptions.write().unwrap();
+                match w_exceptions
+                    .iter_mut()
+                    .find(|bp| bp.filter_id == exception_bp.filter_id)
+                {
+                    Some(existing) => *existing = exception_bp,
+                    None => w_exceptions.push(exception_bp),
+                }
+            }
+            Breakpoint::Instruction(instruction_bp) => {
+                let mut w_instructions = self.instructions.write().unwrap();
+                let address = instruction_bp.address.as_usize();
+                match w_instructions.binary_search_by_key(&address, |bp| bp.address.as_usize()) {
+                    Ok(i) => w_instructions[i] = instruction_bp,
+                    Err(i) => w_instructions.insert(i, instruction_bp),
+                }
+            }
         }
     }
     
@@ -90,6 +279,30 @@ impl BreakpointStore {
                     return true;
                 }
             }
+            Breakpoint::Function(function_bp) => {
+                let mut w_functions = self.functions.write().unwrap();
+                let len = w_functions.len();
+                w_functions.retain(|bp| bp.name != function_bp.name);
+                return w_functions.len() != len;
+            }
+            Breakpoint::Data(data_bp) => {
+                let mut w_watchpoints = self.watchpoints.write().unwrap();
+                let len = w_watchpoints.len();
+                w_watchpoints.retain(|bp| bp.data_id != data_bp.data_id);
+                return w_watchpoints.len() != len;
+            }
+            Breakpoint::Exception(exception_bp) => {
+                let mut w_exceptions = self.exceptions.write().unwrap();
+                let len = w_exceptions.len();
+                w_exceptions.retain(|bp| bp.filter_id != exception_bp.filter_id);
+                return w_exceptions.len() != len;
+            }
+            Breakpoint::Instruction(instruction_bp) => {
+                let mut w_instructions = self.instructions.write().unwrap();
+                let len = w_instructions.len();
+                w_instructions.retain(|bp| bp.address != instruction_bp.address);
+                return w_instructions.len() != len;
+            }
         }
 
         false
@@ -111,6 +324,135 @@ impl BreakpointStore {
         }
     }
     
+    pub fn get_function_breakpoints(&self, out: &mut Vec<FunctionBreakpoint>) {
+        out.clear();
+
+        let functions_r = self.functions.read().unwrap();
+        out.extend(functions_r.iter().cloned());
+    }
+
+    /// Takes the breakpoints from a setFunctionBreakpoints response, which come in the same order
+    /// as `names`, the function breakpoints of the request.
+    pub fn set_function_breakpoint_data(&self, names: &[String], data: Vec<DapBreakpoint>) {
+        for (name, data) in names.iter().zip(data) {
+            let Some(id) = data.id else {
+                continue;
+            };
+
+            {
+                let mut functions_w = self.functions.write().unwrap();
+                if let Some(function_bp) = functions_w.iter_mut().find(|bp| &bp.name == name) {
+                    function_bp.breakpoint_id = id;
+                }
+            }
+
+            let mut data_w = self.data.write().unwrap();
+            data_w.insert(id, data);
+        }
+    }
+
+    pub fn get_data_breakpoints(&self, out: &mut Vec<DataBreakpoint>) {
+        out.clear();
+
+        let watchpoints_r = self.watchpoints.read().unwrap();
+        out.extend(watchpoints_r.iter().cloned());
+    }
+
+    /// Takes the breakpoints from a setDataBreakpoints response, which come in the same order
+    /// as `data_ids`, the data breakpoints of the request.
+    pub fn set_data_breakpoint_data(&self, data_ids: &[String], data: Vec<DapBreakpoint>) {
+        for (data_id, data) in data_ids.iter().zip(data) {
+            let Some(id) = data.id else {
+                continue;
+            };
+
+            {
+                let mut watchpoints_w = self.watchpoints.write().unwrap();
+                if let Some(data_bp) = watchpoints_w.iter_mut().find(|bp| &bp.data_id == data_id) {
+                    data_bp.breakpoint_id = id;
+                }
+            }
+
+            let mut data_w = self.data.write().unwrap();
+            data_w.insert(id, data);
+        }
+    }
+
+    pub fn get_exception_breakpoints(&self, out: &mut Vec<ExceptionBreakpoint>) {
+        out.clear();
+
+        let exceptions_r = self.exceptions.read().unwrap();
+        out.extend(exceptions_r.iter().cloned());
+    }
+
+    /// Enables the filters that are on by default, unless the debugger offered them before.
+    pub fn add_default_exception_breakpoints(&self, filters: &[ExceptionBreakpointsFilter]) {
+        let mut known_w = self.known_exception_filters.write().unwrap();
+        for filter in filters {
+            if known_w.insert(filter.filter.clone()) && filter.default.unwrap_or(false) {
+                self.add(Breakpoint::Exception(ExceptionBreakpoint::new(
+                    &filter.filter,
+                )));
+            }
+        }
+    }
+
+    /// Takes the breakpoints from a setExceptionBreakpoints response, which come in the same
+    /// order as `filter_ids`, the filters of the request.
+    pub fn set_exception_breakpoint_data(&self, filter_ids: &[String], data: Vec<DapBreakpoint>) {
+        for (filter_id, data) in filter_ids.iter().zip(data) {
+            let Some(id) = data.id else {
+                continue;
+            };
+
+            {
+                let mut exceptions_w = self.exceptions.write().unwrap();
+                if let Some(exception_bp) = exceptions_w
+                    .iter_mut()
+                    .find(|bp| &bp.filter_id == filter_id)
+                {
+                    exception_bp.breakpoint_id = id;
+                }
+            }
+
+            let mut data_w = self.data.write().unwrap();
+            data_w.insert(id, data);
+        }
+    }
+
+    pub fn get_instruction_breakpoints(&self, out: &mut Vec<InstructionBreakpoint>) {
+        out.clear();
+
+        let instructions_r = self.instructions.read().unwrap();
+        out.extend(instructions_r.iter().cloned());
+    }
+
+    /// Takes the breakpoints from a setInstructionBreakpoints response, which come in the same
+    /// order as `addresses`, the instruction breakpoints of the request.
+    pub fn set_instruction_breakpoint_data(
+        &self,
+        addresses: &[DebugPointer],
+        data: Vec<DapBreakpoint>,
+    ) {
+        for (address, data) in addresses.iter().zip(data) {
+            let Some(id) = data.id else {
+                continue;
+            };
+
+            {
+                let mut instructions_w = self.instructions.write().unwrap();
+                if let Some(instruction_bp) =
+                    instructions_w.iter_mut().find(|bp| &bp.address == address)
+                {
+                    instruction_bp.breakpoint_id = id;
+                }
+            }
+
+            let mut data_w = self.data.write().unwrap();
+            data_w.insert(id, data);
+        }
+    }
+
     pub fn get_files(&self, out: &mut Vec<PathBuf>) {
         out.clear();
 
@@ -160,6 +502,31 @@ impl BreakpointStore {
     }
 
     pub fn get_breakpoint_for_dap_id(&self, id: u64) -> Option<Breakpoint> {
+        {
+            let functions_r = self.functions.read().unwrap();
+            if let Some(function_bp) = functions_r.iter().find(|bp| bp.breakpoint_id == id) {
+                return Some(Breakpoint::Function(function_bp.clone()));
+            }
+        }
+        {
+            let watchpoints_r = self.watchpoints.read().unwrap();
+            if let Some(data_bp) = watchpoints_r.iter().find(|bp| bp.breakpoint_id == id) {
+                return Some(Breakpoint::Data(data_bp.clone()));
+            }
+        }
+        {
+            let exceptions_r = self.exceptions.read().unwrap();
+            if let Some(exception_bp) = exceptions_r.iter().find(|bp| bp.breakpoint_id == id) {
+                return Some(Breakpoint::Exception(exception_bp.clone()));
+            }
+        }
+        {
+            let instructions_r = self.instructions.read().unwrap();
+            if let Some(instruction_bp) = instructions_r.iter().find(|bp| bp.breakpoint_id == id) {
+                return Some(Breakpoint::Instruction(instruction_bp.clone()));
+            }
+        }
+
         let breakpoint_data = self.get_breakpoint_data(id)?;
         let source = breakpoint_data.source.as_ref()?.path.as_ref()?.as_str();
         let lineno = breakpoint_data.line?;
@@ -170,3 +537,131 @@ impl BreakpointStore {
         file_bps_r.get(&lineno).cloned().map(Breakpoint::Source)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Breakpoint, BreakpointStore, DataBreakpoint, ExceptionBreakpoint, FunctionBreakpoint,
+    };
+    use crate::dap::message_types::{DataBreakpointAccessType, ExceptionBreakpointsFilter};
+
+    #[test]
+    fn test_function_breakpoints() {
+        let store = BreakpointStore::new();
+        store.add(Breakpoint::Function(FunctionBreakpoint::new("rust_panic")));
+        store.add(Breakpoint::Function(FunctionBreakpoint::new("main")));
+        // Adding it again replaces it
+        store.add(Breakpoint::Function(FunctionBreakpoint::new("rust_panic")));
+
+        let mut list = Vec::new();
+        store.get_function_breakpoints(&mut list);
+        let names: Vec<String> = list.iter().map(|bp| bp.name.clone()).collect();
+        assert_eq!(names, ["rust_panic", "main"]);
+
+        let data = serde_json::from_value(serde_json::json!([
+            {"id": 7, "verified": true, "source": {"path": "/src/main.rs"}, "line": 3},
+            {"id": 8, "verified": false, "message": "No function rust_panic"},
+        ]))
+        .unwrap();
+        store.set_function_breakpoint_data(&names, data);
+
+        let Some(Breakpoint::Function(hit)) = store.get_breakpoint_for_dap_id(7) else {
+            panic!("The breakpoint was not found by its id");
+        };
+        assert_eq!(hit.name, "rust_panic");
+        assert!(!store.get_breakpoint_data(8).unwrap().verified);
+
+        assert!(store.remove(&Breakpoint::Function(hit)));
+        store.get_function_breakpoints(&mut list);
+        assert_eq!(list.len(), 1);
+    }
+
+    #[test]
+    fn test_data_breakpoints() {
+        let store = BreakpointStore::new();
+        let watchpoint = |data_id: &str, access_type| {
+            Breakpoint::Data(DataBreakpoint {
+                data_id: data_id.into(),
+                access_type,
+                ..Default::default()
+            })
+        };
+        store.add(watchpoint("0x1000/4", DataBreakpointAccessType::Write));
+        store.add(watchpoint("counter", DataBreakpointAccessType::Read));
+        // Changing the access type replaces it
+        store.add(watchpoint("0x1000/4", DataBreakpointAccessType::ReadWrite));
+
+        let mut list = Vec::new();
+        store.get_data_breakpoints(&mut list);
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].access_type, DataBreakpointAccessType::ReadWrite);
+
+        let data_ids: Vec<String> = list.iter().map(|bp| bp.data_id.clone()).collect();
+        let data = serde_json::from_value(serde_json::json!([
+            {"id": 3, "verified": true},
+            {"id": 4, "verified": false, "message": "Too many watchpoints"},
+        ]))
+        .unwrap();
+        store.set_data_breakpoint_data(&data_ids, data);
+
+        let Some(Breakpoint::Data(hit)) = store.get_breakpoint_for_dap_id(4) else {
+            panic!("The breakpoint was not found by its id");
+        };
+        assert_eq!(hit.data_id, "counter");
+
+        assert!(store.remove(&Breakpoint::Data(hit)));
+        store.get_data_breakpoints(&mut list);
+        assert_eq!(list.len(), 1);
+    }
+
+    #[test]
+    fn test_default_exception_breakpoints() {
+        let store = BreakpointStore::new();
+        let filter = |filter: &str, default| ExceptionBreakpointsFilter {
+            filter: filter.into(),
+            label: filter.into(),
+            default: Some(default),
+            ..Default::default()
+        };
+        let filters = [filter("rust_panic", true), filter("cpp_throw", false)];
+        store.add_default_exception_breakpoints(&filters);
+
+        let mut list = Vec::new();
+        store.get_exception_breakpoints(&mut list);
+        let ids: Vec<&str> = list.iter().map(|bp| bp.filter_id.as_str()).collect();
+        assert_eq!(ids, ["rust_panic"]);
+
+        // Once turned off, the next session does not turn it back on
+        let rust_panic = Breakpoint::Exception(ExceptionBreakpoint::new("rust_panic"));
+        assert!(store.remove(&rust_panic));
+        store.add_default_exception_breakpoints(&filters);
+        store.get_exception_breakpoints(&mut list);
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn test_copy_breakpoints() {
+        let store = BreakpointStore::new();
+        store.add(Breakpoint::Function(FunctionBreakpoint::new("main")));
+        let data =
+            serde_json::from_value(serde_json::json!([{"id": 7, "verified": true}])).unwrap();
+        store.set_function_breakpoint_data(&["main".to_owned()], data);
+        store.add(Breakpoint::Data(DataBreakpoint {
+            data_id: "0x1000/4".into(),
+            ..Default::default()
+        }));
+
+        // The other debugger knows nothing of the ids this one gave
+        let copy = store.copy_breakpoints();
+        let mut list = Vec::new();
+        copy.get_function_breakpoints(&mut list);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].breakpoint_id, 0);
+        assert!(copy.get_breakpoint_data(7).is_none());
+
+        // The data is that of the other debuggee
+        let mut watchpoints = Vec::new();
+        copy.get_data_breakpoints(&mut watchpoints);
+        assert!(watchpoints.is_empty());
+    }
+}
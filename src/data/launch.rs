@@ -0,0 +1,456 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::ffi::OsStr;
+use std::path::{MAIN_SEPARATOR_STR, Path, PathBuf};
+
+/// Where launch configurations are looked for, relative to the workspace folder. The first ones
+/// are listed first.
+pub const LAUNCH_CONFIG_FILES: [&str; 2] = [".memvisor/launch.json", ".vscode/launch.json"];
+
+/// The adapter id of configurations without a `type`, the one of the default adapter.
+pub const DEFAULT_ADAPTER_ID: &str = "rust-gdb";
+
+/// Whether the debugger starts the program, or attaches to one that is already running.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LaunchRequest {
+    #[default]
+    Launch,
+    Attach,
+}
+
+/// A configuration from a `launch.json`. The whole configuration is the body of the launch or
+/// attach request, once its variables are substituted.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LaunchConfig {
+    pub name: String,
+    pub request: LaunchRequest,
+    pub body: Value,
+}
+
+#[derive(Deserialize)]
+struct LaunchFile {
+    #[serde(default)]
+    configurations: Vec<Value>,
+}
+
+impl LaunchConfig {
+    /// What we use when there is no `launch.json` around.
+    pub fn fallback() -> Self {
+        Self {
+            name: "Default".into(),
+            request: LaunchRequest::Launch,
+            body: json!({
+                "name": "Default",
+                "type": DEFAULT_ADAPTER_ID,
+                "request": "launch",
+                "program": "${workspaceFolder}/target/debug/memvisor",
+                "cwd": "${workspaceFolder}",
+            }),
+        }
+    }
+
+    fn from_value(body: Value) -> Result<Self, String> {
+        let name = body
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or("configuration without a name")?
+            .to_owned();
+        let request = match body.get("request") {
+            Some(request) => LaunchRequest::deserialize(request)
+                .map_err(|e| format!("configuration \"{name}\": {e}"))?,
+            None => LaunchRequest::Launch,
+        };
+
+        Ok(Self {
+            name,
+            request,
+            body,
+        })
+    }
+
+    /// The body of the request, with its variables substituted.
+    pub fn resolve(&self, variables: &LaunchVariables) -> Result<Value, String> {
+        variables.substitute(&self.body)
+    }
+
+    /// Whether the variable `name` is used anywhere in the body, like `${name}`.
+    pub fn uses_variable(&self, name: &str) -> bool {
+        self.body.to_string().contains(&format!("${{{name}}}"))
+    }
+}
+
+/// The id the debugger is told it has in the initialize request. Like in VS Code, it's the
+/// `type` of the configuration.
+pub fn adapter_id(body: &Value) -> &str {
+    body.get("type")
+        .and_then(Value::as_str)
+        .unwrap_or(DEFAULT_ADAPTER_ID)
+}
+
+/// The `name` of a configuration, if it has one.
+pub fn config_name(body: &Value) -> Option<&str> {
+    body.get("name").and_then(Value::as_str)
+}
+
+/// Makes the body of a configuration load a core dump of `executable` instead, the way the
+/// debugger of its `type` does it. Returns the request that loads it, if one is needed at all.
+///
+/// - `lldb-dap` attaches to the core with `coreFile`.
+/// - `cppdbg` launches it with `coreDumpPath`.
+/// - Any other is taken to be gdb, which takes the executable and the core on its command line,
+///   so it has to be an adapter we spawn.
+pub fn core_dump_body(
+    body: &Value,
+    executable: &Path,
+    core: &Path,
+) -> Result<(Option<LaunchRequest>, Value), String> {
+    let mut body = body.clone();
+    let entries = body
+        .as_object_mut()
+        .ok_or("the configuration is not an object")?;
+    let executable = lossy(executable);
+    let core = lossy(core);
+
+    let request = match entries.get("type").and_then(Value::as_str) {
+        Some("lldb-dap") => {
+            entries.insert("program".into(), executable.into());
+            entries.insert("coreFile".into(), core.into());
+            Some(LaunchRequest::Attach)
+        }
+        Some("cppdbg") => {
+            entries.insert("program".into(), executable.into());
+            entries.insert("coreDumpPath".into(), core.into());
+            Some(LaunchRequest::Launch)
+        }
+        _ => {
+            if entries.contains_key("debugServer") && !entries.contains_key("adapter") {
+                return Err("gdb can only load a core dump if MemVisor starts it".into());
+            }
+            let adapter = entries
+                .entry("adapter")
+                .or_insert_with(|| json!({ "command": "rust-gdb", "args": ["-i", "dap"] }));
+            if adapter.get("command").is_none() {
+                return Err("gdb can only load a core dump if MemVisor starts it".into());
+            }
+            let args = adapter
+                .as_object_mut()
+                .ok_or("the adapter is not an object")?
+                .entry("args")
+                .or_insert_with(|| json!([]));
+            let args = args
+                .as_array_mut()
+                .ok_or("the adapter arguments are not a list")?;
+            args.push(executable.into());
+            args.push(core.into());
+            None
+        }
+    };
+    entries.insert(
+        "request".into(),
+        match request {
+            Some(LaunchRequest::Launch) => "launch",
+            _ => "attach",
+        }
+        .into(),
+    );
+
+    Ok((request, body))
+}
+
+/// Reads the configurations from every launch file in the workspace. A file that can't be read
+/// or parsed is skipped, and the reason pushed to `errors`.
+pub fn load_launch_configs(workspace_folder: &Path, errors: &mut Vec<String>) -> Vec<LaunchConfig> {
+    let mut configs = Vec::new();
+
+    for file in LAUNCH_CONFIG_FILES {
+        let path = workspace_folder.join(file);
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => {
+                errors.push(format!("{file}: {e}"));
+                continue;
+            }
+        };
+
+        match parse_launch_file(&text) {
+            Ok(file_configs) => configs.extend(file_configs),
+            Err(e) => errors.push(format!("{file}: {e}")),
+        }
+    }
+
+    configs
+}
+
+/// Parses a `launch.json`, which like the rest of the VS Code configuration may have comments
+/// and trailing commas.
+pub fn parse_launch_file(text: &str) -> Result<Vec<LaunchConfig>, String> {
+    let file: LaunchFile = serde_json::from_str(&strip_jsonc(text)).map_err(|e| e.to_string())?;
+
+    file.configurations
+        .into_iter()
+        .map(LaunchConfig::from_value)
+        .collect()
+}
+
+/// Turns JSON with comments into plain JSON, by dropping the comments and trailing commas.
+fn strip_jsonc(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    // Where the last comma outside a string went in `out`, until something else than
+    // whitespace or comments shows up after it
+    let mut pending_comma = None;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                pending_comma = None;
+                out.push(c);
+                while let Some(c) = chars.next() {
+                    out.push(c);
+                    match c {
+                        '\\' => out.extend(chars.next()),
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push(c);
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = '\0';
+                for c in chars.by_ref() {
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            ',' => {
+                pending_comma = Some(out.len());
+                out.push(c);
+            }
+            '}' | ']' => {
+                if let Some(comma) = pending_comma.take() {
+                    out.replace_range(comma..comma + 1, " ");
+                }
+                out.push(c);
+            }
+            c if c.is_whitespace() => out.push(c),
+            c => {
+                pending_comma = None;
+                out.push(c);
+            }
+        }
+    }
+
+    out
+}
+
+/// The values for the `${...}` variables of launch configurations.
+#[derive(Clone, Default, Debug)]
+pub struct LaunchVariables {
+    pub workspace_folder: PathBuf,
+    /// The file open in the focused source tab, if any
+    pub file: Option<PathBuf>,
+    /// The terminal opened for the debuggee, for the debuggers that take a tty to run it in
+    pub terminal_tty: Option<PathBuf>,
+}
+
+impl LaunchVariables {
+    /// Substitutes the variables in every string of `value`.
+    pub fn substitute(&self, value: &Value) -> Result<Value, String> {
+        Ok(match value {
+            Value::String(s) => Value::String(self.substitute_str(s)?),
+            Value::Array(values) => Value::Array(
+                values
+                    .iter()
+                    .map(|value| self.substitute(value))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Object(entries) => Value::Object(
+                entries
+                    .iter()
+                    .map(|(key, value)| Ok((key.clone(), self.substitute(value)?)))
+                    .collect::<Result<_, String>>()?,
+            ),
+            value => value.clone(),
+        })
+    }
+
+    fn substitute_str(&self, s: &str) -> Result<String, String> {
+        let mut out = String::with_capacity(s.len());
+        let mut rest = s;
+
+        while let Some(start) = rest.find("${") {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let end = after
+                .find('}')
+                .ok_or_else(|| format!("Unterminated variable in \"{s}\""))?;
+            out.push_str(&self.variable(&after[..end])?);
+            rest = &after[end + 1..];
+        }
+        out.push_str(rest);
+
+        Ok(out)
+    }
+
+    fn variable(&self, name: &str) -> Result<String, String> {
+        if let Some(env_name) = name.strip_prefix("env:") {
+            // Like VS Code, unset variables are just empty
+            return Ok(std::env::var(env_name).unwrap_or_default());
+        }
+
+        let file = || {
+            self.file
+                .as_deref()
+                .ok_or_else(|| format!("${{{name}}} needs a source file to be focused"))
+        };
+        let file_name = |path: &Path| path.file_name().map(lossy).unwrap_or_default();
+
+        Ok(match name {
+            "workspaceFolder" => lossy(&self.workspace_folder),
+            "workspaceFolderBasename" => file_name(&self.workspace_folder),
+            "cwd" => lossy(std::env::current_dir().map_err(|e| e.to_string())?),
+            "userHome" => std::env::var("HOME").unwrap_or_default(),
+            "pathSeparator" | "/" => MAIN_SEPARATOR_STR.into(),
+            "file" => lossy(file()?),
+            "fileBasename" => file_name(file()?),
+            "fileBasenameNoExtension" => file()?.file_stem().map(lossy).unwrap_or_default(),
+            "fileExtname" => file()?
+                .extension()
+                .map(|ext| format!(".{}", ext.to_string_lossy()))
+                .unwrap_or_default(),
+            "fileDirname" => file()?.parent().map(lossy).unwrap_or_default(),
+            "terminalTty" => lossy(
+                self.terminal_tty
+                    .as_deref()
+                    .ok_or_else(|| format!("${{{name}}} needs a terminal to be opened"))?,
+            ),
+            "relativeFile" => {
+                let file = file()?;
+                lossy(file.strip_prefix(&self.workspace_folder).unwrap_or(file))
+            }
+            _ => return Err(format!("Unknown variable ${{{name}}}")),
+        })
+    }
+}
+
+fn lossy(s: impl AsRef<OsStr>) -> String {
+    s.as_ref().to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DEFAULT_ADAPTER_ID, LaunchConfig, LaunchRequest, LaunchVariables, adapter_id,
+        core_dump_body, parse_launch_file,
+    };
+    use serde_json::json;
+    use std::path::Path;
+
+    #[test]
+    fn test_parse_launch_file() {
+        let text = r#"{
+            // Comments are allowed, even with "quotes"
+            "version": "0.2.0",
+            "configurations": [
+                {
+                    "name": "Attach /* not a comment */",
+                    "request": "attach",
+                    "pid": 1234, /* trailing commas too */
+                },
+            ],
+        }"#;
+
+        let configs = parse_launch_file(text).unwrap();
+        assert_eq!(configs.len(), 1);
+        assert_eq!(configs[0].name, "Attach /* not a comment */");
+        assert_eq!(configs[0].request, LaunchRequest::Attach);
+        assert_eq!(configs[0].body["pid"], 1234);
+
+        assert_eq!(adapter_id(&configs[0].body), DEFAULT_ADAPTER_ID);
+        assert_eq!(adapter_id(&json!({"type": "lldb-dap"})), "lldb-dap");
+        assert_eq!(
+            adapter_id(&LaunchConfig::fallback().body),
+            DEFAULT_ADAPTER_ID
+        );
+    }
+
+    #[test]
+    fn test_substitute_launch_variables() {
+        let variables = LaunchVariables {
+            workspace_folder: "/home/user/project".into(),
+            file: Some("/home/user/project/src/main.rs".into()),
+            terminal_tty: Some("/dev/pts/7".into()),
+        };
+
+        let body = json!({
+            "program": "${workspaceFolder}/target/debug/${workspaceFolderBasename}",
+            "args": ["${relativeFile}", "${fileBasenameNoExtension}${fileExtname}"],
+            "stdio": ["${terminalTty}"],
+            "port": 4711,
+        });
+        assert_eq!(
+            variables.substitute(&body),
+            Ok(json!({
+                "program": "/home/user/project/target/debug/project",
+                "args": ["src/main.rs", "main.rs"],
+                "stdio": ["/dev/pts/7"],
+                "port": 4711,
+            }))
+        );
+
+        let config = LaunchConfig {
+            name: "Launch".into(),
+            request: LaunchRequest::Launch,
+            body,
+        };
+        assert!(config.uses_variable("terminalTty"));
+        assert!(!config.uses_variable("file"));
+
+        assert!(variables.substitute(&json!("${nope}")).is_err());
+        let no_file = LaunchVariables {
+            file: None,
+            terminal_tty: None,
+            ..variables
+        };
+        assert!(no_file.substitute(&json!("${file}")).is_err());
+        assert!(no_file.substitute(&json!("${terminalTty}")).is_err());
+    }
+
+    #[test]
+    fn test_core_dump_body() {
+        let executable = Path::new("/srv/service");
+        let core = Path::new("/var/crash/core.1234");
+
+        let (request, body) =
+            core_dump_body(&json!({"type": "gdb", "name": "Gdb"}), executable, core).unwrap();
+        assert_eq!(request, None);
+        assert_eq!(
+            body["adapter"],
+            json!({
+                "command": "rust-gdb",
+                "args": ["-i", "dap", "/srv/service", "/var/crash/core.1234"],
+            })
+        );
+
+        let (request, body) =
+            core_dump_body(&json!({"type": "lldb-dap"}), executable, core).unwrap();
+        assert_eq!(request, Some(LaunchRequest::Attach));
+        assert_eq!(body["coreFile"], "/var/crash/core.1234");
+
+        // gdb can't be told about the core once it's running
+        assert!(core_dump_body(&json!({"debugServer": 4711}), executable, core).is_err());
+        assert!(core_dump_body(&json!({"adapter": {"port": 4711}}), executable, core).is_err());
+    }
+}
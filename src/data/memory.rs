@@ -0,0 +1,409 @@
+use egui::ahash::HashMap;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::data::types::DebugPointer;
+
+/// Memory is fetched from the debugger in chunks of this many bytes, aligned to this size.
+pub const MEMORY_CHUNK_SIZE: usize = 256;
+
+/// Past this amount of cached chunks we just drop everything and start over.
+const MAX_CACHED_CHUNKS: usize = 4096;
+
+/// What we know about a single byte of the debuggee memory.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum MemoryByte {
+    /// Not fetched yet, or currently being fetched.
+    #[default]
+    Unknown,
+    /// The debugger told us this byte can't be read.
+    Unreadable,
+    Value(u8),
+}
+
+#[derive(Clone, Debug)]
+enum MemoryChunk {
+    Pending,
+    /// Read up to some unreadable bytes, the rest is being read from where they end
+    Resuming(Box<[MemoryByte; MEMORY_CHUNK_SIZE]>),
+    Loaded(Box<[MemoryByte; MEMORY_CHUNK_SIZE]>),
+}
+
+/// A cache of the debuggee memory, filled on demand by readMemory requests.
+///
+/// Responses to reads sent before the cache was last invalidated are recognized by their
+/// generation, and discarded.
+#[derive(Default)]
+pub struct MemoryStore {
+    /// Chunks by their (aligned) start address
+    chunks: RwLock<HashMap<usize, MemoryChunk>>,
+    /// Bumped every time the whole cache is invalidated
+    generation: AtomicU64,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub const fn chunk_address(address: usize) -> usize {
+        address - address % MEMORY_CHUNK_SIZE
+    }
+
+    /// Fills `out` with whatever we know of the memory starting at `address`, and pushes to
+    /// `missing` the address of each chunk that was never requested. Returns whether some of it
+    /// is still being read.
+    pub fn get(
+        &self,
+        address: DebugPointer,
+        out: &mut [MemoryByte],
+        missing: &mut Vec<usize>,
+    ) -> bool {
+        missing.clear();
+        let mut pending = false;
+
+        let chunks_r = self.chunks.read().unwrap();
+        let mut address = address.as_usize();
+        let mut filled = 0;
+        while filled < out.len() {
+            let chunk_address = Self::chunk_address(address);
+            let chunk_offset = address - chunk_address;
+            let len = (MEMORY_CHUNK_SIZE - chunk_offset).min(out.len() - filled);
+            let dest = &mut out[filled..filled + len];
+
+            match chunks_r.get(&chunk_address) {
+                Some(MemoryChunk::Loaded(bytes)) => {
+                    dest.copy_from_slice(&bytes[chunk_offset..chunk_offset + len]);
+                }
+                Some(MemoryChunk::Resuming(bytes)) => {
+                    dest.copy_from_slice(&bytes[chunk_offset..chunk_offset + len]);
+                    pending = true;
+                }
+                Some(MemoryChunk::Pending) => {
+                    dest.fill(MemoryByte::Unknown);
+                    pending = true;
+                }
+                None => {
+                    dest.fill(MemoryByte::Unknown);
+                    missing.push(chunk_address);
+                }
+            }
+
+            filled += len;
+            match address.checked_add(len) {
+                Some(next) => address = next,
+                None => {
+                    // The end of the address space, nothing after it
+                    out[filled..].fill(MemoryByte::Unreadable);
+                    break;
+                }
+            }
+        }
+
+        pending
+    }
+
+    /// Marks a chunk as being fetched. Returns the generation the read should be completed
+    /// with.
+    pub fn set_pending(&self, chunk_address: usize) -> u64 {
+        let mut chunks_w = self.chunks.write().unwrap();
+        if chunks_w.len() >= MAX_CACHED_CHUNKS {
+            chunks_w.clear();
+        }
+        chunks_w.insert(chunk_address, MemoryChunk::Pending);
+
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Completes the read of the chunk at `chunk_address`. `data` is the memory starting at
+    /// `address`, followed by `unreadable_bytes` that can't be read. Whatever is before it in
+    /// the chunk and was not read yet is unreadable too. So is what comes after, unless there
+    /// were unreadable bytes: readable memory may follow them, so the chunk is left to be read
+    /// again from where they end, see [Self::resume_address].
+    ///
+    /// Returns false if the read is stale, in which case it is discarded.
+    pub fn complete_read(
+        &self,
+        generation: u64,
+        chunk_address: usize,
+        address: DebugPointer,
+        data: &[u8],
+        unreadable_bytes: usize,
+    ) -> bool {
+        let mut chunks_w = self.chunks.write().unwrap();
+        if self.generation.load(Ordering::Acquire) != generation {
+            return false;
+        }
+        let mut bytes = match chunks_w.get(&chunk_address) {
+            Some(MemoryChunk::Pending) => Box::new([MemoryByte::Unknown; MEMORY_CHUNK_SIZE]),
+            Some(MemoryChunk::Resuming(bytes)) => bytes.clone(),
+            _ => return false,
+        };
+
+        let unknown_before = count_unknown(&bytes);
+        let address = address.as_usize();
+        let unreadable_end = address
+            .saturating_add(data.len())
+            .saturating_add(unreadable_bytes);
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            let byte_address = chunk_address + i;
+            match byte_address
+                .checked_sub(address)
+                .and_then(|data_offset| data.get(data_offset))
+            {
+                Some(value) => *byte = MemoryByte::Value(*value),
+                None if byte_address < unreadable_end && *byte == MemoryByte::Unknown => {
+                    *byte = MemoryByte::Unreadable;
+                }
+                None => {}
+            }
+        }
+
+        // Each read has to learn something, a debugger answering the same thing over and over
+        // would have us asking forever
+        let resumes = unreadable_bytes > 0
+            && unreadable_end < chunk_address.saturating_add(MEMORY_CHUNK_SIZE)
+            && count_unknown(&bytes) < unknown_before;
+        if resumes {
+            chunks_w.insert(chunk_address, MemoryChunk::Resuming(bytes));
+        } else {
+            for byte in bytes.iter_mut() {
+                if *byte == MemoryByte::Unknown {
+                    *byte = MemoryByte::Unreadable;
+                }
+            }
+            chunks_w.insert(chunk_address, MemoryChunk::Loaded(bytes));
+        }
+
+        true
+    }
+
+    /// Marks whatever was not read of the chunk as unreadable.
+    pub fn fail_read(&self, generation: u64, chunk_address: usize) -> bool {
+        self.complete_read(generation, chunk_address, DebugPointer::null(), &[], 0)
+    }
+
+    /// Where the chunk at `chunk_address` is to be read again from, if [Self::complete_read]
+    /// left some of it to be read past unreadable bytes.
+    pub fn resume_address(&self, chunk_address: usize) -> Option<usize> {
+        match self.chunks.read().unwrap().get(&chunk_address) {
+            Some(MemoryChunk::Resuming(bytes)) => bytes
+                .iter()
+                .position(|byte| *byte == MemoryByte::Unknown)
+                .map(|offset| chunk_address + offset),
+            _ => None,
+        }
+    }
+
+    /// Forgets the cached memory in the given range, it will be fetched again when needed.
+    pub fn invalidate_range(&self, address: usize, len: usize) {
+        if len == 0 {
+            return;
+        }
+
+        let first_chunk = Self::chunk_address(address);
+        let last_chunk = Self::chunk_address(address.saturating_add(len - 1));
+
+        let mut chunks_w = self.chunks.write().unwrap();
+        for chunk_address in (first_chunk..=last_chunk).step_by(MEMORY_CHUNK_SIZE) {
+            chunks_w.remove(&chunk_address);
+        }
+    }
+
+    /// Forgets all the cached memory, it will be fetched again when needed.
+    pub fn invalidate(&self) {
+        let mut chunks_w = self.chunks.write().unwrap();
+        chunks_w.clear();
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+}
+
+fn count_unknown(bytes: &[MemoryByte; MEMORY_CHUNK_SIZE]) -> usize {
+    bytes
+        .iter()
+        .filter(|byte| **byte == MemoryByte::Unknown)
+        .count()
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+/// How the user typed the value to be written to memory.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MemoryEditFormat {
+    /// Raw bytes in hex, like `de ad be ef`.
+    Hex,
+    /// An integer of `width` bytes. Decimal or `0x` prefixed hex, and may be negative.
+    Integer {
+        width: usize,
+        endianness: Endianness,
+    },
+    /// The UTF-8 bytes of the string, without any terminator.
+    String,
+}
+
+impl MemoryEditFormat {
+    /// Turns the user input into the bytes to be written.
+    pub fn encode(self, input: &str) -> Result<Vec<u8>, String> {
+        match self {
+            MemoryEditFormat::Hex => {
+                let digits: Vec<u8> = input.bytes().filter(|c| !c.is_ascii_whitespace()).collect();
+                if !digits.len().is_multiple_of(2) {
+                    return Err("Odd number of hex digits".into());
+                }
+
+                digits
+                    .chunks(2)
+                    .map(|pair| {
+                        std::str::from_utf8(pair)
+                            .ok()
+                            .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                            .ok_or_else(|| format!("Invalid hex byte: {}", pair.escape_ascii()))
+                    })
+                    .collect()
+            }
+            MemoryEditFormat::Integer { width, endianness } => {
+                let input = input.trim();
+                let (negative, magnitude) = match input.strip_prefix('-') {
+                    Some(magnitude) => (true, magnitude),
+                    None => (false, input),
+                };
+                let magnitude = match magnitude.strip_prefix("0x") {
+                    Some(hex) => u128::from_str_radix(hex, 16),
+                    None => magnitude.parse(),
+                }
+                .map_err(|e| format!("Invalid integer: {e}"))?;
+
+                let bits = width as u32 * 8;
+                let fits = if negative {
+                    magnitude <= 1 << (bits - 1)
+                } else {
+                    magnitude <= u128::MAX >> (128 - bits)
+                };
+                if !fits {
+                    return Err(format!("{input} does not fit in {width} bytes"));
+                }
+
+                let value = if negative {
+                    magnitude.wrapping_neg()
+                } else {
+                    magnitude
+                };
+                let mut bytes = value.to_le_bytes()[..width].to_vec();
+                if endianness == Endianness::Big {
+                    bytes.reverse();
+                }
+
+                Ok(bytes)
+            }
+            MemoryEditFormat::String => Ok(input.as_bytes().to_vec()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Endianness, MEMORY_CHUNK_SIZE, MemoryByte, MemoryEditFormat, MemoryStore};
+    use crate::data::types::DebugPointer;
+
+    #[test]
+    fn test_partially_readable_chunk() {
+        let store = MemoryStore::new();
+        let chunk = 0x1000;
+        let generation = store.set_pending(chunk);
+
+        // Only the second half of the chunk is readable
+        let half = MEMORY_CHUNK_SIZE / 2;
+        let data = vec![0xab; half];
+        let address = DebugPointer::new(chunk + half);
+        assert!(store.complete_read(generation, chunk, address, &data, 0));
+
+        let mut out = [MemoryByte::Unknown; 4];
+        let mut missing = Vec::new();
+        let pending = store.get(DebugPointer::new(chunk + half - 2), &mut out, &mut missing);
+
+        assert!(!pending);
+        assert!(missing.is_empty());
+        assert_eq!(
+            out,
+            [
+                MemoryByte::Unreadable,
+                MemoryByte::Unreadable,
+                MemoryByte::Value(0xab),
+                MemoryByte::Value(0xab),
+            ]
+        );
+
+        // Reading past the chunk asks for the next one
+        store.get(
+            DebugPointer::new(chunk + MEMORY_CHUNK_SIZE - 1),
+            &mut out,
+            &mut missing,
+        );
+        assert_eq!(missing, vec![chunk + MEMORY_CHUNK_SIZE]);
+
+        // Reads sent before the program ran again are stale
+        let next_chunk = chunk + MEMORY_CHUNK_SIZE;
+        let stale = store.set_pending(next_chunk);
+        store.invalidate();
+        let generation = store.set_pending(next_chunk);
+        assert!(store.get(DebugPointer::new(next_chunk), &mut out, &mut missing));
+        assert!(!store.fail_read(stale, next_chunk));
+        assert!(store.fail_read(generation, next_chunk));
+
+        // Readable memory can follow the unreadable bytes, it's read from where they end
+        let chunk = 0x2000;
+        let generation = store.set_pending(chunk);
+        let address = DebugPointer::new(chunk);
+        assert!(store.complete_read(generation, chunk, address, &[1, 2], 2));
+        assert_eq!(store.resume_address(chunk), Some(chunk + 4));
+        let mut out = [MemoryByte::Unknown; 6];
+        assert!(store.get(address, &mut out, &mut missing));
+        assert!(missing.is_empty());
+        assert_eq!(
+            out[..4],
+            [
+                MemoryByte::Value(1),
+                MemoryByte::Value(2),
+                MemoryByte::Unreadable,
+                MemoryByte::Unreadable,
+            ]
+        );
+        assert_eq!(out[4], MemoryByte::Unknown);
+
+        let resumed = DebugPointer::new(chunk + 4);
+        assert!(store.complete_read(generation, chunk, resumed, &[3], 0));
+        assert_eq!(store.resume_address(chunk), None);
+        assert!(!store.get(address, &mut out, &mut missing));
+        assert_eq!(out[4], MemoryByte::Value(3));
+        assert_eq!(out[5], MemoryByte::Unreadable);
+    }
+
+    #[test]
+    fn test_encode_memory_edit() {
+        assert_eq!(
+            MemoryEditFormat::Hex.encode("de ad BEEF"),
+            Ok(vec![0xde, 0xad, 0xbe, 0xef])
+        );
+        assert!(MemoryEditFormat::Hex.encode("abc").is_err());
+
+        let i32_le = MemoryEditFormat::Integer {
+            width: 4,
+            endianness: Endianness::Little,
+        };
+        assert_eq!(i32_le.encode("-2"), Ok(vec![0xfe, 0xff, 0xff, 0xff]));
+        assert_eq!(i32_le.encode("0xffffffff"), Ok(vec![0xff; 4]));
+        assert!(i32_le.encode("0x100000000").is_err());
+
+        let u16_be = MemoryEditFormat::Integer {
+            width: 2,
+            endianness: Endianness::Big,
+        };
+        assert_eq!(u16_be.encode("0x1234"), Ok(vec![0x12, 0x34]));
+        assert!(u16_be.encode("-32769").is_err());
+    }
+}
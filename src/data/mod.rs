@@ -1,2 +1,12 @@
 pub mod breakpoints;
+pub mod launch;
+pub mod memory;
+pub mod output;
+pub mod processes;
+pub mod pty;
+pub mod stack;
+pub mod terminal;
+pub mod threads;
 pub mod types;
+pub mod variables;
+pub mod watch;
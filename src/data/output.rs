@@ -0,0 +1,362 @@
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use crate::dap::message_types::OutputEventCategory;
+
+/// How many lines of output are kept. Past that, the oldest ones are dropped.
+pub const MAX_OUTPUT_LINES: usize = 10_000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OutputLineKind {
+    /// Printed by the debugger or the debuggee
+    Text,
+    /// The header of a group, the lines after it are nested in it until the group ends
+    GroupStart { collapsed: bool },
+    /// An expression typed in the console
+    Input,
+    /// What an expression typed in the console evaluated to
+    Result,
+}
+
+#[derive(Clone, Debug)]
+pub struct OutputLine {
+    /// Identifies the line for as long as it's kept
+    pub id: u64,
+    pub category: OutputEventCategory,
+    pub kind: OutputLineKind,
+    pub text: String,
+    /// Where the output came from, like the logpoint that printed it
+    pub location: Option<(PathBuf, usize)>,
+    /// How many groups the line is nested in
+    pub depth: usize,
+    /// If this is > 0, the output is structured, and its children can be retrieved with the
+    /// variables request
+    pub variables_reference: u64,
+    /// Whether the line ended, otherwise the next output of its category goes after it
+    ended: bool,
+}
+
+#[derive(Default)]
+struct OutputLines {
+    lines: VecDeque<OutputLine>,
+    /// The ids of the lines not hidden in a collapsed group, in order
+    visible: VecDeque<u64>,
+    /// The groups new lines go in, by the id of their header, and whether they are collapsed
+    open_groups: Vec<(u64, bool)>,
+    next_id: u64,
+}
+
+impl OutputLines {
+    fn index_of(&self, id: u64) -> Option<usize> {
+        let first_id = self.lines.front()?.id;
+        let index = usize::try_from(id.checked_sub(first_id)?).ok()?;
+        (index < self.lines.len()).then_some(index)
+    }
+
+    fn push_line(
+        &mut self,
+        category: OutputEventCategory,
+        kind: OutputLineKind,
+        text: &str,
+        location: Option<(PathBuf, usize)>,
+        variables_reference: u64,
+        ended: bool,
+    ) {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        if !self.open_groups.iter().any(|(_, collapsed)| *collapsed) {
+            self.visible.push_back(id);
+        }
+        self.lines.push_back(OutputLine {
+            id,
+            category,
+            kind,
+            text: text.to_owned(),
+            location,
+            depth: self.open_groups.len(),
+            variables_reference,
+            ended,
+        });
+
+        if self.lines.len() > MAX_OUTPUT_LINES
+            && let Some(dropped) = self.lines.pop_front()
+            && self.visible.front() == Some(&dropped.id)
+        {
+            self.visible.pop_front();
+        }
+    }
+
+    /// Works out which lines are visible again, after a group was collapsed or expanded.
+    fn update_visible(&mut self) {
+        self.visible.clear();
+        // Whether each of the groups the line is in is collapsed
+        let mut collapsed_groups: Vec<bool> = Vec::new();
+        for line in &self.lines {
+            collapsed_groups.resize(line.depth, false);
+            if !collapsed_groups.contains(&true) {
+                self.visible.push_back(line.id);
+            }
+            if let OutputLineKind::GroupStart { collapsed } = line.kind {
+                collapsed_groups.push(collapsed);
+            }
+        }
+    }
+}
+
+/// What the debugger and the debuggee printed, split in lines, along with what was typed in the
+/// console.
+#[derive(Default)]
+pub struct OutputStore {
+    lines: RwLock<OutputLines>,
+}
+
+impl OutputStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the text of an output event. Output does not have to come in whole lines, text
+    /// without a line break is continued by the next output, if it's of the same category.
+    pub fn push(
+        &self,
+        category: OutputEventCategory,
+        text: &str,
+        location: Option<(PathBuf, usize)>,
+    ) {
+        let mut lines_w = self.lines.write().unwrap();
+        let depth = lines_w.open_groups.len();
+
+        for segment in text.split_inclusive('\n') {
+            let ended = segment.ends_with('\n');
+            let segment = segment.trim_end_matches(['\n', '\r']);
+
+            match lines_w.lines.back_mut() {
+                Some(last)
+                    if !last.ended
+                        && last.category == category
+                        && last.kind == OutputLineKind::Text
+                        && last.depth == depth =>
+                {
+                    last.text.push_str(segment);
+                    last.ended = ended;
+                }
+                _ => {
+                    lines_w.push_line(
+                        category,
+                        OutputLineKind::Text,
+                        segment,
+                        location.clone(),
+                        0,
+                        ended,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Adds output whose children can be retrieved with the variables request. It's always a
+    /// line of its own.
+    pub fn push_structured(
+        &self,
+        category: OutputEventCategory,
+        text: &str,
+        variables_reference: u64,
+        location: Option<(PathBuf, usize)>,
+    ) {
+        self.lines.write().unwrap().push_line(
+            category,
+            OutputLineKind::Text,
+            text.trim_end_matches(['\n', '\r']),
+            location,
+            variables_reference,
+            true,
+        );
+    }
+
+    /// Adds a line with the header of a group. The output after it goes in the group, until
+    /// [Self::end_group] is called.
+    pub fn start_group(
+        &self,
+        category: OutputEventCategory,
+        text: &str,
+        location: Option<(PathBuf, usize)>,
+        collapsed: bool,
+    ) {
+        let mut lines_w = self.lines.write().unwrap();
+        let id = lines_w.next_id;
+        lines_w.push_line(
+            category,
+            OutputLineKind::GroupStart { collapsed },
+            text.trim_end_matches(['\n', '\r']),
+            location,
+            0,
+            true,
+        );
+        lines_w.open_groups.push((id, collapsed));
+    }
+
+    /// Ends the innermost group, if there's one.
+    pub fn end_group(&self) {
+        self.lines.write().unwrap().open_groups.pop();
+    }
+
+    /// Adds an expression typed in the console.
+    pub fn push_input(&self, expression: &str) {
+        self.lines.write().unwrap().push_line(
+            OutputEventCategory::Console,
+            OutputLineKind::Input,
+            expression,
+            None,
+            0,
+            true,
+        );
+    }
+
+    /// Adds what an expression typed in the console evaluated to. If the result is structured,
+    /// its children go under its first line.
+    pub fn push_result(&self, result: &str, variables_reference: u64) {
+        let mut lines_w = self.lines.write().unwrap();
+        for (i, line) in result.lines().enumerate() {
+            lines_w.push_line(
+                OutputEventCategory::Console,
+                OutputLineKind::Result,
+                line,
+                None,
+                if i == 0 { variables_reference } else { 0 },
+                true,
+            );
+        }
+    }
+
+    /// Collapses the group started by the line `id` if it's expanded, expands it otherwise.
+    pub fn toggle_group(&self, id: u64) {
+        let mut lines_w = self.lines.write().unwrap();
+        let Some(index) = lines_w.index_of(id) else {
+            return;
+        };
+        let OutputLineKind::GroupStart { collapsed } = &mut lines_w.lines[index].kind else {
+            return;
+        };
+        *collapsed = !*collapsed;
+        let collapsed = *collapsed;
+
+        if let Some(group) = lines_w
+            .open_groups
+            .iter_mut()
+            .find(|(start, _)| *start == id)
+        {
+            group.1 = collapsed;
+        }
+        lines_w.update_visible();
+    }
+
+    /// How many lines there are, leaving out the ones in collapsed groups.
+    pub fn num_lines(&self) -> usize {
+        self.lines.read().unwrap().visible.len()
+    }
+
+    /// Fills `out` with the lines in `range`, counting only the ones not in collapsed groups.
+    pub fn get(&self, range: Range<usize>, out: &mut Vec<OutputLine>) {
+        out.clear();
+
+        let lines_r = self.lines.read().unwrap();
+        out.extend(
+            lines_r
+                .visible
+                .range(range)
+                .filter_map(|id| lines_r.index_of(*id))
+                .map(|index| lines_r.lines[index].clone()),
+        );
+    }
+
+    pub fn clear(&self) {
+        let mut lines_w = self.lines.write().unwrap();
+        lines_w.lines.clear();
+        lines_w.visible.clear();
+        // The headers of the open groups are gone, so they could not be expanded anymore
+        lines_w.open_groups.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OutputStore;
+    use crate::dap::message_types::OutputEventCategory;
+
+    #[test]
+    fn test_output_lines() {
+        let store = OutputStore::new();
+        store.push(OutputEventCategory::Stdout, "hello ", None);
+        store.push(OutputEventCategory::Stdout, "world\r\nsecond", None);
+        // Other output does not go in the middle of a line
+        store.push(OutputEventCategory::Stderr, "oops\n", None);
+        store.push(
+            OutputEventCategory::Console,
+            "x = 4\n",
+            Some(("/src/main.rs".into(), 3)),
+        );
+
+        let mut lines = Vec::new();
+        store.get(0..store.num_lines(), &mut lines);
+        let texts: Vec<&str> = lines.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(texts, ["hello world", "second", "oops", "x = 4"]);
+        assert_eq!(lines[3].location, Some(("/src/main.rs".into(), 3)));
+    }
+
+    #[test]
+    fn test_output_groups() {
+        let store = OutputStore::new();
+        store.start_group(OutputEventCategory::Console, "outer\n", None, false);
+        store.push(OutputEventCategory::Stdout, "a\n", None);
+        store.start_group(OutputEventCategory::Console, "inner", None, true);
+        store.push(OutputEventCategory::Stdout, "b\n", None);
+        store.end_group();
+        store.push(OutputEventCategory::Stdout, "c\n", None);
+        store.end_group();
+        store.push(OutputEventCategory::Stdout, "d\n", None);
+
+        let texts = |store: &OutputStore| {
+            let mut lines = Vec::new();
+            store.get(0..store.num_lines(), &mut lines);
+            lines
+                .iter()
+                .map(|line| (line.text.clone(), line.depth))
+                .collect::<Vec<_>>()
+        };
+        let expected = |texts: &[(&str, usize)]| {
+            texts
+                .iter()
+                .map(|(text, depth)| (text.to_string(), *depth))
+                .collect::<Vec<_>>()
+        };
+
+        // The inner group starts collapsed
+        assert_eq!(
+            texts(&store),
+            expected(&[("outer", 0), ("a", 1), ("inner", 1), ("c", 1), ("d", 0)])
+        );
+
+        let mut lines = Vec::new();
+        store.get(0..1, &mut lines);
+        store.toggle_group(lines[0].id);
+        assert_eq!(texts(&store), expected(&[("outer", 0), ("d", 0)]));
+
+        store.toggle_group(lines[0].id);
+        store.get(2..3, &mut lines);
+        store.toggle_group(lines[0].id);
+        assert_eq!(
+            texts(&store),
+            expected(&[
+                ("outer", 0),
+                ("a", 1),
+                ("inner", 1),
+                ("b", 2),
+                ("c", 1),
+                ("d", 0)
+            ])
+        );
+    }
+}
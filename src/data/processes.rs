@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// The clock ticks `/proc` counts times in. The kernel always reports them at this rate.
+const PROC_TICKS_PER_SECOND: u64 = 100;
+
+/// A process running on this machine, as far as `/proc` tells.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    /// The arguments the process was started with, or its name in brackets if it has none, like
+    /// kernel threads
+    pub command_line: String,
+    /// The name of the user that owns the process, or its uid if it has no name
+    pub user: String,
+    pub start_time: Option<SystemTime>,
+}
+
+impl ProcessInfo {
+    /// Whether the process has `filter` in its pid, command line or user, ignoring case.
+    pub fn matches(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_lowercase();
+        filter.is_empty()
+            || self.pid.to_string().contains(&filter)
+            || self.command_line.to_lowercase().contains(&filter)
+            || self.user.to_lowercase().contains(&filter)
+    }
+}
+
+/// Lists the processes running on this machine, sorted by pid. The ones that go away while
+/// they are being read are left out.
+pub fn list_processes() -> Result<Vec<ProcessInfo>, String> {
+    let proc = Path::new("/proc");
+    let entries = std::fs::read_dir(proc).map_err(|e| format!("/proc: {e}"))?;
+    let users = std::fs::read_to_string("/etc/passwd")
+        .map(|passwd| parse_passwd(&passwd))
+        .unwrap_or_default();
+    let boot_time = std::fs::read_to_string(proc.join("stat"))
+        .ok()
+        .and_then(|stat| parse_boot_time(&stat));
+
+    let mut processes: Vec<ProcessInfo> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(|pid| read_process(&proc.join(pid.to_string()), pid, &users, boot_time))
+        .collect();
+    processes.sort_by_key(|process| process.pid);
+
+    Ok(processes)
+}
+
+fn read_process(
+    dir: &Path,
+    pid: u32,
+    users: &HashMap<u32, String>,
+    boot_time: Option<SystemTime>,
+) -> Option<ProcessInfo> {
+    let stat = std::fs::read_to_string(dir.join("stat")).ok()?;
+    let (name, start_ticks) = parse_stat(&stat)?;
+    let status = std::fs::read_to_string(dir.join("status")).ok()?;
+    let uid = parse_uid(&status)?;
+
+    // Arguments are separated by nul bytes
+    let cmdline = std::fs::read(dir.join("cmdline")).unwrap_or_default();
+    let args: Vec<String> = cmdline
+        .split(|b| *b == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+    let command_line = if args.is_empty() {
+        format!("[{name}]")
+    } else {
+        args.join(" ")
+    };
+
+    Some(ProcessInfo {
+        pid,
+        command_line,
+        user: users.get(&uid).cloned().unwrap_or_else(|| uid.to_string()),
+        start_time: boot_time
+            .map(|boot| boot + Duration::from_millis(start_ticks * 1000 / PROC_TICKS_PER_SECOND)),
+    })
+}
+
+/// Takes the name and the start time in clock ticks after boot out of `/proc/<pid>/stat`.
+fn parse_stat(stat: &str) -> Option<(String, u64)> {
+    // The name is in parentheses, and may have parentheses and spaces itself
+    let name_start = stat.find('(')? + 1;
+    let name_end = stat.rfind(')')?;
+    let name = stat.get(name_start..name_end)?.to_owned();
+
+    // The start time is the 22nd field, the name being the 2nd
+    let start_ticks = stat
+        .get(name_end + 1..)?
+        .split_whitespace()
+        .nth(22 - 3)?
+        .parse()
+        .ok()?;
+
+    Some((name, start_ticks))
+}
+
+/// Takes the real uid out of `/proc/<pid>/status`.
+fn parse_uid(status: &str) -> Option<u32> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("Uid:"))?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Takes when the machine booted out of `/proc/stat`.
+fn parse_boot_time(stat: &str) -> Option<SystemTime> {
+    let seconds = stat
+        .lines()
+        .find_map(|line| line.strip_prefix("btime "))?
+        .trim()
+        .parse()
+        .ok()?;
+
+    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+/// Maps the uids in `/etc/passwd` to their user names.
+fn parse_passwd(passwd: &str) -> HashMap<u32, String> {
+    passwd
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let uid = fields.nth(1)?.parse().ok()?;
+            Some((uid, name.to_owned()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_passwd, parse_stat, parse_uid};
+
+    #[test]
+    fn test_parse_proc_files() {
+        let stat = "4321 (my (weird) prog) S 1 4321 4321 0 -1 4194560 1089 0 0 0 3 1 0 0 20 0 1 \
+                    0 123456 10000000 500 18446744073709551615 0 0 0 0 0 0 0 0 0 0 0 0 17 3 0";
+        assert_eq!(
+            parse_stat(stat),
+            Some(("my (weird) prog".to_owned(), 123456))
+        );
+
+        let status = "Name:\tprog\nUmask:\t0022\nUid:\t1000\t1000\t1000\t1000\nGid:\t100\n";
+        assert_eq!(parse_uid(status), Some(1000));
+
+        let users = parse_passwd("root:x:0:0:root:/root:/bin/sh\nme:x:1000:100::/home/me:\n");
+        assert_eq!(users.get(&0).map(String::as_str), Some("root"));
+        assert_eq!(users.get(&1000).map(String::as_str), Some("me"));
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+use std::ffi::{CStr, OsStr};
+use std::fs::File;
+use std::io::{self, ErrorKind, Read, Write};
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+/// The rows and columns a pseudo-terminal starts with.
+pub const DEFAULT_TERMINAL_SIZE: (u16, u16) = (24, 80);
+
+/// The terminal emulator used when `$TERMINAL` does not name one.
+const DEFAULT_TERMINAL_EMULATOR: &str = "x-terminal-emulator";
+
+/// How much is read from a pseudo-terminal at once.
+const READ_BUFFER_SIZE: usize = 4096;
+
+/// What the programs we run in a pseudo-terminal are told it is, so they use colors.
+const TERM: &str = "xterm-256color";
+
+/// Big enough for the path of any terminal.
+const TTY_NAME_SIZE: usize = 64;
+
+/// A command the debugger wants run in a terminal, see
+/// [RequestMessage::RunInTerminal](crate::dap::message::RequestMessage::RunInTerminal).
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct TerminalCommand {
+    /// The program and its arguments
+    pub args: Vec<String>,
+    /// Where it runs. If empty, it's wherever we run.
+    pub cwd: PathBuf,
+    /// Variables added to our environment, or removed from it if None
+    pub env: HashMap<String, Option<String>>,
+    /// Whether the arguments go through a shell as they are, so they can use its syntax
+    pub through_shell: bool,
+}
+
+impl TerminalCommand {
+    /// What is actually run: the arguments themselves, or a shell running them.
+    fn argv(&self) -> Vec<String> {
+        if self.through_shell {
+            vec!["/bin/sh".into(), "-c".into(), self.args.join(" ")]
+        } else {
+            self.args.clone()
+        }
+    }
+
+    /// The command to spawn, with `prefix` going before the arguments.
+    fn to_command(&self, prefix: &[String]) -> io::Result<Command> {
+        let mut argv = prefix
+            .iter()
+            .chain(self.argv().iter())
+            .cloned()
+            .collect::<Vec<_>>();
+        if argv.is_empty() {
+            return Err(io::Error::new(ErrorKind::InvalidInput, "nothing to run"));
+        }
+
+        let mut command = Command::new(argv.remove(0));
+        command.args(argv);
+        if !self.cwd.as_os_str().is_empty() {
+            command.current_dir(&self.cwd);
+        }
+        for (name, value) in &self.env {
+            match value {
+                Some(value) => command.env(name, value),
+                None => command.env_remove(name),
+            };
+        }
+
+        Ok(command)
+    }
+}
+
+/// A pseudo-terminal we own, usually with a program running in it. What's printed to it is
+/// read in the background, and taken with [Self::read_output].
+pub struct Pty {
+    /// The pid of the program, if we ran it
+    pid: Option<u32>,
+    /// Taken when dropped, to be waited on
+    child: Option<Child>,
+    /// Our side of the terminal, the program is hung up once it's closed
+    master: File,
+    /// The program's side of the terminal, if we don't run the program ourselves. Until the
+    /// program opens it, it's only open here, and reading the terminal would fail otherwise.
+    #[allow(unused)]
+    slave: Option<File>,
+    tty_path: PathBuf,
+    size: Mutex<(u16, u16)>,
+    output: Mutex<Receiver<Vec<u8>>>,
+}
+
+impl Pty {
+    /// Opens a pseudo-terminal with nothing running in it, for a program to be started on
+    /// [Self::tty_path] by someone else.
+    pub fn open() -> io::Result<Self> {
+        let (master, slave) = open_pty(DEFAULT_TERMINAL_SIZE)?;
+        let tty_path = tty_name(slave.as_fd())?;
+        Self::new(master, tty_path, Some(File::from(slave)), None)
+    }
+
+    /// Runs `command` in a new pseudo-terminal, as the leader of a session of its own, like a
+    /// shell would.
+    pub fn spawn(command: &TerminalCommand) -> io::Result<Self> {
+        let (master, slave) = open_pty(DEFAULT_TERMINAL_SIZE)?;
+        let tty_path = tty_name(slave.as_fd())?;
+        let slave = File::from(slave);
+
+        let mut process = command.to_command(&[])?;
+        if !command.env.contains_key("TERM") {
+            process.env("TERM", TERM);
+        }
+        process
+            .stdin(Stdio::from(slave.try_clone()?))
+            .stdout(Stdio::from(slave.try_clone()?))
+            .stderr(Stdio::from(slave));
+        // SAFETY: only async-signal-safe functions are called between fork and exec
+        unsafe {
+            process.pre_exec(|| {
+                // The terminal becomes the controlling terminal of the new session, so the
+                // program gets its signals, like the interrupt from ^C
+                if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY, 0) == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let child = process.spawn()?;
+        // Only the child keeps the slave end open then, so reading fails once it's gone
+        drop(process);
+
+        Self::new(master, tty_path, None, Some(child))
+    }
+
+    fn new(
+        master: OwnedFd,
+        tty_path: PathBuf,
+        slave: Option<File>,
+        child: Option<Child>,
+    ) -> io::Result<Self> {
+        let master = File::from(master);
+        let mut reader = master.try_clone()?;
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut buffer = [0; READ_BUFFER_SIZE];
+            loop {
+                match reader.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(read) => {
+                        if tx.send(buffer[..read].to_vec()).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                    // Linux says EIO once the other end is closed
+                    Err(_) => break,
+                }
+            }
+        });
+
+        Ok(Self {
+            pid: child.as_ref().map(Child::id),
+            child,
+            master,
+            slave,
+            tty_path,
+            size: Mutex::new(DEFAULT_TERMINAL_SIZE),
+            output: Mutex::new(rx),
+        })
+    }
+
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// The path programs open the terminal at, like `/dev/pts/3`.
+    pub fn tty_path(&self) -> &Path {
+        &self.tty_path
+    }
+
+    /// The rows and columns of the terminal.
+    pub fn size(&self) -> (u16, u16) {
+        *self.size.lock().unwrap()
+    }
+
+    /// Appends to `out` what the program printed since the last call. Returns false once the
+    /// terminal is closed and there's nothing left to read.
+    pub fn read_output(&self, out: &mut Vec<u8>) -> bool {
+        let output = self.output.lock().unwrap();
+        loop {
+            match output.try_recv() {
+                Ok(bytes) => out.extend_from_slice(&bytes),
+                Err(TryRecvError::Empty) => return true,
+                Err(TryRecvError::Disconnected) => return false,
+            }
+        }
+    }
+
+    /// Sends `bytes` to the program, as if they were typed in the terminal.
+    pub fn write_input(&self, bytes: &[u8]) -> io::Result<()> {
+        (&self.master).write_all(bytes)
+    }
+
+    /// Changes the rows and columns of the terminal. The program is sent SIGWINCH, to draw
+    /// itself again if it cares.
+    pub fn resize(&self, (rows, columns): (u16, u16)) -> io::Result<()> {
+        let mut size = self.size.lock().unwrap();
+        if *size == (rows, columns) {
+            return Ok(());
+        }
+
+        let winsize = winsize((rows, columns));
+        // SAFETY: the master is an open terminal, and the pointer is valid during the call
+        if unsafe { libc::ioctl(self.master.as_raw_fd(), libc::TIOCSWINSZ, &winsize) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        *size = (rows, columns);
+        Ok(())
+    }
+}
+
+impl Drop for Pty {
+    /// Closing the terminal hangs the program up, it's waited on in the background so it does
+    /// not linger as a zombie.
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            std::thread::spawn(move || child.wait());
+        }
+    }
+}
+
+/// Runs `command` in a terminal emulator window of its own, the one `$TERMINAL` names or the
+/// system's default. Returns the pid of the terminal emulator.
+pub fn spawn_external(command: &TerminalCommand) -> io::Result<u32> {
+    let emulator = std::env::var("TERMINAL")
+        .ok()
+        .filter(|terminal| !terminal.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_TERMINAL_EMULATOR.into());
+    let mut child = command
+        .to_command(&[emulator, "-e".into()])?
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let pid = child.id();
+    std::thread::spawn(move || child.wait());
+    Ok(pid)
+}
+
+/// Opens a pseudo-terminal of `(rows, columns)`, returning its master and slave ends. Neither
+/// is inherited by the processes we spawn, unless they are given to them.
+fn open_pty(size: (u16, u16)) -> io::Result<(OwnedFd, OwnedFd)> {
+    let size = winsize(size);
+    let (mut master, mut slave) = (-1, -1);
+    // SAFETY: the pointers are valid for the duration of the call, and no name is asked for
+    let result = unsafe {
+        libc::openpty(
+            &mut master,
+            &mut slave,
+            std::ptr::null_mut(),
+            std::ptr::null(),
+            &size,
+        )
+    };
+    if result == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: openpty succeeded, so both are open file descriptors nothing else owns
+    let (master, slave) = unsafe { (OwnedFd::from_raw_fd(master), OwnedFd::from_raw_fd(slave)) };
+
+    for fd in [&master, &slave] {
+        // SAFETY: fd is an open file descriptor
+        if unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    Ok((master, slave))
+}
+
+fn winsize((rows, columns): (u16, u16)) -> libc::winsize {
+    libc::winsize {
+        ws_row: rows,
+        ws_col: columns,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    }
+}
+
+/// The path of the terminal `fd` is open on.
+fn tty_name(fd: BorrowedFd) -> io::Result<PathBuf> {
+    let mut name = [0 as libc::c_char; TTY_NAME_SIZE];
+    // SAFETY: the buffer is valid for its whole length during the call
+    let result = unsafe { libc::ttyname_r(fd.as_raw_fd(), name.as_mut_ptr(), name.len()) };
+    if result != 0 {
+        return Err(io::Error::from_raw_os_error(result));
+    }
+
+    // SAFETY: ttyname_r succeeded, so the buffer holds a nul terminated string
+    let name = unsafe { CStr::from_ptr(name.as_ptr()) };
+    Ok(PathBuf::from(OsStr::from_bytes(name.to_bytes())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pty, TerminalCommand};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_pty() {
+        let command = TerminalCommand {
+            args: vec!["test -t 0 && printf \"$GREETING\"".into()],
+            env: [("GREETING".to_owned(), Some("hello".to_owned()))].into(),
+            through_shell: true,
+            ..Default::default()
+        };
+        let pty = Pty::spawn(&command).unwrap();
+        assert!(pty.tty_path().starts_with("/dev"));
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut output = Vec::new();
+        while pty.read_output(&mut output) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(String::from_utf8_lossy(&output), "hello");
+
+        // What's typed is echoed back by the terminal, with nothing running in it
+        let pty = Pty::open().unwrap();
+        pty.write_input(b"typed").unwrap();
+        let mut output = Vec::new();
+        while !output.ends_with(b"typed") && Instant::now() < deadline {
+            pty.read_output(&mut output);
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(String::from_utf8_lossy(&output), "typed");
+    }
+}
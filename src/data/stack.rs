@@ -0,0 +1,175 @@
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::dap::message_types::StackFrame;
+
+/// How many frames are fetched by the first stackTrace request after stopping. It's kept small
+/// so the top frame shows up quickly.
+pub const FIRST_STACK_PAGE_SIZE: u64 = 20;
+
+/// How many frames are fetched by each stackTrace request after the first one.
+pub const STACK_PAGE_SIZE: u64 = 200;
+
+/// Past this many frames, the rest of the stack is only fetched when the user asks for it.
+pub const MAX_AUTO_LOADED_FRAMES: usize = 1000;
+
+/// The frames of a thread fetched so far.
+#[derive(Clone, Default, Debug)]
+pub struct CallStack {
+    pub thread_id: Option<u64>,
+    pub frames: Vec<StackFrame>,
+    /// Whether every frame was fetched
+    pub complete: bool,
+    /// Whether a page of frames is being fetched
+    pub loading: bool,
+    pub error: Option<String>,
+}
+
+/// The call stack of the stopped thread. Like variables, frames are only valid while the
+/// program is stopped, responses to requests sent before it last ran are recognized by their
+/// generation and discarded.
+#[derive(Default)]
+pub struct StackStore {
+    stack: RwLock<CallStack>,
+    /// Bumped every time the stack is reset
+    generation: AtomicU64,
+}
+
+impl StackStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self) -> CallStack {
+        self.stack.read().unwrap().clone()
+    }
+
+    /// The thread whose stack this is.
+    pub fn thread_id(&self) -> Option<u64> {
+        self.stack.read().unwrap().thread_id
+    }
+
+    pub fn frame(&self, frame_id: u64) -> Option<StackFrame> {
+        let stack_r = self.stack.read().unwrap();
+        stack_r
+            .frames
+            .iter()
+            .find(|frame| frame.id == frame_id)
+            .cloned()
+    }
+
+    /// Forgets every frame, and starts over with the stack of `thread_id`. Returns the new
+    /// generation.
+    pub fn reset(&self, thread_id: Option<u64>) -> u64 {
+        let mut stack_w = self.stack.write().unwrap();
+        *stack_w = CallStack {
+            thread_id,
+            ..Default::default()
+        };
+
+        self.generation.fetch_add(1, Ordering::AcqRel) + 1
+    }
+
+    /// Marks the next page as being fetched. Returns the generation it should be completed with,
+    /// the thread, and the index of the first frame of the page. None if there is nothing left
+    /// to fetch.
+    pub fn set_loading(&self) -> Option<(u64, u64, usize)> {
+        let mut stack_w = self.stack.write().unwrap();
+        let thread_id = stack_w.thread_id?;
+        if stack_w.complete || stack_w.loading {
+            return None;
+        }
+        stack_w.loading = true;
+        stack_w.error = None;
+
+        Some((
+            self.generation.load(Ordering::Acquire),
+            thread_id,
+            stack_w.frames.len(),
+        ))
+    }
+
+    /// Adds a page of frames. `levels` is the amount of frames that were requested, getting less
+    /// than that means the stack is over. Returns false if the page is stale, in which case it's
+    /// discarded.
+    pub fn add_page(
+        &self,
+        generation: u64,
+        frames: Vec<StackFrame>,
+        levels: u64,
+        total_frames: Option<usize>,
+    ) -> bool {
+        let mut stack_w = self.stack.write().unwrap();
+        if self.generation.load(Ordering::Acquire) != generation {
+            return false;
+        }
+
+        let page_len = frames.len();
+        stack_w.frames.extend(frames);
+        stack_w.loading = false;
+        stack_w.complete = (page_len as u64) < levels
+            || total_frames.is_some_and(|total| stack_w.frames.len() >= total);
+
+        true
+    }
+
+    /// Returns false if the request is stale.
+    pub fn fail(&self, generation: u64, message: String) -> bool {
+        let mut stack_w = self.stack.write().unwrap();
+        if self.generation.load(Ordering::Acquire) != generation {
+            return false;
+        }
+        stack_w.loading = false;
+        stack_w.error = Some(message);
+
+        true
+    }
+
+    /// Whether more frames should be fetched without the user asking.
+    pub fn wants_more(&self) -> bool {
+        let stack_r = self.stack.read().unwrap();
+        !stack_r.complete && stack_r.frames.len() < MAX_AUTO_LOADED_FRAMES
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StackStore;
+    use crate::dap::message_types::StackFrame;
+
+    fn frames(ids: std::ops::Range<u64>) -> Vec<StackFrame> {
+        ids.map(|id| {
+            serde_json::from_value(serde_json::json!({
+                "id": id, "name": format!("frame {id}"), "line": 0, "column": 0,
+            }))
+            .unwrap()
+        })
+        .collect()
+    }
+
+    #[test]
+    fn test_stack_paging() {
+        let store = StackStore::new();
+        store.reset(Some(1));
+
+        let (generation, thread_id, start) = store.set_loading().unwrap();
+        assert_eq!((thread_id, start), (1, 0));
+        // Only one page at a time
+        assert!(store.set_loading().is_none());
+        assert!(store.add_page(generation, frames(0..2), 2, Some(3)));
+        assert!(store.wants_more());
+
+        let (generation, _, start) = store.set_loading().unwrap();
+        assert_eq!(start, 2);
+        assert!(store.add_page(generation, frames(2..3), 2, Some(3)));
+        assert!(store.get().complete);
+        assert!(store.set_loading().is_none());
+
+        // Pages of an earlier stop are dropped
+        store.reset(Some(1));
+        let (generation, _, _) = store.set_loading().unwrap();
+        store.reset(Some(2));
+        assert!(!store.add_page(generation, frames(0..2), 2, None));
+        assert!(store.get().frames.is_empty());
+    }
+}
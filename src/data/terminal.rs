@@ -0,0 +1,746 @@
+use crate::data::pty::Pty;
+use std::collections::VecDeque;
+use std::io;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+/// How many lines a terminal keeps. Past that, the oldest ones are dropped.
+pub const MAX_TERMINAL_LINES: usize = 10_000;
+
+const TAB_WIDTH: usize = 8;
+
+/// Escape sequences longer than this are none we know, they are dropped.
+const MAX_SEQUENCE_LEN: usize = 256;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum TerminalColor {
+    /// The color of the text or of the background, whichever it's used for
+    #[default]
+    Default,
+    /// One of the 256 colors of the palette, the first 16 being the ones themes pick
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// How a character looks, as set by the SGR escape sequences.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct CellStyle {
+    pub foreground: TerminalColor,
+    pub background: TerminalColor,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    /// The foreground and background colors are swapped
+    pub inverse: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Cell {
+    c: char,
+    style: CellStyle,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            c: ' ',
+            style: CellStyle::default(),
+        }
+    }
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct TerminalLine {
+    cells: Vec<Cell>,
+}
+
+impl TerminalLine {
+    pub fn text(&self) -> String {
+        self.cells.iter().map(|cell| cell.c).collect()
+    }
+
+    /// The text of the line, in runs of the same style.
+    pub fn spans(&self) -> Vec<(String, CellStyle)> {
+        let mut spans: Vec<(String, CellStyle)> = Vec::new();
+        for cell in &self.cells {
+            match spans.last_mut() {
+                Some((text, style)) if *style == cell.style => text.push(cell.c),
+                _ => spans.push((cell.c.into(), cell.style)),
+            }
+        }
+
+        spans
+    }
+
+    fn set(&mut self, column: usize, cell: Cell) {
+        if column >= self.cells.len() {
+            self.cells.resize(column + 1, Cell::default());
+        }
+        self.cells[column] = cell;
+    }
+
+    /// Blanks the cells in `range`, the ones past the end of the line are blank already.
+    fn erase(&mut self, range: Range<usize>) {
+        let end = range.end.min(self.cells.len());
+        if range.start < end {
+            self.cells[range.start..end].fill(Cell::default());
+        }
+    }
+}
+
+/// Where the parser is in the escape sequence it's reading, if any.
+#[derive(Default)]
+enum ParserState {
+    #[default]
+    Ground,
+    /// Right after ESC
+    Escape,
+    /// In a control sequence, `ESC [`, with the parameters read so far
+    Csi(String),
+    /// In an operating system command, `ESC ]`, with its text so far
+    Osc(String),
+    /// Right after the ESC that may end an operating system command
+    OscEscape(String),
+    /// The next character is the argument of an escape sequence we ignore
+    Skip,
+}
+
+/// What a terminal shows: the lines a program printed, and the cursor it prints at. Text and
+/// the escape sequences programs commonly use are understood, like colors, cursor movement and
+/// erasing. Lines that scroll off the screen are kept, up to [MAX_TERMINAL_LINES].
+pub struct TerminalScreen {
+    lines: VecDeque<TerminalLine>,
+    rows: usize,
+    columns: usize,
+    /// Where the next character goes, as a line index and a column. The column may be one past
+    /// the last, then the line wraps when the next character comes.
+    cursor: (usize, usize),
+    saved_cursor: (usize, usize),
+    style: CellStyle,
+    /// The title the program gave the terminal, if it did
+    title: Option<String>,
+    state: ParserState,
+    /// The start of a UTF-8 sequence the last bytes ended in the middle of
+    incomplete: Vec<u8>,
+}
+
+impl TerminalScreen {
+    pub fn new((rows, columns): (u16, u16)) -> Self {
+        Self {
+            lines: VecDeque::from([TerminalLine::default()]),
+            rows: rows.max(1).into(),
+            columns: columns.max(1).into(),
+            cursor: (0, 0),
+            saved_cursor: (0, 0),
+            style: CellStyle::default(),
+            title: None,
+            state: ParserState::Ground,
+            incomplete: Vec::new(),
+        }
+    }
+
+    /// Changes the size of the screen. Lines are not wrapped again.
+    pub fn resize(&mut self, (rows, columns): (u16, u16)) {
+        self.rows = rows.max(1).into();
+        self.columns = columns.max(1).into();
+        self.cursor.1 = self.cursor.1.min(self.columns);
+    }
+
+    pub fn num_lines(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Fills `out` with the lines in `range`.
+    pub fn get(&self, range: Range<usize>, out: &mut Vec<TerminalLine>) {
+        out.clear();
+        out.extend(self.lines.range(range).cloned());
+    }
+
+    pub fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Looks for the first line with `text` in it, ignoring case. It starts at the line
+    /// `start` and goes down, or up if not `forward`, wrapping around. A `start` past the last
+    /// line wraps around too.
+    pub fn find(&self, text: &str, start: usize, forward: bool) -> Option<usize> {
+        let text = text.to_lowercase();
+        let len = self.lines.len();
+        if text.is_empty() {
+            return None;
+        }
+
+        let start = start % len;
+        (0..len)
+            .map(|i| {
+                if forward {
+                    (start + i) % len
+                } else {
+                    (start + len - i) % len
+                }
+            })
+            .find(|i| self.lines[*i].text().to_lowercase().contains(&text))
+    }
+
+    /// Takes what the program printed. It does not have to end at a character boundary, the
+    /// rest of the character is expected in the next call.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        let mut pending = std::mem::take(&mut self.incomplete);
+        pending.extend_from_slice(bytes);
+
+        let mut rest = &pending[..];
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(text) => {
+                    text.chars().for_each(|c| self.put(c));
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    String::from_utf8_lossy(valid)
+                        .chars()
+                        .for_each(|c| self.put(c));
+                    match e.error_len() {
+                        Some(len) => {
+                            self.put(char::REPLACEMENT_CHARACTER);
+                            rest = &after[len..];
+                        }
+                        None => {
+                            self.incomplete = after.to_vec();
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn put(&mut self, c: char) {
+        self.state = match std::mem::take(&mut self.state) {
+            ParserState::Ground => {
+                match c {
+                    '\x1b' => return self.state = ParserState::Escape,
+                    '\r' => self.cursor.1 = 0,
+                    '\n' | '\x0b' | '\x0c' => self.line_feed(),
+                    '\x08' => self.cursor.1 = self.cursor.1.saturating_sub(1),
+                    '\t' => {
+                        self.cursor.1 =
+                            ((self.cursor.1 / TAB_WIDTH + 1) * TAB_WIDTH).min(self.columns - 1);
+                    }
+                    // Like the bell
+                    c if c.is_control() => {}
+                    c => self.print(c),
+                }
+                ParserState::Ground
+            }
+            ParserState::Escape => match c {
+                '[' => ParserState::Csi(String::new()),
+                ']' => ParserState::Osc(String::new()),
+                // Character sets, only the default one is used
+                '(' | ')' | '*' | '+' => ParserState::Skip,
+                '7' => {
+                    self.saved_cursor = self.cursor;
+                    ParserState::Ground
+                }
+                '8' => {
+                    self.restore_cursor();
+                    ParserState::Ground
+                }
+                'D' => {
+                    self.line_feed();
+                    ParserState::Ground
+                }
+                'E' => {
+                    self.line_feed();
+                    self.cursor.1 = 0;
+                    ParserState::Ground
+                }
+                'M' => {
+                    self.cursor.0 = self.cursor.0.saturating_sub(1).max(self.screen_top());
+                    ParserState::Ground
+                }
+                'c' => {
+                    self.style = CellStyle::default();
+                    self.title = None;
+                    self.erase_in_display(2);
+                    self.move_to(0, 0);
+                    ParserState::Ground
+                }
+                _ => ParserState::Ground,
+            },
+            ParserState::Csi(mut sequence) => {
+                if ('\x40'..='\x7e').contains(&c) {
+                    self.control_sequence(&sequence, c);
+                    ParserState::Ground
+                } else if sequence.len() < MAX_SEQUENCE_LEN {
+                    sequence.push(c);
+                    ParserState::Csi(sequence)
+                } else {
+                    ParserState::Ground
+                }
+            }
+            ParserState::Osc(mut text) => match c {
+                '\x07' => {
+                    self.operating_system_command(&text);
+                    ParserState::Ground
+                }
+                '\x1b' => ParserState::OscEscape(text),
+                c if text.len() < MAX_SEQUENCE_LEN => {
+                    text.push(c);
+                    ParserState::Osc(text)
+                }
+                _ => ParserState::Ground,
+            },
+            ParserState::OscEscape(text) => {
+                if c == '\\' {
+                    self.operating_system_command(&text);
+                }
+                ParserState::Ground
+            }
+            ParserState::Skip => ParserState::Ground,
+        };
+    }
+
+    /// The index of the first line on the screen, the ones before it are scrollback.
+    fn screen_top(&self) -> usize {
+        self.lines.len().saturating_sub(self.rows)
+    }
+
+    fn print(&mut self, c: char) {
+        if self.cursor.1 >= self.columns {
+            self.line_feed();
+            self.cursor.1 = 0;
+        }
+
+        let (row, column) = self.cursor;
+        let style = self.style;
+        self.lines[row].set(column, Cell { c, style });
+        self.cursor.1 += 1;
+    }
+
+    fn line_feed(&mut self) {
+        self.move_to_row(self.cursor.0 + 1);
+    }
+
+    /// Moves the cursor to the line `row`, adding lines until there, and dropping the oldest
+    /// ones if there are too many.
+    fn move_to_row(&mut self, row: usize) {
+        while self.lines.len() <= row {
+            self.lines.push_back(TerminalLine::default());
+        }
+        self.cursor.0 = row;
+
+        let excess = self.lines.len().saturating_sub(MAX_TERMINAL_LINES);
+        self.lines.drain(..excess);
+        self.cursor.0 -= excess;
+        self.saved_cursor.0 = self.saved_cursor.0.saturating_sub(excess);
+    }
+
+    /// Moves the cursor to `row` and `column` of the screen, counting from 0.
+    fn move_to(&mut self, row: usize, column: usize) {
+        self.move_to_row(self.screen_top() + row.min(self.rows - 1));
+        self.cursor.1 = column.min(self.columns - 1);
+    }
+
+    fn restore_cursor(&mut self) {
+        let (row, column) = self.saved_cursor;
+        self.move_to_row(row);
+        self.cursor.1 = column.min(self.columns);
+    }
+
+    fn control_sequence(&mut self, sequence: &str, action: char) {
+        // Private sequences, like the ones hiding the cursor, don't change what's shown
+        if sequence.starts_with(['?', '>', '<', '=']) {
+            return;
+        }
+
+        // Missing parameters are 0, which usually means the default
+        let params: Vec<usize> = sequence
+            .split(';')
+            .map(|param| param.parse().unwrap_or(0))
+            .collect();
+        let param = |i: usize, default: usize| {
+            params
+                .get(i)
+                .copied()
+                .filter(|param| *param != 0)
+                .unwrap_or(default)
+        };
+        let count = param(0, 1).min(self.columns.max(self.rows));
+        let (row, column) = self.cursor;
+        let top = self.screen_top();
+        let screen_row = row - top;
+
+        match action {
+            'A' => self.cursor.0 = row.saturating_sub(count).max(top),
+            'B' | 'e' => self.move_to(screen_row + count, column),
+            'C' | 'a' => self.cursor.1 = (column + count).min(self.columns - 1),
+            'D' => self.cursor.1 = column.saturating_sub(count),
+            'E' => self.move_to(screen_row + count, 0),
+            'F' => self.move_to(screen_row.saturating_sub(count), 0),
+            'G' | '`' => self.cursor.1 = (count - 1).min(self.columns - 1),
+            'H' | 'f' => self.move_to(param(0, 1) - 1, param(1, 1) - 1),
+            'd' => self.move_to(count - 1, column),
+            'J' => self.erase_in_display(param(0, 0)),
+            'K' => self.erase_in_line(param(0, 0)),
+            'P' => {
+                let cells = &mut self.lines[row].cells;
+                if column < cells.len() {
+                    cells.drain(column..(column + count).min(cells.len()));
+                }
+            }
+            '@' => {
+                let cells = &mut self.lines[row].cells;
+                if column < cells.len() {
+                    cells.splice(column..column, std::iter::repeat_n(Cell::default(), count));
+                    cells.truncate(self.columns);
+                }
+            }
+            'X' => self.lines[row].erase(column..column + count),
+            'm' => self.select_graphic_rendition(&params),
+            's' => self.saved_cursor = self.cursor,
+            'u' => self.restore_cursor(),
+            _ => {}
+        }
+    }
+
+    fn erase_in_display(&mut self, mode: usize) {
+        let (row, column) = self.cursor;
+        let top = self.screen_top();
+        match mode {
+            0 => {
+                self.erase_in_line(0);
+                self.lines
+                    .range_mut(row + 1..)
+                    .for_each(|line| line.cells.clear());
+            }
+            1 => {
+                self.lines
+                    .range_mut(top..row)
+                    .for_each(|line| line.cells.clear());
+                self.lines[row].erase(0..column + 1);
+            }
+            // What was on the screen is scrolled off it, to not lose it
+            2 => {
+                let screen_row = row - top;
+                let last_used = self
+                    .lines
+                    .iter()
+                    .rposition(|line| !line.cells.is_empty())
+                    .unwrap_or(0);
+                self.move_to_row(last_used.max(row) + self.rows);
+                self.move_to(screen_row, column);
+            }
+            3 => {
+                self.lines.drain(..top);
+                self.cursor.0 -= top;
+                self.saved_cursor.0 = self.saved_cursor.0.saturating_sub(top);
+            }
+            _ => {}
+        }
+    }
+
+    fn erase_in_line(&mut self, mode: usize) {
+        let (row, column) = self.cursor;
+        let line = &mut self.lines[row];
+        match mode {
+            0 => line.cells.truncate(column),
+            1 => line.erase(0..column + 1),
+            2 => line.cells.clear(),
+            _ => {}
+        }
+    }
+
+    fn select_graphic_rendition(&mut self, params: &[usize]) {
+        let style = &mut self.style;
+        let mut params = params.iter().copied();
+        while let Some(param) = params.next() {
+            match param {
+                0 => *style = CellStyle::default(),
+                1 => style.bold = true,
+                2 => style.dim = true,
+                3 => style.italic = true,
+                4 => style.underline = true,
+                7 => style.inverse = true,
+                22 => (style.bold, style.dim) = (false, false),
+                23 => style.italic = false,
+                24 => style.underline = false,
+                27 => style.inverse = false,
+                30..=37 => style.foreground = TerminalColor::Indexed(param as u8 - 30),
+                38 => {
+                    if let Some(color) = extended_color(&mut params) {
+                        style.foreground = color;
+                    }
+                }
+                39 => style.foreground = TerminalColor::Default,
+                40..=47 => style.background = TerminalColor::Indexed(param as u8 - 40),
+                48 => {
+                    if let Some(color) = extended_color(&mut params) {
+                        style.background = color;
+                    }
+                }
+                49 => style.background = TerminalColor::Default,
+                90..=97 => style.foreground = TerminalColor::Indexed(param as u8 - 90 + 8),
+                100..=107 => style.background = TerminalColor::Indexed(param as u8 - 100 + 8),
+                _ => {}
+            }
+        }
+    }
+
+    fn operating_system_command(&mut self, text: &str) {
+        if let Some((code, title)) = text.split_once(';')
+            && matches!(code, "0" | "2")
+        {
+            self.title = Some(title.to_owned());
+        }
+    }
+}
+
+/// Takes the color out of the parameters after a 38 or 48 SGR parameter, either `5;<index>` or
+/// `2;<red>;<green>;<blue>`.
+fn extended_color(params: &mut impl Iterator<Item = usize>) -> Option<TerminalColor> {
+    let mut component = || params.next().map(|param| param.min(255) as u8);
+    match component()? {
+        5 => Some(TerminalColor::Indexed(component()?)),
+        2 => Some(TerminalColor::Rgb(component()?, component()?, component()?)),
+        _ => None,
+    }
+}
+
+/// A terminal a program runs in, usually the debuggee. What the program prints is shown on its
+/// [TerminalScreen], and what's written to it is what the program reads.
+pub struct Terminal {
+    id: u64,
+    /// What the terminal is called, unless the program gives it a title
+    name: String,
+    pty: Pty,
+    screen: RwLock<TerminalScreen>,
+    /// Whether the program is gone, and everything it printed was read
+    closed: AtomicBool,
+}
+
+impl Terminal {
+    pub fn title(&self) -> String {
+        let screen_r = self.screen.read().unwrap();
+        screen_r.title().unwrap_or(&self.name).to_owned()
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    pub fn num_lines(&self) -> usize {
+        self.screen.read().unwrap().num_lines()
+    }
+
+    /// Fills `out` with the lines in `range`.
+    pub fn get_lines(&self, range: Range<usize>, out: &mut Vec<TerminalLine>) {
+        self.screen.read().unwrap().get(range, out);
+    }
+
+    pub fn cursor(&self) -> (usize, usize) {
+        self.screen.read().unwrap().cursor()
+    }
+
+    /// See [TerminalScreen::find].
+    pub fn find(&self, text: &str, start: usize, forward: bool) -> Option<usize> {
+        self.screen.read().unwrap().find(text, start, forward)
+    }
+
+    /// Sends `bytes` to the program, as if they were typed in the terminal.
+    pub fn write_input(&self, bytes: &[u8]) -> io::Result<()> {
+        self.pty.write_input(bytes)
+    }
+
+    /// Changes the size of the terminal, in rows and columns. The program is told about it.
+    pub fn resize(&self, size: (u16, u16)) -> io::Result<()> {
+        self.screen.write().unwrap().resize(size);
+        self.pty.resize(size)
+    }
+
+    fn read_output(&self) {
+        let mut output = Vec::new();
+        let open = self.pty.read_output(&mut output);
+        if !output.is_empty() {
+            self.screen.write().unwrap().feed(&output);
+        }
+        if !open {
+            self.closed.store(true, Ordering::Release);
+        }
+    }
+}
+
+/// The terminals programs run in, by id. They are kept after their program is gone, until they
+/// are removed.
+#[derive(Default)]
+pub struct TerminalStore {
+    terminals: RwLock<Vec<Arc<Terminal>>>,
+    /// The terminals added since [Self::take_new] was last called
+    new_terminals: Mutex<Vec<u64>>,
+    next_id: AtomicU64,
+}
+
+impl TerminalStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the terminal of `pty`, called `name`. Returns its id.
+    pub fn add(&self, name: &str, pty: Pty) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let terminal = Terminal {
+            id,
+            name: name.to_owned(),
+            screen: RwLock::new(TerminalScreen::new(pty.size())),
+            pty,
+            closed: AtomicBool::new(false),
+        };
+
+        self.terminals.write().unwrap().push(Arc::new(terminal));
+        self.new_terminals.lock().unwrap().push(id);
+        id
+    }
+
+    pub fn get(&self, id: u64) -> Option<Arc<Terminal>> {
+        let terminals_r = self.terminals.read().unwrap();
+        terminals_r
+            .iter()
+            .find(|terminal| terminal.id == id)
+            .cloned()
+    }
+
+    /// The ids of the terminals added since the last call.
+    pub fn take_new(&self) -> Vec<u64> {
+        std::mem::take(&mut *self.new_terminals.lock().unwrap())
+    }
+
+    /// Removes the terminal `id`, hanging up its program if it's still running.
+    pub fn remove(&self, id: u64) {
+        self.terminals
+            .write()
+            .unwrap()
+            .retain(|terminal| terminal.id != id);
+        self.new_terminals
+            .lock()
+            .unwrap()
+            .retain(|new_id| *new_id != id);
+    }
+
+    /// Puts what the programs printed on the screens of their terminals.
+    pub fn read_output(&self) {
+        for terminal in self.terminals.read().unwrap().iter() {
+            terminal.read_output();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CellStyle, TerminalColor, TerminalScreen};
+
+    fn screen_text(screen: &TerminalScreen) -> Vec<String> {
+        let mut lines = Vec::new();
+        screen.get(0..screen.num_lines(), &mut lines);
+        lines.iter().map(|line| line.text()).collect()
+    }
+
+    #[test]
+    fn test_terminal_text() {
+        let mut screen = TerminalScreen::new((24, 12));
+        screen.feed(b"hello\r\nprogress 10%\rprogress 99%\r\n");
+        // Wraps past the last column
+        screen.feed(b"0123456789abcdef\r\na\tb\x08\x08X\r\n");
+        // A character split between two reads
+        screen.feed(&[0xc3]);
+        screen.feed(&[0xa9, b'!']);
+
+        assert_eq!(
+            screen_text(&screen),
+            [
+                "hello",
+                "progress 99%",
+                "0123456789ab",
+                "cdef",
+                "a      Xb",
+                "é!"
+            ]
+        );
+        assert_eq!(screen.cursor(), (5, 2));
+    }
+
+    #[test]
+    fn test_terminal_escape_sequences() {
+        let mut screen = TerminalScreen::new((3, 20));
+        screen.feed(b"\x1b]0;My Program\x07");
+        screen.feed(b"\x1b[1;31mred\x1b[0m plain \x1b[38;5;208morange\x1b[38;2;1;2;3mrgb\r\n");
+        assert_eq!(screen.title(), Some("My Program"));
+
+        let mut lines = Vec::new();
+        screen.get(0..1, &mut lines);
+        let bold_red = CellStyle {
+            foreground: TerminalColor::Indexed(1),
+            bold: true,
+            ..Default::default()
+        };
+        let orange = CellStyle {
+            foreground: TerminalColor::Indexed(208),
+            ..Default::default()
+        };
+        let rgb = CellStyle {
+            foreground: TerminalColor::Rgb(1, 2, 3),
+            ..Default::default()
+        };
+        assert_eq!(
+            lines[0].spans(),
+            [
+                ("red".into(), bold_red),
+                (" plain ".into(), CellStyle::default()),
+                ("orange".into(), orange),
+                ("rgb".into(), rgb),
+            ]
+        );
+
+        screen.feed(b"\x1b[0merase me\x1b[3D\x1b[K\r\n");
+        screen.feed(b"\x1b[1;5Hx\x1b[3;1Hbottom");
+        assert_eq!(
+            screen_text(&screen),
+            ["red xlain orangergb", "erase", "bottom"]
+        );
+        assert_eq!(screen.cursor(), (2, 6));
+
+        // Once the screen is full, rows are counted from the lines at its bottom
+        screen.feed(b"\r\nlast\x1b[1;1Hfirst");
+        assert_eq!(
+            screen_text(&screen),
+            ["red xlain orangergb", "first", "bottom", "last"]
+        );
+
+        // Clearing the screen keeps what was on it in the scrollback
+        screen.feed(b"\x1b[2J\x1b[Hcleared");
+        let text = screen_text(&screen);
+        assert_eq!(text.len(), 7);
+        assert_eq!(text[4], "cleared");
+        screen.feed(b"\x1b[3J");
+        assert_eq!(screen_text(&screen), ["cleared", "", ""]);
+    }
+
+    #[test]
+    fn test_terminal_find() {
+        let mut screen = TerminalScreen::new((24, 80));
+        screen.feed(b"one\nTwo\nthree\ntwo again");
+
+        assert_eq!(screen.find("two", 0, true), Some(1));
+        assert_eq!(screen.find("two", 2, true), Some(3));
+        assert_eq!(screen.find("TWO", 0, false), Some(3));
+        assert_eq!(screen.find("two", 2, false), Some(1));
+        assert_eq!(screen.find("one", 4, true), Some(0));
+        assert_eq!(screen.find("four", 0, true), None);
+        assert_eq!(screen.find("", 0, true), None);
+    }
+}
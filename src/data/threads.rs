@@ -0,0 +1,189 @@
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::dap::message_types::Thread;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ThreadState {
+    Running,
+    /// Stopped, along with what the debugger said about why
+    Stopped {
+        description: Option<String>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct ThreadInfo {
+    pub id: u64,
+    pub name: String,
+    pub state: ThreadState,
+}
+
+#[derive(Default)]
+struct Threads {
+    list: Vec<ThreadInfo>,
+    /// Whether the last stop stopped every thread, in which case threads we learn about before
+    /// it runs again are stopped too
+    all_stopped: bool,
+}
+
+/// The threads of the debuggee, and whether each of them is stopped. The list comes from threads
+/// requests, while the state of each thread is kept up to date by the stopped, continued and
+/// thread events.
+#[derive(Default)]
+pub struct ThreadStore {
+    threads: RwLock<Threads>,
+    /// Bumped every time a thread stops. Responses telling that threads resumed, sent before the
+    /// last stop, are discarded by it.
+    generation: AtomicU64,
+}
+
+impl ThreadStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self) -> Vec<ThreadInfo> {
+        self.threads.read().unwrap().list.clone()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    pub fn any_stopped(&self) -> bool {
+        let threads_r = self.threads.read().unwrap();
+        threads_r
+            .list
+            .iter()
+            .any(|thread| matches!(thread.state, ThreadState::Stopped { .. }))
+    }
+
+    /// Replaces the list with the one from a threads response. Threads that were already known
+    /// keep their state.
+    pub fn set_threads(&self, threads: Vec<Thread>) {
+        let mut threads_w = self.threads.write().unwrap();
+        let new_state = if threads_w.all_stopped {
+            ThreadState::Stopped { description: None }
+        } else {
+            ThreadState::Running
+        };
+
+        let list = threads
+            .into_iter()
+            .map(|thread| {
+                let state = threads_w
+                    .list
+                    .iter()
+                    .find(|known| known.id == thread.id)
+                    .map_or_else(|| new_state.clone(), |known| known.state.clone());
+                ThreadInfo {
+                    id: thread.id,
+                    name: thread.name,
+                    state,
+                }
+            })
+            .collect();
+        threads_w.list = list;
+    }
+
+    /// Adds a thread the debugger told us just started. Its name is only known after the next
+    /// threads request.
+    pub fn add(&self, thread_id: u64) {
+        let mut threads_w = self.threads.write().unwrap();
+        if !threads_w.list.iter().any(|thread| thread.id == thread_id) {
+            threads_w.list.push(ThreadInfo {
+                id: thread_id,
+                name: format!("Thread {thread_id}"),
+                state: ThreadState::Running,
+            });
+        }
+    }
+
+    pub fn remove(&self, thread_id: u64) {
+        let mut threads_w = self.threads.write().unwrap();
+        threads_w.list.retain(|thread| thread.id != thread_id);
+    }
+
+    /// Marks `thread_id` as stopped, or every thread if `all` is set.
+    pub fn set_stopped(&self, thread_id: Option<u64>, all: bool, description: Option<String>) {
+        let mut threads_w = self.threads.write().unwrap();
+        threads_w.all_stopped |= all;
+        for thread in &mut threads_w.list {
+            if all || Some(thread.id) == thread_id {
+                thread.state = ThreadState::Stopped {
+                    description: description.clone().filter(|_| Some(thread.id) == thread_id),
+                };
+            }
+        }
+
+        self.generation.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Marks `thread_id` as running, or every thread if `all` is set. Returns false, changing
+    /// nothing, if a thread stopped after `generation`.
+    pub fn set_running(&self, generation: u64, thread_id: Option<u64>, all: bool) -> bool {
+        let mut threads_w = self.threads.write().unwrap();
+        if self.generation.load(Ordering::Acquire) != generation {
+            return false;
+        }
+
+        threads_w.all_stopped = false;
+        for thread in &mut threads_w.list {
+            if all || Some(thread.id) == thread_id {
+                thread.state = ThreadState::Running;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ThreadState, ThreadStore};
+    use crate::dap::message_types::Thread;
+
+    fn thread(id: u64) -> Thread {
+        Thread {
+            id,
+            name: format!("worker {id}"),
+        }
+    }
+
+    #[test]
+    fn test_thread_states() {
+        let store = ThreadStore::new();
+        store.set_threads(vec![thread(1), thread(2)]);
+        assert!(!store.any_stopped());
+
+        store.set_stopped(Some(2), false, Some("breakpoint".into()));
+        let generation = store.generation();
+        store.add(3);
+        let threads = store.get();
+        assert_eq!(threads[0].state, ThreadState::Running);
+        assert_eq!(
+            threads[1].state,
+            ThreadState::Stopped {
+                description: Some("breakpoint".into())
+            }
+        );
+        assert_eq!(threads[2].name, "Thread 3");
+
+        // Known threads keep their state, new ones stop along with the rest
+        store.set_stopped(Some(1), true, None);
+        store.set_threads(vec![thread(1), thread(3), thread(4)]);
+        let threads = store.get();
+        assert_eq!(threads.len(), 3);
+        assert_eq!(threads[2].state, ThreadState::Stopped { description: None });
+
+        // It stopped again after the request that resumed it was sent
+        assert!(!store.set_running(generation, None, true));
+        assert!(store.any_stopped());
+
+        assert!(store.set_running(store.generation(), Some(3), false));
+        assert!(store.any_stopped());
+        assert!(store.set_running(store.generation(), None, true));
+        assert!(!store.any_stopped());
+    }
+}
@@ -2,7 +2,9 @@ use std::{fmt::Display, str::FromStr};
 
 use serde_with::{DeserializeFromStr, SerializeDisplay};
 
-#[derive(Default, Clone, Copy, PartialEq, Eq, Hash, Debug, SerializeDisplay, DeserializeFromStr)]
+#[derive(
+    Default, Clone, Copy, PartialEq, Eq, Hash, Debug, SerializeDisplay, DeserializeFromStr,
+)]
 pub struct DebugPointer(usize);
 
 impl DebugPointer {
@@ -30,9 +32,28 @@ impl Display for DebugPointer {
 impl FromStr for DebugPointer {
     type Err = std::num::ParseIntError;
 
+    /// Addresses prefixed with `0x` are read as hex, anything else as decimal, which is how DAP
+    /// encodes them.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let number = usize::from_str_radix(s.trim_start_matches("0x"), 16)?;
+        let s = s.trim();
+        let number = if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            usize::from_str_radix(hex, 16)?
+        } else {
+            s.parse()?
+        };
 
         Ok(Self(number))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::DebugPointer;
+
+    #[test]
+    fn test_parse_debug_pointer() {
+        assert_eq!("0x7ffd1000".parse(), Ok(DebugPointer::new(0x7ffd1000)));
+        assert_eq!("4096".parse(), Ok(DebugPointer::new(4096)));
+        assert!("beef".parse::<DebugPointer>().is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use egui::ahash::HashMap;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::dap::message_types::{Scope, Variable};
+
+/// What we know about the scopes of the selected stack frame.
+#[derive(Clone, Default, Debug)]
+pub enum ScopesState {
+    /// Nothing was requested, probably because the program is not stopped.
+    #[default]
+    None,
+    Pending {
+        frame_id: u64,
+    },
+    Loaded {
+        frame_id: u64,
+        scopes: Vec<Scope>,
+    },
+    Failed(String),
+}
+
+/// What we know about the children of a `variablesReference`.
+#[derive(Clone, Debug)]
+pub enum VariablesState {
+    Pending,
+    Loaded(Vec<Variable>),
+    Failed(String),
+}
+
+/// The scopes of the selected stack frame, and all the variables fetched from them so far.
+///
+/// Variable references are only valid while the program is stopped, so everything here is
+/// forgotten once it runs again. Responses to requests sent before that are recognized by their
+/// generation, and discarded.
+#[derive(Default)]
+pub struct VariableStore {
+    scopes: RwLock<ScopesState>,
+    /// Children by the `variablesReference` of their parent
+    variables: RwLock<HashMap<u64, VariablesState>>,
+    /// Bumped every time everything is forgotten
+    generation: AtomicU64,
+}
+
+impl VariableStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_scopes(&self) -> ScopesState {
+        self.scopes.read().unwrap().clone()
+    }
+
+    /// Forgets everything and waits for the scopes of `frame_id`. Returns the generation the
+    /// result should be completed with.
+    pub fn set_scopes_pending(&self, frame_id: u64) -> u64 {
+        let generation = self.invalidate();
+        *self.scopes.write().unwrap() = ScopesState::Pending { frame_id };
+
+        generation
+    }
+
+    /// Completes the scopes request of the given generation. Returns false if the result is
+    /// stale, in which case it is discarded.
+    pub fn complete_scopes(&self, generation: u64, result: Result<Vec<Scope>, String>) -> bool {
+        let mut scopes_w = self.scopes.write().unwrap();
+        if self.generation.load(Ordering::Acquire) != generation {
+            return false;
+        }
+        let ScopesState::Pending { frame_id } = *scopes_w else {
+            return false;
+        };
+        *scopes_w = match result {
+            Ok(scopes) => ScopesState::Loaded { frame_id, scopes },
+            Err(message) => ScopesState::Failed(message),
+        };
+
+        true
+    }
+
+    /// Returns the children of the given reference, or None if they were never requested.
+    pub fn get_variables(&self, variables_reference: u64) -> Option<VariablesState> {
+        self.variables
+            .read()
+            .unwrap()
+            .get(&variables_reference)
+            .cloned()
+    }
+
+    /// Returns the generation the result should be completed with.
+    pub fn set_variables_pending(&self, variables_reference: u64) -> u64 {
+        let mut variables_w = self.variables.write().unwrap();
+        variables_w.insert(variables_reference, VariablesState::Pending);
+
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Completes the variables request for `variables_reference` of the given generation.
+    /// Returns false if the result is stale, in which case it is discarded.
+    pub fn complete_variables(
+        &self,
+        generation: u64,
+        variables_reference: u64,
+        result: Result<Vec<Variable>, String>,
+    ) -> bool {
+        let mut variables_w = self.variables.write().unwrap();
+        if self.generation.load(Ordering::Acquire) != generation {
+            return false;
+        }
+
+        variables_w.insert(
+            variables_reference,
+            match result {
+                Ok(variables) => VariablesState::Loaded(variables),
+                Err(message) => VariablesState::Failed(message),
+            },
+        );
+
+        true
+    }
+
+    /// The generation requests sent now should be completed with.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Changes the variable `name` among the children of `variables_reference`, if it's still
+    /// there in the given generation. The children of the variable are forgotten, as they may
+    /// have changed along with it. Returns false if the change is stale, in which case it is
+    /// discarded.
+    pub fn update_variable(
+        &self,
+        generation: u64,
+        variables_reference: u64,
+        name: &str,
+        update: impl FnOnce(&mut Variable),
+    ) -> bool {
+        let mut variables_w = self.variables.write().unwrap();
+        if self.generation.load(Ordering::Acquire) != generation {
+            return false;
+        }
+        let Some(VariablesState::Loaded(variables)) = variables_w.get_mut(&variables_reference)
+        else {
+            return false;
+        };
+        let Some(variable) = variables.iter_mut().find(|variable| variable.name == name) else {
+            return false;
+        };
+
+        let old_reference = variable.variables_reference;
+        update(variable);
+        let new_reference = variable.variables_reference;
+        variables_w.remove(&old_reference);
+        variables_w.remove(&new_reference);
+
+        true
+    }
+
+    /// Forgets all the scopes and variables. Returns the new generation.
+    pub fn invalidate(&self) -> u64 {
+        let mut scopes_w = self.scopes.write().unwrap();
+        let mut variables_w = self.variables.write().unwrap();
+        *scopes_w = ScopesState::None;
+        variables_w.clear();
+
+        self.generation.fetch_add(1, Ordering::AcqRel) + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VariableStore, VariablesState};
+    use crate::dap::message_types::Variable;
+
+    fn variable(name: &str, value: &str, variables_reference: u64) -> Variable {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "value": value,
+            "variablesReference": variables_reference,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_update_variable() {
+        let store = VariableStore::new();
+        let generation = store.set_variables_pending(1);
+        store.complete_variables(
+            generation,
+            1,
+            Ok(vec![variable("x", "4", 0), variable("p", "0x10", 2)]),
+        );
+        store.complete_variables(generation, 2, Ok(vec![variable("*p", "7", 0)]));
+
+        let set_value = |value: &str| {
+            let value = value.to_owned();
+            move |variable: &mut Variable| variable.value = value
+        };
+        assert!(store.update_variable(generation, 1, "p", set_value("0x20")));
+        let Some(VariablesState::Loaded(variables)) = store.get_variables(1) else {
+            panic!("the variables should still be loaded");
+        };
+        assert_eq!(variables[1].value, "0x20");
+        // What it pointed to is fetched again
+        assert!(store.get_variables(2).is_none());
+
+        // Once the program runs, the change is about variables that are gone
+        store.invalidate();
+        assert!(!store.update_variable(generation, 1, "x", set_value("5")));
+    }
+}
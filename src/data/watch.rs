@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Where the watch expressions are kept, relative to the workspace folder.
+pub const WATCH_FILE: &str = ".memvisor/watch.json";
+
+#[derive(Default, Serialize, Deserialize)]
+struct WatchFile {
+    #[serde(default)]
+    expressions: Vec<String>,
+}
+
+/// Reads the watch expressions of the workspace. There are none if the file does not exist.
+pub fn load_watch_expressions(workspace_folder: &Path) -> Result<Vec<String>, String> {
+    let path = workspace_folder.join(WATCH_FILE);
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("{WATCH_FILE}: {e}")),
+    };
+
+    let file: WatchFile = serde_json::from_str(&text).map_err(|e| format!("{WATCH_FILE}: {e}"))?;
+    Ok(file.expressions)
+}
+
+/// Writes the watch expressions of the workspace, creating the `.memvisor` folder if needed.
+pub fn save_watch_expressions(
+    workspace_folder: &Path,
+    expressions: &[String],
+) -> Result<(), String> {
+    let path = workspace_folder.join(WATCH_FILE);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("{WATCH_FILE}: {e}"))?;
+    }
+
+    let file = WatchFile {
+        expressions: expressions.to_vec(),
+    };
+    let text = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    std::fs::write(&path, text).map_err(|e| format!("{WATCH_FILE}: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_watch_expressions, save_watch_expressions};
+
+    #[test]
+    fn test_watch_file() {
+        let workspace = std::env::temp_dir().join(format!("memvisor-watch-{}", std::process::id()));
+        assert_eq!(load_watch_expressions(&workspace), Ok(Vec::new()));
+
+        let expressions = vec!["self.len".to_owned(), "*ptr".to_owned()];
+        save_watch_expressions(&workspace, &expressions).unwrap();
+        assert_eq!(load_watch_expressions(&workspace), Ok(expressions));
+
+        std::fs::remove_dir_all(&workspace).unwrap();
+    }
+}
@@ -291,6 +291,7 @@ impl ApplicationHandler for MemVisorApp {
         match event {
             WindowEvent::CloseRequested => {
                 println!("The close button was pressed; stopping");
+                w_state.dap_interface.shutdown();
                 event_loop.exit();
             }
             WindowEvent::RedrawRequested => {
@@ -1,27 +1,83 @@
-use crate::dap::dap_interface::{ContinueMode, DapInterface};
-use crate::widget::SourceListing;
+use crate::dap::DapError;
+use crate::dap::dap_interface::{ContinueMode, DapInterface, DebugSession, DebugState, StepKind};
+use crate::dap::message::StepInTargetsResponseBody;
+use crate::dap::message_types::{StepInTarget, SteppingGranularity};
+use crate::dap::requests::RequestHandle;
+use crate::dap::transport::DapTransport;
+use crate::data::launch::{
+    self, LaunchConfig, LaunchRequest, LaunchVariables, core_dump_body, load_launch_configs,
+};
+use crate::widget::{
+    AttachDialog, BreakpointsView, CallStackView, CoreDumpDialog, DisassemblyView,
+    FunctionBreakpointsDialog, MemoryView, OutputView, SourceListing, TerminalView, ThreadsView,
+    VarView, WatchView,
+};
 use egui::{
-    Align2, Button, Context, Id, Popup, PopupCloseBehavior, RectAlign, Ui, Widget, WidgetText,
+    Align2, Button, ComboBox, Context, Id, Popup, PopupCloseBehavior, RectAlign, Response, Ui,
+    Widget, WidgetText,
 };
+use egui_dock::tab_viewer::OnCloseResponse;
 use egui_dock::{DockArea, DockState, Style, TabViewer};
-use serde_json::json;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 pub enum AppTab {
     Source(SourceListing),
+    Memory(MemoryView),
+    Variables(VarView),
+    CallStack(CallStackView),
+    Threads(ThreadsView),
+    Output(OutputView),
+    Breakpoints(BreakpointsView),
+    Disassembly(DisassemblyView),
+    Watch(WatchView),
+    Terminal(TerminalView),
 }
 
 impl AppTab {
     pub fn title(&self) -> String {
         match self {
             AppTab::Source(source) => source.filename().into(),
+            AppTab::Memory(memory) => memory.title(),
+            AppTab::Variables(_) => "Variables".into(),
+            AppTab::CallStack(_) => "Call Stack".into(),
+            AppTab::Threads(_) => "Threads".into(),
+            AppTab::Output(_) => "Debug Console".into(),
+            AppTab::Breakpoints(_) => "Breakpoints".into(),
+            AppTab::Disassembly(_) => "Disassembly".into(),
+            AppTab::Watch(_) => "Watch".into(),
+            AppTab::Terminal(terminal) => terminal.title(),
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        match self {
+            AppTab::Source(_)
+            | AppTab::Variables(_)
+            | AppTab::CallStack(_)
+            | AppTab::Threads(_)
+            | AppTab::Output(_)
+            | AppTab::Breakpoints(_)
+            | AppTab::Disassembly(_)
+            | AppTab::Watch(_) => Id::new(self.title()),
+            AppTab::Memory(memory) => memory.id(),
+            AppTab::Terminal(terminal) => terminal.id(),
         }
     }
 
-    pub fn widget(&mut self) -> impl Widget {
+    pub fn ui(&mut self, ui: &mut Ui) -> Response {
         match self {
-            AppTab::Source(source) => source,
+            AppTab::Source(source) => source.ui(ui),
+            AppTab::Memory(memory) => memory.ui(ui),
+            AppTab::Variables(var_view) => var_view.ui(ui),
+            AppTab::CallStack(call_stack) => call_stack.ui(ui),
+            AppTab::Threads(threads) => threads.ui(ui),
+            AppTab::Output(output) => output.ui(ui),
+            AppTab::Breakpoints(breakpoints) => breakpoints.ui(ui),
+            AppTab::Disassembly(disassembly) => disassembly.ui(ui),
+            AppTab::Watch(watch) => watch.ui(ui),
+            AppTab::Terminal(terminal) => terminal.ui(ui),
         }
     }
 }
@@ -36,19 +92,50 @@ impl TabViewer for AppTabViewer {
     }
 
     fn ui(&mut self, ui: &mut Ui, tab: &mut Self::Tab) {
-        tab.widget().ui(ui);
+        tab.ui(ui);
+    }
+
+    fn id(&mut self, tab: &mut Self::Tab) -> Id {
+        tab.id()
+    }
+
+    fn on_close(&mut self, tab: &mut Self::Tab) -> OnCloseResponse {
+        if let AppTab::Terminal(terminal) = tab {
+            terminal.close();
+        }
+        OnCloseResponse::Close
     }
 }
 
 const RENDER_TIME_NUM_SAMPLES: u32 = 10;
 
+/// The launch variable that stands for the tty of a terminal opened for the debuggee.
+const TERMINAL_TTY_VARIABLE: &str = "terminalTty";
+
+/// What the "Step Into..." menu shows while it's open.
+enum StepInTargets {
+    Loading(RequestHandle<StepInTargetsResponseBody>),
+    Loaded(Vec<StepInTarget>),
+    Failed(String),
+}
+
 pub struct MemVisorUi {
-    debugging: bool,
-    selected_tab: usize,
-    tabs: Vec<AppTab>,
     dock_state: DockState<AppTab>,
 
-    zero_t: Instant,
+    workspace_folder: PathBuf,
+    launch_configs: Vec<LaunchConfig>,
+    selected_launch_config: usize,
+    /// Why the last attempt to load the configurations or start debugging failed
+    launch_error: Option<String>,
+    /// Sessions to start again once they are over, along with how they were started
+    relaunch_pending: Vec<(u64, LaunchRequest, serde_json::Value)>,
+    /// The source location last brought up, see [Self::follow_current_location]
+    shown_location: Option<(PathBuf, usize)>,
+    step_in_targets: Option<StepInTargets>,
+    function_breakpoints: FunctionBreakpointsDialog,
+    attach_dialog: AttachDialog,
+    core_dump_dialog: CoreDumpDialog,
+
     last_render_t: Instant,
     render_time_acc: Duration,
     render_time_avg: Duration,
@@ -57,17 +144,318 @@ pub struct MemVisorUi {
 
 impl MemVisorUi {
     pub fn new() -> Self {
-        Self {
-            debugging: false,
-            selected_tab: 0,
-            tabs: Vec::new(),
+        let mut ui = Self {
             dock_state: DockState::new(Vec::new()),
 
-            zero_t: Instant::now(),
+            workspace_folder: std::env::current_dir().unwrap_or_default(),
+            launch_configs: Vec::new(),
+            selected_launch_config: 0,
+            launch_error: None,
+            relaunch_pending: Vec::new(),
+            shown_location: None,
+            step_in_targets: None,
+            function_breakpoints: FunctionBreakpointsDialog::new(),
+            attach_dialog: AttachDialog::new(),
+            core_dump_dialog: CoreDumpDialog::new(),
+
             last_render_t: Instant::now(),
             render_time_acc: Duration::new(0, 0),
             render_time_avg: Duration::new(0, 0),
             num_render_time_samples: 0,
+        };
+        ui.reload_launch_configs();
+
+        ui
+    }
+
+    fn reload_launch_configs(&mut self) {
+        let mut errors = Vec::new();
+        self.launch_configs = load_launch_configs(&self.workspace_folder, &mut errors);
+        if self.launch_configs.is_empty() {
+            self.launch_configs.push(LaunchConfig::fallback());
+        }
+        self.selected_launch_config = self
+            .selected_launch_config
+            .min(self.launch_configs.len() - 1);
+
+        for error in &errors {
+            log::error!("Failed to load launch configurations: {error}");
+        }
+        self.launch_error = (!errors.is_empty()).then(|| errors.join("\n"));
+    }
+
+    /// The body of the selected launch configuration, with its variables substituted. If it
+    /// uses `${terminalTty}`, a terminal is opened for the debuggee, and its id returned along
+    /// with the body. It's up to the caller to close it if the session does not start.
+    fn resolve_launch_config(
+        &mut self,
+        dap_interface: &DapInterface,
+    ) -> Result<(serde_json::Value, Option<u64>), String> {
+        let config = &self.launch_configs[self.selected_launch_config];
+        let terminal = if config.uses_variable(TERMINAL_TTY_VARIABLE) {
+            let terminal = dap_interface
+                .open_terminal(&config.name)
+                .map_err(|e| format!("Failed to open a terminal: {e}"))?;
+            Some(terminal)
+        } else {
+            None
+        };
+        let terminal_id = terminal.as_ref().map(|(id, _)| *id);
+        let variables = LaunchVariables {
+            workspace_folder: self.workspace_folder.clone(),
+            file: match self.dock_state.find_active_focused() {
+                Some((_, AppTab::Source(source))) => Some(source.path().to_path_buf()),
+                _ => None,
+            },
+            terminal_tty: terminal.map(|(_, tty)| tty),
+        };
+
+        let body = config
+            .resolve(&variables)
+            .inspect_err(|_| close_terminal(dap_interface, terminal_id))?;
+        Ok((body, terminal_id))
+    }
+
+    /// Starts the debugger for the selected launch configuration.
+    fn start_debugging(&mut self, dap_interface: &DapInterface) -> Result<(), String> {
+        let request = self.launch_configs[self.selected_launch_config].request;
+        let (body, terminal_id) = self.resolve_launch_config(dap_interface)?;
+        self.start_session(dap_interface, request, body, terminal_id)
+    }
+
+    /// Starts the debugger of the selected launch configuration, attaching it to the process
+    /// `pid` instead of doing what the configuration says.
+    fn attach_to_process(&mut self, dap_interface: &DapInterface, pid: u32) -> Result<(), String> {
+        let (mut body, terminal_id) = self.resolve_launch_config(dap_interface)?;
+        if let Some(body) = body.as_object_mut() {
+            body.insert("request".into(), "attach".into());
+            body.insert("pid".into(), pid.into());
+        }
+        self.start_session(dap_interface, LaunchRequest::Attach, body, terminal_id)
+    }
+
+    /// Starts the debugger of the selected launch configuration on the core dump `core` of
+    /// `executable`, instead of doing what the configuration says.
+    fn open_core_dump(
+        &mut self,
+        dap_interface: &DapInterface,
+        executable: &Path,
+        core: &Path,
+    ) -> Result<(), String> {
+        let (config, terminal_id) = self.resolve_launch_config(dap_interface)?;
+        let (request, body) = core_dump_body(&config, executable, core)
+            .inspect_err(|_| close_terminal(dap_interface, terminal_id))?;
+        start_dap(dap_interface, body, terminal_id, move |session, body| {
+            session.open_core_dump(request, body)
+        })
+    }
+
+    /// Starts a session launched or attached with `body`. `terminal_id` is the terminal opened
+    /// for the debuggee, if there's one, which is closed if the session fails to start.
+    fn start_session(
+        &mut self,
+        dap_interface: &DapInterface,
+        request: LaunchRequest,
+        body: serde_json::Value,
+        terminal_id: Option<u64>,
+    ) -> Result<(), String> {
+        start_dap(dap_interface, body, terminal_id, move |session, body| {
+            session.launch(request, body)
+        })
+    }
+
+    /// Restarts the current session. If the debugger can't do it in place, the session is
+    /// ended, and started again once it's over, see [Self::relaunch_if_pending]. A process that
+    /// was attached to is left running, to attach to it again.
+    fn restart_session(&mut self, dap_interface: &DapInterface) -> Result<(), String> {
+        let session = dap_interface.session();
+        let Some((request, body)) = dap_interface.launch_arguments(session.id()) else {
+            return Err("There is no session to restart".into());
+        };
+
+        let result = if session.supports_restart_request() {
+            session.restart(body)
+        } else {
+            let result = match request {
+                LaunchRequest::Launch => session.terminate(),
+                LaunchRequest::Attach => session.detach(),
+            };
+            self.relaunch_pending.push((session.id(), request, body));
+            result
+        };
+        result.map_err(|e| e.to_string())
+    }
+
+    /// Starts the sessions that are over again if they were meant to restart, either because
+    /// we asked for it or because their debugger did.
+    fn relaunch_if_pending(&mut self, dap_interface: &DapInterface) {
+        while let Some((session_id, restart_data)) = dap_interface.take_restart_request() {
+            if let Some((request, mut body)) = dap_interface.launch_arguments(session_id) {
+                if let Some(body) = body.as_object_mut() {
+                    body.insert("__restart".into(), restart_data);
+                }
+                self.relaunch_pending.push((session_id, request, body));
+            }
+        }
+
+        let (over, pending) = std::mem::take(&mut self.relaunch_pending)
+            .into_iter()
+            .partition(|(session_id, _, _)| !dap_interface.is_session_connected(*session_id));
+        self.relaunch_pending = pending;
+        for (_, request, body) in over {
+            self.launch_error = self.start_session(dap_interface, request, body, None).err();
+            if let Some(e) = &self.launch_error {
+                log::error!("{e}");
+            }
+        }
+    }
+
+    /// Brings up the source of the selected frame whenever it changes, opening it if needed.
+    fn follow_current_location(&mut self, dap_interface: &Arc<DapInterface>) {
+        let location = dap_interface.session().get_current_location();
+        if location == self.shown_location {
+            return;
+        }
+        self.shown_location = location.clone();
+        let Some((file, _)) = location else {
+            return;
+        };
+
+        let open_tab = self
+            .dock_state
+            .find_tab_from(|tab| matches!(tab, AppTab::Source(source) if source.path() == file));
+        match open_tab {
+            Some(tab_path) => {
+                let _ = self.dock_state.set_active_tab(tab_path);
+            }
+            None => match SourceListing::load(Arc::clone(dap_interface), &file) {
+                Ok(listing) => self
+                    .dock_state
+                    .push_to_focused_leaf(AppTab::Source(listing)),
+                Err(e) => log::warn!("Failed to open {file:?}: {e}"),
+            },
+        }
+    }
+
+    /// Opens a tab for each terminal the debuggee got since the last frame.
+    fn open_new_terminals(&mut self, dap_interface: &Arc<DapInterface>) {
+        for terminal_id in dap_interface.take_new_terminals() {
+            let view = TerminalView::new(Arc::clone(dap_interface), terminal_id);
+            self.dock_state.push_to_focused_leaf(AppTab::Terminal(view));
+        }
+    }
+
+    /// The buttons that step, continue and pause the program.
+    fn execution_controls_ui(&mut self, ui: &mut Ui, dap_interface: &DapInterface) {
+        let mut result = Ok(());
+        if ui.button("Step Over").clicked() {
+            result = dap_interface.session().request_step(StepKind::Over, None);
+        }
+        if ui.button("Step Into").clicked() {
+            result = dap_interface
+                .session()
+                .request_step(StepKind::In { target_id: None }, None);
+        }
+        if ui.button("Step Out").clicked() {
+            result = dap_interface.session().request_step(StepKind::Out, None);
+        }
+        if ui.button("Continue").clicked() {
+            result = dap_interface
+                .session()
+                .request_continue(ContinueMode::AllThreads);
+        }
+        if ui.button("Pause").clicked() {
+            result = dap_interface.session().request_pause(None);
+        }
+        if dap_interface.session().supports_step_back() {
+            if ui.button("Step Back").clicked() {
+                result = dap_interface.session().request_step(StepKind::Back, None);
+            }
+            if ui.button("Reverse Continue").clicked() {
+                result = dap_interface
+                    .session()
+                    .request_reverse_continue(ContinueMode::AllThreads);
+            }
+        }
+        if let Err(e) = result {
+            log::error!("{e}");
+        }
+
+        if dap_interface.session().supports_step_in_targets() {
+            let menu = ui.menu_button("Step Into...", |ui| {
+                self.step_in_targets_ui(ui, dap_interface)
+            });
+            if menu.inner.is_none() {
+                self.step_in_targets = None;
+            }
+        }
+
+        ui.add_enabled_ui(
+            dap_interface.session().supports_stepping_granularity(),
+            |ui| {
+                let mut granularity = dap_interface.session().stepping_granularity();
+                ComboBox::from_id_salt("stepping-granularity")
+                    .selected_text(granularity_name(granularity))
+                    .show_ui(ui, |ui| {
+                        for option in [
+                            SteppingGranularity::Statement,
+                            SteppingGranularity::Line,
+                            SteppingGranularity::Instruction,
+                        ] {
+                            ui.selectable_value(&mut granularity, option, granularity_name(option));
+                        }
+                    });
+                dap_interface
+                    .session()
+                    .set_stepping_granularity(granularity);
+            },
+        )
+        .response
+        .on_disabled_hover_text("The debugger does not support choosing how far to step");
+    }
+
+    /// Lists the functions that can be stepped into from the selected frame, fetching them the
+    /// first time the menu is shown.
+    fn step_in_targets_ui(&mut self, ui: &mut Ui, dap_interface: &DapInterface) {
+        let targets = self.step_in_targets.get_or_insert_with(|| {
+            match dap_interface.session().request_step_in_targets() {
+                Ok(handle) => StepInTargets::Loading(handle),
+                Err(e) => StepInTargets::Failed(e.to_string()),
+            }
+        });
+        if let StepInTargets::Loading(handle) = targets
+            && let Some(result) = handle.poll()
+        {
+            *targets = match result {
+                Ok(body) => StepInTargets::Loaded(body.targets),
+                Err(e) => StepInTargets::Failed(e.to_string()),
+            };
+        }
+
+        match targets {
+            StepInTargets::Loading(_) => {
+                ui.spinner();
+                ui.ctx().request_repaint();
+            }
+            StepInTargets::Loaded(targets) if targets.is_empty() => {
+                ui.weak("Nothing to step into");
+            }
+            StepInTargets::Loaded(targets) => {
+                for target in targets.iter() {
+                    if ui.button(&target.label).clicked() {
+                        let kind = StepKind::In {
+                            target_id: Some(target.id),
+                        };
+                        if let Err(e) = dap_interface.session().request_step(kind, None) {
+                            log::error!("Failed to step into {}: {e}", target.label);
+                        }
+                        ui.close();
+                    }
+                }
+            }
+            StepInTargets::Failed(e) => {
+                ui.colored_label(ui.style().visuals.error_fg_color, e.as_str());
+            }
         }
     }
 
@@ -83,36 +471,92 @@ impl MemVisorUi {
                     Popup::toggle_id(ctx, popup_id);
                 }
 
+                let view_res = ui.button("View");
+
+                let mut reload = false;
+                ComboBox::from_id_salt("launch-config")
+                    .selected_text(&self.launch_configs[self.selected_launch_config].name)
+                    .show_ui(ui, |ui| {
+                        for (i, config) in self.launch_configs.iter().enumerate() {
+                            ui.selectable_value(&mut self.selected_launch_config, i, &config.name);
+                        }
+                        ui.separator();
+                        reload = ui.button("Reload configurations").clicked();
+                    });
+                if reload {
+                    self.reload_launch_configs();
+                }
+
                 if ui.button("Start").clicked() {
-                    let res = dap_interface.start_dap("rust-gdb", ["-i", "dap"]);
-                    if let Err(err) = res {
-                        log::error!("Start DAP error: {err}");
-                    } else if let Err(e) = dap_interface.launch(
-                        json!({
-                            "name": "launch",
-                            "type": "gdb",
-                            "request": "launch",
-                            "program": "target/debug/memvisor",
-                            "cwd": ".",
-                        })
-                        .to_string(),
-                    ) {
-                        log::error!("Error: {e}");
-                    } else {
-                        self.debugging = true;
+                    self.launch_error = self.start_debugging(&dap_interface).err();
+                    if let Some(e) = &self.launch_error {
+                        log::error!("{e}");
+                    }
+                }
+                if dap_interface.is_connecting() {
+                    ui.spinner().on_hover_text("Connecting to the debugger");
+                }
+                if ui
+                    .button("Attach…")
+                    .on_hover_text("Attach the debugger of this configuration to a process")
+                    .clicked()
+                {
+                    self.attach_dialog.open = true;
+                }
+                if ui
+                    .button("Open Core Dump…")
+                    .on_hover_text("Inspect a core dump with the debugger of this configuration")
+                    .clicked()
+                {
+                    self.core_dump_dialog.open = true;
+                }
+                session_switcher_ui(ui, &dap_interface);
+                let session = dap_interface.session();
+                if session.is_connected() {
+                    if ui
+                        .button("Stop")
+                        .on_hover_text("Stop debugging, terminating the program")
+                        .clicked()
+                        && let Err(e) = session.terminate()
+                    {
+                        log::error!("Failed to stop: {e}");
                     }
+                    let launch_arguments = dap_interface.launch_arguments(session.id());
+                    if ui
+                        .add_enabled(launch_arguments.is_some(), Button::new("Restart"))
+                        .clicked()
+                    {
+                        self.launch_error = self.restart_session(&dap_interface).err();
+                    }
+                    // A launched program is terminated when the session ends, unless the
+                    // debugger can be told otherwise
+                    let can_detach = session.supports_terminate_debuggee()
+                        || matches!(launch_arguments, Some((LaunchRequest::Attach, _)));
+                    if ui
+                        .add_enabled(can_detach, Button::new("Detach"))
+                        .on_hover_text("Stop debugging, leaving the program running")
+                        .on_disabled_hover_text(
+                            "The debugger can't leave a program it launched running",
+                        )
+                        .clicked()
+                        && let Err(e) = session.detach()
+                    {
+                        log::error!("Failed to detach: {e}");
+                    }
+                } else if let Some(exit_code) = session.exit_code() {
+                    ui.weak(format!("Exited with code {exit_code}"));
                 }
 
-                if ui.button("Step").clicked() {
-                    dap_interface
-                        .request_next()
-                        .expect("TODO remove this panic");
+                // Nothing runs in a core dump
+                if session.get_debug_state().is_post_mortem() {
+                    ui.add_enabled_ui(false, |ui| self.execution_controls_ui(ui, &dap_interface));
+                    ui.weak("Post-mortem");
+                } else {
+                    self.execution_controls_ui(ui, &dap_interface);
                 }
 
-                if ui.button("Continue").clicked() {
-                    dap_interface
-                        .request_continue(ContinueMode::AllThreads)
-                        .expect("TODO remove this panic");
+                if let Some(e) = self.launch_error.clone().or_else(|| session.launch_error()) {
+                    ui.colored_label(ui.style().visuals.error_fg_color, e);
                 }
 
                 Popup::menu(&file_res)
@@ -133,13 +577,74 @@ impl MemVisorUi {
                                 if let Ok(listing) =
                                     SourceListing::load(Arc::clone(&dap_interface), &file)
                                 {
-                                    //self.tabs.push(AppTab::Source(listing));
                                     self.dock_state
                                         .push_to_focused_leaf(AppTab::Source(listing));
                                 }
                             }
                         }
                     });
+
+                Popup::menu(&view_res)
+                    .gap(4.0)
+                    .align(RectAlign {
+                        parent: Align2::LEFT_BOTTOM,
+                        child: Align2::LEFT_TOP,
+                    })
+                    .close_behavior(PopupCloseBehavior::CloseOnClick)
+                    .show(|ui| {
+                        ui.set_min_width(120.0);
+                        if ui.add(Button::new("Memory").frame(false)).clicked() {
+                            self.dock_state
+                                .push_to_focused_leaf(AppTab::Memory(MemoryView::new(Arc::clone(
+                                    &dap_interface,
+                                ))));
+                        }
+                        if ui.add(Button::new("Variables").frame(false)).clicked() {
+                            self.dock_state
+                                .push_to_focused_leaf(AppTab::Variables(VarView::new(Arc::clone(
+                                    &dap_interface,
+                                ))));
+                        }
+                        if ui.add(Button::new("Watch").frame(false)).clicked() {
+                            self.dock_state
+                                .push_to_focused_leaf(AppTab::Watch(WatchView::new(
+                                    Arc::clone(&dap_interface),
+                                    self.workspace_folder.clone(),
+                                )));
+                        }
+                        if ui.add(Button::new("Call Stack").frame(false)).clicked() {
+                            self.dock_state.push_to_focused_leaf(AppTab::CallStack(
+                                CallStackView::new(Arc::clone(&dap_interface)),
+                            ));
+                        }
+                        if ui.add(Button::new("Threads").frame(false)).clicked() {
+                            self.dock_state.push_to_focused_leaf(AppTab::Threads(
+                                ThreadsView::new(Arc::clone(&dap_interface)),
+                            ));
+                        }
+                        if ui.add(Button::new("Disassembly").frame(false)).clicked() {
+                            self.dock_state.push_to_focused_leaf(AppTab::Disassembly(
+                                DisassemblyView::new(Arc::clone(&dap_interface)),
+                            ));
+                        }
+                        if ui.add(Button::new("Breakpoints").frame(false)).clicked() {
+                            self.dock_state.push_to_focused_leaf(AppTab::Breakpoints(
+                                BreakpointsView::new(Arc::clone(&dap_interface)),
+                            ));
+                        }
+                        if ui
+                            .add(Button::new("Function Breakpoints").frame(false))
+                            .clicked()
+                        {
+                            self.function_breakpoints.open = true;
+                        }
+                        if ui.add(Button::new("Debug Console").frame(false)).clicked() {
+                            self.dock_state
+                                .push_to_focused_leaf(AppTab::Output(OutputView::new(Arc::clone(
+                                    &dap_interface,
+                                ))));
+                        }
+                    });
             });
         });
 
@@ -164,6 +669,28 @@ impl MemVisorUi {
             });
         });
 
+        if let Some(e) = dap_interface.take_start_error() {
+            self.launch_error = Some(e);
+        }
+        self.relaunch_if_pending(&dap_interface);
+        self.open_new_terminals(&dap_interface);
+        self.follow_current_location(&dap_interface);
+        self.function_breakpoints.show(ctx, &dap_interface);
+        if let Some(pid) = self.attach_dialog.show(ctx) {
+            self.launch_error = self.attach_to_process(&dap_interface, pid).err();
+            if let Some(e) = &self.launch_error {
+                log::error!("{e}");
+            }
+        }
+        if let Some((executable, core)) = self.core_dump_dialog.show(ctx) {
+            self.launch_error = self
+                .open_core_dump(&dap_interface, &executable, &core)
+                .err();
+            if let Some(e) = &self.launch_error {
+                log::error!("{e}");
+            }
+        }
+
         egui::CentralPanel::default().show_inside(ui, |ui| {
             DockArea::new(&mut self.dock_state)
                 .style(Style::from_egui(ui.style().as_ref()))
@@ -171,3 +698,84 @@ impl MemVisorUi {
         });
     }
 }
+
+/// Picks the session the views show and the controls act on, out of those that started.
+fn session_switcher_ui(ui: &mut Ui, dap_interface: &DapInterface) {
+    let sessions = dap_interface.sessions();
+    if sessions.is_empty() {
+        return;
+    }
+
+    let current = dap_interface.session();
+    let mut selected = current.id();
+    ComboBox::from_id_salt("session")
+        .selected_text(current.name())
+        .show_ui(ui, |ui| {
+            for session in &sessions {
+                let label = format!("{} ({})", session.name(), session_status(session));
+                ui.selectable_value(&mut selected, session.id(), label);
+            }
+        })
+        .response
+        .on_hover_text("The session shown");
+    if selected != current.id() {
+        dap_interface.select_session(selected);
+    }
+}
+
+/// What a session is doing, in a word.
+fn session_status(session: &DebugSession) -> &'static str {
+    if !session.is_connected() {
+        return if session.launch_error().is_some() {
+            "failed"
+        } else {
+            "ended"
+        };
+    }
+    match session.get_debug_state() {
+        DebugState::NotInitialized => "starting",
+        DebugState::Ready | DebugState::Running => "running",
+        DebugState::Paused { .. } => "paused",
+        DebugState::Stopped { .. } => "stopped",
+        DebugState::PostMortem { .. } => "post-mortem",
+    }
+}
+
+/// Connects to the debugger of the configuration `body` in the background, for a new session
+/// that `on_connected` launches with `body`. The terminal `terminal_id` is closed if it fails.
+fn start_dap<F>(
+    dap_interface: &DapInterface,
+    body: serde_json::Value,
+    terminal_id: Option<u64>,
+    on_connected: F,
+) -> Result<(), String>
+where
+    F: FnOnce(&DebugSession, serde_json::Value) -> Result<(), DapError> + Send + 'static,
+{
+    let transport = DapTransport::from_launch_config(&body)
+        .map_err(|e| e.to_string())
+        .inspect_err(|_| close_terminal(dap_interface, terminal_id))?;
+    let name = launch::config_name(&body)
+        .unwrap_or_else(|| launch::adapter_id(&body))
+        .to_owned();
+    dap_interface.start_dap(transport, &name, terminal_id, move |session| {
+        on_connected(session, body)
+    });
+
+    Ok(())
+}
+
+/// Closes the terminal opened for a session that did not start, if there's one.
+fn close_terminal(dap_interface: &DapInterface, terminal_id: Option<u64>) {
+    if let Some(terminal_id) = terminal_id {
+        dap_interface.close_terminal(terminal_id);
+    }
+}
+
+fn granularity_name(granularity: SteppingGranularity) -> &'static str {
+    match granularity {
+        SteppingGranularity::Statement => "Statement",
+        SteppingGranularity::Line => "Line",
+        SteppingGranularity::Instruction => "Instruction",
+    }
+}
@@ -0,0 +1,123 @@
+use crate::data::processes::{ProcessInfo, list_processes};
+use egui::{Button, Context, Grid, ScrollArea, TextEdit, Ui, Window};
+use std::time::SystemTime;
+
+/// A window to pick a running process to attach to.
+#[derive(Default)]
+pub struct AttachDialog {
+    pub open: bool,
+    filter: String,
+    /// The processes as they were when the list was last read
+    processes: Option<Result<Vec<ProcessInfo>, String>>,
+    selected: Option<u32>,
+}
+
+impl AttachDialog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shows the window if it's open. Returns the pid of the process to attach to once one is
+    /// chosen, closing the window.
+    pub fn show(&mut self, ctx: &Context) -> Option<u32> {
+        if !self.open {
+            // Read again the next time it's opened
+            self.processes = None;
+            return None;
+        }
+
+        let mut open = self.open;
+        let mut chosen = None;
+        Window::new("Attach to Process")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([640.0, 400.0])
+            .show(ctx, |ui| chosen = self.contents_ui(ui));
+        self.open = open && chosen.is_none();
+
+        chosen
+    }
+
+    fn contents_ui(&mut self, ui: &mut Ui) -> Option<u32> {
+        let processes = self.processes.get_or_insert_with(list_processes);
+
+        let mut chosen = None;
+        let mut refresh = false;
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.filter).hint_text("Filter by pid, user or command"),
+            );
+            refresh = ui.button("Refresh").clicked();
+            let attach = ui.add_enabled(self.selected.is_some(), Button::new("Attach"));
+            if attach.clicked() {
+                chosen = self.selected;
+            }
+        });
+        ui.separator();
+
+        match processes {
+            Ok(processes) => {
+                let now = SystemTime::now();
+                ScrollArea::both().auto_shrink(false).show(ui, |ui| {
+                    Grid::new("attach-processes")
+                        .num_columns(4)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("PID");
+                            ui.strong("User");
+                            ui.strong("Started");
+                            ui.strong("Command");
+                            ui.end_row();
+
+                            for process in processes.iter().filter(|p| p.matches(&self.filter)) {
+                                let is_selected = self.selected == Some(process.pid);
+                                let response =
+                                    ui.selectable_label(is_selected, process.pid.to_string());
+                                if response.clicked() {
+                                    self.selected = Some(process.pid);
+                                }
+                                // Picking it twice is enough to attach
+                                if response.double_clicked() {
+                                    chosen = Some(process.pid);
+                                }
+                                ui.label(&process.user);
+                                match process.start_time {
+                                    Some(start_time) => ui.label(format_age(now, start_time)),
+                                    None => ui.weak("?"),
+                                };
+                                ui.monospace(&process.command_line);
+                                ui.end_row();
+                            }
+                        });
+                });
+            }
+            Err(e) => {
+                ui.colored_label(ui.style().visuals.error_fg_color, e.as_str());
+            }
+        }
+
+        if refresh {
+            self.processes = None;
+        }
+        chosen
+    }
+}
+
+/// How long ago `start_time` was, in its two largest units.
+fn format_age(now: SystemTime, start_time: SystemTime) -> String {
+    let seconds = now
+        .duration_since(start_time)
+        .map(|age| age.as_secs())
+        .unwrap_or(0);
+    let (days, hours, minutes) = (seconds / 86400, seconds / 3600 % 24, seconds / 60 % 60);
+
+    if days > 0 {
+        format!("{days}d {hours:02}h ago")
+    } else if hours > 0 {
+        format!("{hours}h {minutes:02}m ago")
+    } else if minutes > 0 {
+        format!("{minutes}m {:02}s ago", seconds % 60)
+    } else {
+        format!("{seconds}s ago")
+    }
+}
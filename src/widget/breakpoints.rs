@@ -0,0 +1,230 @@
+use crate::dap::dap_interface::{DapInterface, DebugState};
+use crate::dap::message_types::ExceptionBreakpointsFilter;
+use crate::data::breakpoints::{
+    Breakpoint, DataBreakpoint, ExceptionBreakpoint, FunctionBreakpoint, InstructionBreakpoint,
+};
+use crate::widget::access_type_label;
+use egui::ahash::HashMap;
+use egui::{Key, Response, RichText, ScrollArea, TextEdit, Ui, Widget};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Lists every breakpoint, highlighting the one the program stopped at.
+pub struct BreakpointsView {
+    dap_interface: Arc<DapInterface>,
+    /// The lists shown on the last frame, kept around to not allocate every frame
+    files: Vec<PathBuf>,
+    file_breakpoints: Vec<Breakpoint>,
+    function_breakpoints: Vec<FunctionBreakpoint>,
+    data_breakpoints: Vec<DataBreakpoint>,
+    exception_breakpoints: Vec<ExceptionBreakpoint>,
+    instruction_breakpoints: Vec<InstructionBreakpoint>,
+    /// The conditions being typed for each exception filter
+    exception_conditions: HashMap<String, String>,
+}
+
+impl BreakpointsView {
+    pub fn new(dap_interface: Arc<DapInterface>) -> Self {
+        Self {
+            dap_interface,
+            files: Vec::new(),
+            file_breakpoints: Vec::new(),
+            function_breakpoints: Vec::new(),
+            data_breakpoints: Vec::new(),
+            exception_breakpoints: Vec::new(),
+            instruction_breakpoints: Vec::new(),
+            exception_conditions: HashMap::default(),
+        }
+    }
+
+    /// Shows an exception filter of the debugger as a toggle, along with its condition if it
+    /// can have one.
+    fn exception_filter_ui(
+        &mut self,
+        ui: &mut Ui,
+        filter: &ExceptionBreakpointsFilter,
+        hit_id: u64,
+    ) {
+        let enabled_bp = self
+            .exception_breakpoints
+            .iter()
+            .find(|bp| bp.filter_id == filter.filter);
+        let can_have_condition = filter.supports_condition.unwrap_or(false)
+            && self
+                .dap_interface
+                .session()
+                .supports_exception_filter_options();
+        let condition = self
+            .exception_conditions
+            .entry(filter.filter.clone())
+            .or_insert_with(|| {
+                enabled_bp
+                    .and_then(|bp| bp.condition.clone())
+                    .unwrap_or_default()
+            });
+
+        let mut result = Ok(());
+        ui.horizontal(|ui| {
+            let mut enabled = enabled_bp.is_some();
+            let mut label = RichText::new(&filter.label);
+            if enabled_bp.is_some_and(|bp| bp.breakpoint_id != 0 && bp.breakpoint_id == hit_id) {
+                label = label.background_color(ui.style().visuals.selection.bg_fill);
+            }
+            let mut response = ui.checkbox(&mut enabled, label);
+            if let Some(description) = &filter.description {
+                response = response.on_hover_text(description);
+            }
+
+            let mut submitted = false;
+            if can_have_condition {
+                let hint = filter
+                    .condition_description
+                    .as_deref()
+                    .unwrap_or("Condition");
+                let input_res = ui.add(TextEdit::singleline(condition).hint_text(hint));
+                submitted = input_res.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+            }
+
+            let breakpoint = Breakpoint::Exception(ExceptionBreakpoint {
+                filter_id: filter.filter.clone(),
+                condition: (can_have_condition && !condition.trim().is_empty())
+                    .then(|| condition.clone()),
+                ..Default::default()
+            });
+            if response.changed() && !enabled {
+                result = self.dap_interface.remove_breakpoint(&breakpoint);
+            } else if response.changed() || (submitted && enabled) {
+                result = self.dap_interface.put_breakpoint(breakpoint);
+            }
+        });
+
+        if let Err(e) = result {
+            log::error!("Failed to set exception breakpoint: {e}");
+        }
+    }
+
+    /// Shows a breakpoint in a row with a button to remove it, along with whether the debugger
+    /// could set it.
+    fn breakpoint_ui(&self, ui: &mut Ui, breakpoint: &Breakpoint, text: String, hit_id: u64) {
+        let id = breakpoint.breakpoint_id();
+
+        ui.horizontal(|ui| {
+            if ui.small_button("Remove").clicked()
+                && let Err(e) = self.dap_interface.remove_breakpoint(breakpoint)
+            {
+                log::error!("Failed to remove breakpoint: {e}");
+            }
+
+            let mut text = RichText::new(text).monospace();
+            if id != 0 && id == hit_id {
+                text = text.background_color(ui.style().visuals.selection.bg_fill);
+            }
+            ui.label(text);
+
+            match self.dap_interface.session().get_breakpoint_data(id) {
+                Some(data) if data.verified => {}
+                Some(data) => {
+                    let response = ui.colored_label(ui.style().visuals.warn_fg_color, "Not set");
+                    if let Some(message) = &data.message {
+                        response.on_hover_text(message);
+                    }
+                }
+                None => {
+                    ui.weak("Pending");
+                }
+            }
+        });
+    }
+}
+
+impl Widget for &mut BreakpointsView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let _span = tracy_client::span!("ui_update_breakpoints");
+
+        let hit_id = match self.dap_interface.session().get_debug_state() {
+            DebugState::Stopped {
+                breakpoint: Some(breakpoint),
+                ..
+            } => breakpoint.breakpoint_id(),
+            _ => 0,
+        };
+
+        self.dap_interface
+            .session()
+            .get_files_with_breakpoints(&mut self.files);
+        self.dap_interface
+            .session()
+            .get_function_breakpoints(&mut self.function_breakpoints);
+        self.dap_interface
+            .session()
+            .get_data_breakpoints(&mut self.data_breakpoints);
+        self.dap_interface
+            .session()
+            .get_exception_breakpoints(&mut self.exception_breakpoints);
+        self.dap_interface
+            .session()
+            .get_instruction_breakpoints(&mut self.instruction_breakpoints);
+        let exception_filters = self.dap_interface.session().exception_filters();
+
+        ScrollArea::both().auto_shrink(false).show(ui, |ui| {
+            if !exception_filters.is_empty() {
+                ui.strong("Exceptions");
+                for filter in &exception_filters {
+                    self.exception_filter_ui(ui, filter, hit_id);
+                }
+                ui.separator();
+            }
+
+            let mut empty = true;
+
+            for file in &self.files {
+                self.dap_interface
+                    .session()
+                    .get_file_breakpoints(file, &mut self.file_breakpoints);
+                for breakpoint in &self.file_breakpoints {
+                    let Breakpoint::Source(code_bp) = breakpoint else {
+                        continue;
+                    };
+                    let name = file
+                        .file_name()
+                        .map(|name| name.to_string_lossy())
+                        .unwrap_or(file.to_string_lossy());
+                    let text = format!("{name}:{}", code_bp.lineno);
+                    self.breakpoint_ui(ui, breakpoint, text, hit_id);
+                    empty = false;
+                }
+            }
+
+            for function_bp in &self.function_breakpoints {
+                let text = format!("{}()", function_bp.name);
+                let breakpoint = Breakpoint::Function(function_bp.clone());
+                self.breakpoint_ui(ui, &breakpoint, text, hit_id);
+                empty = false;
+            }
+
+            for data_bp in &self.data_breakpoints {
+                let text = format!(
+                    "{}: {}",
+                    data_bp.description,
+                    access_type_label(data_bp.access_type)
+                );
+                let breakpoint = Breakpoint::Data(data_bp.clone());
+                self.breakpoint_ui(ui, &breakpoint, text, hit_id);
+                empty = false;
+            }
+
+            for instruction_bp in &self.instruction_breakpoints {
+                let text = format!("Instruction at {}", instruction_bp.address);
+                let breakpoint = Breakpoint::Instruction(instruction_bp.clone());
+                self.breakpoint_ui(ui, &breakpoint, text, hit_id);
+                empty = false;
+            }
+
+            if empty {
+                ui.weak("No breakpoints");
+            }
+        });
+
+        ui.response()
+    }
+}
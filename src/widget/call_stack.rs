@@ -0,0 +1,121 @@
+use crate::dap::dap_interface::DapInterface;
+use crate::dap::message_types::{StackFrame, StackFramePresentationHint};
+use egui::{Button, Response, RichText, ScrollArea, Ui, Widget};
+use std::path::Path;
+use std::sync::Arc;
+
+pub struct CallStackView {
+    dap_interface: Arc<DapInterface>,
+}
+
+impl CallStackView {
+    pub fn new(dap_interface: Arc<DapInterface>) -> Self {
+        Self { dap_interface }
+    }
+
+    fn frame_ui(&self, ui: &mut Ui, frame: &StackFrame, selected: bool) {
+        let location = frame
+            .source
+            .as_ref()
+            .and_then(|source| source.path.as_deref().or(source.name.as_deref()))
+            .map(|path| {
+                let name = Path::new(path)
+                    .file_name()
+                    .map(|name| name.to_string_lossy())
+                    .unwrap_or(path.into());
+                format!("{name}:{}", frame.line)
+            });
+
+        match frame.presentation_hint {
+            // Labels are not frames, just something to tell frames apart
+            Some(StackFramePresentationHint::Label) => {
+                ui.label(RichText::new(&frame.name).italics().weak());
+            }
+            hint => {
+                let subtle = hint == Some(StackFramePresentationHint::Subtle);
+                let mut text = RichText::new(&frame.name).monospace();
+                if subtle {
+                    text = text.weak();
+                }
+
+                ui.horizontal(|ui| {
+                    let response = ui.selectable_label(selected, text);
+                    if let Some(location) = &location {
+                        ui.weak(location);
+                    }
+
+                    if response.clicked()
+                        && !selected
+                        && let Err(e) = self.dap_interface.session().select_frame(frame.id)
+                    {
+                        log::error!("Failed to select frame: {e}");
+                    }
+                    response.context_menu(|ui| self.frame_menu_ui(ui, frame));
+                });
+            }
+        }
+    }
+
+    fn frame_menu_ui(&self, ui: &mut Ui, frame: &StackFrame) {
+        let disabled_reason = if !self.dap_interface.session().supports_restart_frame() {
+            Some("The debugger does not support restarting frames")
+        } else if self
+            .dap_interface
+            .session()
+            .get_debug_state()
+            .is_post_mortem()
+        {
+            Some("Nothing runs in a core dump")
+        } else if frame.can_restart == Some(false) {
+            Some("This frame can't be restarted")
+        } else {
+            None
+        };
+
+        let mut response = ui.add_enabled(disabled_reason.is_none(), Button::new("Restart Frame"));
+        if let Some(reason) = disabled_reason {
+            response = response.on_disabled_hover_text(reason);
+        }
+        if response.clicked() {
+            if let Err(e) = self.dap_interface.session().request_restart_frame(frame.id) {
+                log::error!("Failed to restart frame: {e}");
+            }
+            ui.close();
+        }
+    }
+}
+impl Widget for &mut CallStackView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let _span = tracy_client::span!("ui_update_call_stack");
+
+        let stack = self.dap_interface.session().get_call_stack();
+        let selected_frame_id = self.dap_interface.session().selected_frame_id();
+
+        ScrollArea::both().auto_shrink(false).show(ui, |ui| {
+            if stack.thread_id.is_none() {
+                ui.weak("The program is not stopped");
+            }
+
+            for frame in &stack.frames {
+                self.frame_ui(ui, frame, selected_frame_id == Some(frame.id));
+            }
+
+            if let Some(error) = &stack.error {
+                ui.colored_label(ui.style().visuals.error_fg_color, error);
+            }
+
+            if stack.loading {
+                ui.spinner();
+                ui.ctx().request_repaint();
+            } else if !stack.complete
+                && stack.thread_id.is_some()
+                && ui.button("Load more frames").clicked()
+                && let Err(e) = self.dap_interface.session().load_more_frames()
+            {
+                log::error!("Failed to load more frames: {e}");
+            }
+        });
+
+        ui.response()
+    }
+}
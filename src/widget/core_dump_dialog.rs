@@ -0,0 +1,69 @@
+use egui::{Button, Context, Grid, TextEdit, Ui, Window};
+use std::path::PathBuf;
+
+/// A window to pick a core dump, along with the executable that produced it.
+#[derive(Default)]
+pub struct CoreDumpDialog {
+    pub open: bool,
+    executable: String,
+    core: String,
+}
+
+impl CoreDumpDialog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shows the window if it's open. Returns the executable and the core dump once both are
+    /// chosen, closing the window. They are kept for the next time it's opened.
+    pub fn show(&mut self, ctx: &Context) -> Option<(PathBuf, PathBuf)> {
+        if !self.open {
+            return None;
+        }
+
+        let mut open = self.open;
+        let mut chosen = None;
+        Window::new("Open Core Dump")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| chosen = self.contents_ui(ui));
+        self.open = open && chosen.is_none();
+
+        chosen
+    }
+
+    fn contents_ui(&mut self, ui: &mut Ui) -> Option<(PathBuf, PathBuf)> {
+        Grid::new("core-dump-paths").num_columns(3).show(ui, |ui| {
+            ui.label("Executable");
+            path_ui(ui, &mut self.executable);
+            ui.end_row();
+
+            ui.label("Core dump");
+            path_ui(ui, &mut self.core);
+            ui.end_row();
+        });
+        ui.separator();
+
+        let executable = self.executable.trim();
+        let core = self.core.trim();
+        let can_open = !executable.is_empty() && !core.is_empty();
+        ui.add_enabled(can_open, Button::new("Open"))
+            .clicked()
+            .then(|| (PathBuf::from(executable), PathBuf::from(core)))
+    }
+}
+
+/// A field to type a path in, with a button to browse for it instead.
+fn path_ui(ui: &mut Ui, path: &mut String) {
+    ui.add(TextEdit::singleline(path).desired_width(320.0));
+    if ui.button("Browse…").clicked() {
+        let directory = PathBuf::from(path.trim())
+            .parent()
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_dir())
+            .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+        if let Some(file) = rfd::FileDialog::new().set_directory(directory).pick_file() {
+            *path = file.to_string_lossy().into_owned();
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use crate::dap::dap_interface::{DapInterface, DataBreakpointTarget};
+use crate::dap::message::DataBreakpointInfoResponseBody;
+use crate::dap::message_types::DataBreakpointAccessType;
+use crate::dap::requests::RequestHandle;
+use crate::data::breakpoints::{Breakpoint, DataBreakpoint};
+use egui::Ui;
+
+const ACCESS_TYPES: [DataBreakpointAccessType; 3] = [
+    DataBreakpointAccessType::Write,
+    DataBreakpointAccessType::Read,
+    DataBreakpointAccessType::ReadWrite,
+];
+
+enum DataBreakpointInfo {
+    Loading(RequestHandle<DataBreakpointInfoResponseBody>),
+    Loaded(DataBreakpointInfoResponseBody),
+    Failed(String),
+}
+
+/// The contents of a menu to put a data breakpoint on something. What can be watched is asked
+/// to the debugger the first time the menu is shown for it.
+#[derive(Default)]
+pub struct DataBreakpointMenu {
+    info: Option<(DataBreakpointTarget, DataBreakpointInfo)>,
+}
+
+impl DataBreakpointMenu {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets what the debugger said, to be called once the menu is closed.
+    pub fn close(&mut self) {
+        self.info = None;
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, dap_interface: &DapInterface, target: &DataBreakpointTarget) {
+        if self.info.as_ref().is_none_or(|(shown, _)| shown != target) {
+            let info = match dap_interface.session().request_data_breakpoint_info(target) {
+                Ok(handle) => DataBreakpointInfo::Loading(handle),
+                Err(e) => DataBreakpointInfo::Failed(e.to_string()),
+            };
+            self.info = Some((target.clone(), info));
+        }
+        let Some((_, info)) = &mut self.info else {
+            return;
+        };
+
+        if let DataBreakpointInfo::Loading(handle) = info
+            && let Some(result) = handle.poll()
+        {
+            *info = match result {
+                Ok(body) => DataBreakpointInfo::Loaded(body),
+                Err(e) => DataBreakpointInfo::Failed(e.to_string()),
+            };
+        }
+
+        match info {
+            DataBreakpointInfo::Loading(_) => {
+                ui.spinner();
+                ui.ctx().request_repaint();
+            }
+            DataBreakpointInfo::Loaded(body) => match &body.data_id {
+                Some(data_id) => {
+                    ui.weak(&body.description);
+                    // If the debugger does not say, every access type is offered
+                    let access_types = body.access_types.as_deref().unwrap_or(&ACCESS_TYPES);
+                    for access_type in access_types {
+                        if ui.button(access_type_label(*access_type)).clicked() {
+                            let breakpoint = Breakpoint::Data(DataBreakpoint {
+                                data_id: data_id.clone(),
+                                description: body.description.clone(),
+                                access_type: *access_type,
+                                breakpoint_id: 0,
+                            });
+                            if let Err(e) = dap_interface.put_breakpoint(breakpoint) {
+                                log::error!("Failed to set data breakpoint: {e}");
+                            }
+                            ui.close();
+                        }
+                    }
+                }
+                // The description tells why it can't be watched
+                None => {
+                    ui.colored_label(ui.style().visuals.warn_fg_color, &body.description);
+                }
+            },
+            DataBreakpointInfo::Failed(e) => {
+                ui.colored_label(ui.style().visuals.error_fg_color, e.as_str());
+            }
+        }
+    }
+}
+
+pub fn access_type_label(access_type: DataBreakpointAccessType) -> &'static str {
+    match access_type {
+        DataBreakpointAccessType::Read => "Break on read",
+        DataBreakpointAccessType::Write => "Break on write",
+        DataBreakpointAccessType::ReadWrite => "Break on read or write",
+    }
+}
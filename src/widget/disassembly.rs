@@ -0,0 +1,341 @@
+use crate::dap::dap_interface::{DapInterface, StepKind};
+use crate::dap::message::DisassembleResponseBody;
+use crate::dap::message_types::DisassembledInstruction;
+use crate::dap::requests::RequestHandle;
+use crate::data::breakpoints::{Breakpoint, InstructionBreakpoint};
+use crate::data::types::DebugPointer;
+use egui::ahash::HashMap;
+use egui::{Button, Color32, Response, RichText, ScrollArea, TextStyle, Ui, Widget};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// How many instructions are disassembled before and after the instruction pointer
+const INSTRUCTIONS_AROUND: i64 = 64;
+
+/// How close to the ends of the disassembly the instruction pointer can get before the code
+/// around it is disassembled again
+const REFETCH_MARGIN: usize = 8;
+
+enum Disassembly {
+    Loading(RequestHandle<DisassembleResponseBody>),
+    Loaded(Vec<DisassembledInstruction>),
+    Failed(String),
+}
+
+/// What each row of the view shows.
+enum DisassemblyRow {
+    /// A line of the source code the instructions below it come from
+    Source {
+        file: PathBuf,
+        lineno: usize,
+        text: String,
+    },
+    /// The instruction at this index of the disassembly
+    Instruction(usize),
+}
+
+/// Shows the machine code around the instruction pointer of the selected frame, with the source
+/// lines it came from in between.
+pub struct DisassemblyView {
+    dap_interface: Arc<DapInterface>,
+    disassembly: Option<Disassembly>,
+    rows: Vec<DisassemblyRow>,
+    /// The lines of the source files shown in between instructions, None if they can't be read
+    sources: HashMap<PathBuf, Option<Vec<String>>>,
+    /// The instruction pointer the view last scrolled to
+    shown_pointer: Option<DebugPointer>,
+    scroll_to_pointer: bool,
+    /// The breakpoints shown on the last frame, kept around to not allocate every frame
+    breakpoints: Vec<InstructionBreakpoint>,
+}
+
+impl DisassemblyView {
+    pub fn new(dap_interface: Arc<DapInterface>) -> Self {
+        Self {
+            dap_interface,
+            disassembly: None,
+            rows: Vec::new(),
+            sources: HashMap::default(),
+            shown_pointer: None,
+            scroll_to_pointer: false,
+            breakpoints: Vec::new(),
+        }
+    }
+
+    /// Disassembles the code around `pointer`, unless it's well within what was already
+    /// disassembled.
+    fn update_disassembly(&mut self, pointer: DebugPointer) {
+        if let Some(Disassembly::Loaded(instructions)) = &self.disassembly {
+            let index = instructions
+                .iter()
+                .position(|instruction| instruction.address == pointer);
+            if index.is_some_and(|i| i >= REFETCH_MARGIN && i + REFETCH_MARGIN < instructions.len())
+            {
+                return;
+            }
+        }
+
+        self.rows.clear();
+        self.disassembly = Some(
+            match self.dap_interface.session().request_disassemble(
+                pointer,
+                -INSTRUCTIONS_AROUND,
+                2 * INSTRUCTIONS_AROUND as u64,
+            ) {
+                Ok(handle) => Disassembly::Loading(handle),
+                Err(e) => Disassembly::Failed(e.to_string()),
+            },
+        );
+    }
+
+    fn poll_disassembly(&mut self) {
+        if let Some(Disassembly::Loading(handle)) = &self.disassembly
+            && let Some(result) = handle.poll()
+        {
+            self.disassembly = Some(match result {
+                Ok(body) => Disassembly::Loaded(body.instructions),
+                Err(e) => Disassembly::Failed(e.to_string()),
+            });
+            self.build_rows();
+        }
+    }
+
+    /// Lays out the instructions, putting a line of source before each instruction that comes
+    /// from a different line than the one before it.
+    fn build_rows(&mut self) {
+        self.rows.clear();
+        let Some(Disassembly::Loaded(instructions)) = &self.disassembly else {
+            return;
+        };
+
+        // The location is left out when it's the same as the previous instruction's
+        let mut file: Option<PathBuf> = None;
+        let mut last_line = None;
+        for (i, instruction) in instructions.iter().enumerate() {
+            if let Some(path) = instruction.location.as_ref().and_then(|l| l.path.as_ref()) {
+                file = Some(PathBuf::from(path));
+            }
+
+            if let Some(file) = &file
+                && let Some(lineno) = instruction.line
+                && last_line != Some((file.clone(), lineno))
+            {
+                last_line = Some((file.clone(), lineno));
+                let lines = self
+                    .sources
+                    .entry(file.clone())
+                    .or_insert_with(|| read_lines(file));
+                let text = lines
+                    .as_ref()
+                    .and_then(|lines| lines.get(lineno.saturating_sub(1)))
+                    .cloned()
+                    .unwrap_or_default();
+                self.rows.push(DisassemblyRow::Source {
+                    file: file.clone(),
+                    lineno,
+                    text,
+                });
+            }
+
+            self.rows.push(DisassemblyRow::Instruction(i));
+        }
+    }
+
+    fn instruction_address(&self, index: usize) -> Option<DebugPointer> {
+        match &self.disassembly {
+            Some(Disassembly::Loaded(instructions)) => Some(instructions.get(index)?.address),
+            _ => None,
+        }
+    }
+
+    fn instruction_ui(
+        &self,
+        ui: &mut Ui,
+        instruction: &DisassembledInstruction,
+        pointer: Option<DebugPointer>,
+        row_height: f32,
+    ) {
+        let breakpoint = self
+            .breakpoints
+            .iter()
+            .find(|bp| bp.address == instruction.address);
+        let is_current = Some(instruction.address) == pointer;
+
+        egui::Frame::new()
+            .fill(if is_current {
+                Color32::from_rgb(24, 32, 72)
+            } else {
+                ui.style().visuals.window_fill
+            })
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.set_width(ui.available_width());
+
+                    let can_set = self
+                        .dap_interface
+                        .session()
+                        .supports_instruction_breakpoints();
+                    let gutter_res = ui
+                        .add_enabled(
+                            can_set,
+                            Button::selectable(breakpoint.is_some(), "O")
+                                .min_size(egui::vec2(row_height, row_height)),
+                        )
+                        .on_disabled_hover_text(
+                            "The debugger does not support instruction breakpoints",
+                        );
+                    if gutter_res.clicked() {
+                        let result = match breakpoint {
+                            Some(bp) => self
+                                .dap_interface
+                                .remove_breakpoint(&Breakpoint::Instruction(bp.clone())),
+                            None => self.dap_interface.put_breakpoint(Breakpoint::Instruction(
+                                InstructionBreakpoint {
+                                    address: instruction.address,
+                                    breakpoint_id: 0,
+                                },
+                            )),
+                        };
+                        if let Err(e) = result {
+                            log::error!("Failed to toggle instruction breakpoint: {e}");
+                        }
+                    }
+
+                    let bytes = instruction.instruction_bytes.as_deref().unwrap_or("");
+                    let text = format!(
+                        "{:>18}  {bytes:<24} {}",
+                        instruction.address.to_string(),
+                        instruction.instruction
+                    );
+                    let mut text = RichText::new(text).monospace();
+                    if instruction.presentation_hint.as_deref() == Some("invalid") {
+                        text = text.weak();
+                    }
+                    let response = ui.label(text);
+                    if let Some(symbol) = &instruction.symbol {
+                        response.on_hover_text(symbol);
+                    }
+                });
+            });
+    }
+}
+
+impl Widget for &mut DisassemblyView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let _span = tracy_client::span!("ui_update_disassembly");
+
+        let debug_state = self.dap_interface.session().get_debug_state();
+        let pointer = self.dap_interface.session().get_instruction_pointer();
+
+        ui.horizontal(|ui| {
+            let can_step = debug_state.is_stopped()
+                && !debug_state.is_post_mortem()
+                && self.dap_interface.session().supports_stepping_granularity();
+            let mut result = Ok(());
+            ui.add_enabled_ui(can_step, |ui| {
+                if ui.button("Step Instruction").clicked() {
+                    result = self
+                        .dap_interface
+                        .session()
+                        .request_instruction_step(StepKind::Over);
+                }
+                if ui.button("Step Into Instruction").clicked() {
+                    result = self
+                        .dap_interface
+                        .session()
+                        .request_instruction_step(StepKind::In { target_id: None });
+                }
+            })
+            .response
+            .on_disabled_hover_text("The debugger does not support stepping by instruction");
+            if let Err(e) = result {
+                log::error!("Failed to step: {e}");
+            }
+        });
+        ui.separator();
+
+        if debug_state.is_stopped() && !self.dap_interface.session().supports_disassemble() {
+            ui.colored_label(
+                ui.style().visuals.warn_fg_color,
+                "The debugger does not support disassembling",
+            );
+            return ui.response();
+        }
+
+        if pointer != self.shown_pointer {
+            self.shown_pointer = pointer;
+            self.scroll_to_pointer = pointer.is_some();
+            if let Some(pointer) = pointer {
+                self.update_disassembly(pointer);
+            }
+        }
+        self.poll_disassembly();
+
+        match &self.disassembly {
+            None => {
+                ui.weak("The program is not stopped");
+                return ui.response();
+            }
+            Some(Disassembly::Loading(_)) => {
+                ui.spinner();
+                ui.ctx().request_repaint();
+                return ui.response();
+            }
+            Some(Disassembly::Failed(message)) => {
+                ui.colored_label(ui.style().visuals.error_fg_color, message);
+                return ui.response();
+            }
+            Some(Disassembly::Loaded(_)) => {}
+        }
+
+        self.dap_interface
+            .session()
+            .get_instruction_breakpoints(&mut self.breakpoints);
+
+        let row_height = ui.text_style_height(&TextStyle::Monospace);
+        let mut scroll_area = ScrollArea::both().auto_shrink(false);
+        if self.scroll_to_pointer {
+            self.scroll_to_pointer = false;
+            let pointer_row = self.rows.iter().position(|row| match row {
+                DisassemblyRow::Instruction(i) => self.instruction_address(*i) == pointer,
+                DisassemblyRow::Source { .. } => false,
+            });
+            if let Some(row) = pointer_row {
+                let spacing = ui.spacing().item_spacing.y;
+                let offset = row as f32 * (row_height + spacing) - ui.available_height() / 2.0;
+                scroll_area = scroll_area.vertical_scroll_offset(offset.max(0.0));
+            }
+        }
+
+        scroll_area.show_rows(ui, row_height, self.rows.len(), |ui, range| {
+            let Some(Disassembly::Loaded(instructions)) = &self.disassembly else {
+                return;
+            };
+            for row in &self.rows[range] {
+                match row {
+                    DisassemblyRow::Source { file, lineno, text } => {
+                        let name = file
+                            .file_name()
+                            .map(|name| name.to_string_lossy())
+                            .unwrap_or(file.to_string_lossy());
+                        ui.label(
+                            RichText::new(format!("{name}:{lineno}  {}", text.trim()))
+                                .monospace()
+                                .weak(),
+                        );
+                    }
+                    DisassemblyRow::Instruction(i) => {
+                        self.instruction_ui(ui, &instructions[*i], pointer, row_height);
+                    }
+                }
+            }
+        });
+
+        ui.response()
+    }
+}
+
+fn read_lines(file: &Path) -> Option<Vec<String>> {
+    let content = std::fs::read_to_string(file).ok()?;
+    Some(content.lines().map(String::from).collect())
+}
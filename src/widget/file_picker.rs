@@ -1,6 +1,6 @@
 // TODO: We need to implement this eventually
 #![allow(unused)]
-use egui::{CentralPanel, Id, Response, TopBottomPanel, Ui, Widget};
+use egui::{CentralPanel, Id, Response, Ui, Widget};
 use std::fs;
 use std::path::PathBuf;
 
@@ -17,7 +17,7 @@ pub struct FilePicker {
 impl FilePicker {
     pub fn new() -> Self {
         let mut new = Self {
-            cwd: std::env::current_dir().unwrap_or(PathBuf::new()),
+            cwd: std::env::current_dir().unwrap_or_default(),
             dir_listing: Vec::new(),
         };
         let _ = new.refresh_directory().ok();
@@ -42,6 +42,11 @@ impl FilePicker {
         Ok(())
     }
 }
+impl Default for FilePicker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 impl Widget for &mut FilePicker {
     fn ui(self, ui: &mut Ui) -> Response {
         for entry in &self.dir_listing {
@@ -0,0 +1,97 @@
+use crate::dap::dap_interface::{DapInterface, DebugState};
+use crate::data::breakpoints::{Breakpoint, FunctionBreakpoint};
+use egui::{Context, Key, TextEdit, Ui, Window};
+
+/// A window to add and remove breakpoints on functions.
+#[derive(Default)]
+pub struct FunctionBreakpointsDialog {
+    pub open: bool,
+    /// The name being typed
+    name: String,
+    /// The breakpoints shown on the last frame, kept around to not allocate every frame
+    breakpoints: Vec<FunctionBreakpoint>,
+}
+
+impl FunctionBreakpointsDialog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn show(&mut self, ctx: &Context, dap_interface: &DapInterface) {
+        let mut open = self.open;
+        Window::new("Function Breakpoints")
+            .open(&mut open)
+            .resizable(true)
+            .show(ctx, |ui| self.contents_ui(ui, dap_interface));
+        self.open = open;
+    }
+
+    fn contents_ui(&mut self, ui: &mut Ui, dap_interface: &DapInterface) {
+        // Until the debugger is initialized we don't know, they are sent once it is
+        let initialized = !matches!(
+            dap_interface.session().get_debug_state(),
+            DebugState::NotInitialized
+        );
+        if initialized && !dap_interface.session().supports_function_breakpoints() {
+            ui.colored_label(
+                ui.style().visuals.warn_fg_color,
+                "The debugger does not support function breakpoints",
+            );
+        }
+
+        let mut result = Ok(());
+        ui.horizontal(|ui| {
+            let response =
+                ui.add(TextEdit::singleline(&mut self.name).hint_text("my_crate::Foo::bar"));
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+            let name = self.name.trim();
+
+            if (ui.button("Add").clicked() || submitted) && !name.is_empty() {
+                let breakpoint = Breakpoint::Function(FunctionBreakpoint::new(name));
+                result = dap_interface.put_breakpoint(breakpoint);
+                self.name.clear();
+            }
+        });
+        ui.separator();
+
+        dap_interface
+            .session()
+            .get_function_breakpoints(&mut self.breakpoints);
+        if self.breakpoints.is_empty() {
+            ui.weak("No function breakpoints");
+        }
+
+        for breakpoint in &self.breakpoints {
+            ui.horizontal(|ui| {
+                if ui.small_button("Remove").clicked() {
+                    result =
+                        dap_interface.remove_breakpoint(&Breakpoint::Function(breakpoint.clone()));
+                }
+                ui.monospace(&breakpoint.name);
+
+                match dap_interface
+                    .session()
+                    .get_breakpoint_data(breakpoint.breakpoint_id)
+                {
+                    Some(data) if data.verified => {
+                        ui.weak("Set");
+                    }
+                    Some(data) => {
+                        let response =
+                            ui.colored_label(ui.style().visuals.warn_fg_color, "Not set");
+                        if let Some(message) = &data.message {
+                            response.on_hover_text(message);
+                        }
+                    }
+                    None => {
+                        ui.weak("Pending");
+                    }
+                }
+            });
+        }
+
+        if let Err(e) = result {
+            log::error!("{e}");
+        }
+    }
+}
@@ -0,0 +1,530 @@
+use crate::dap::dap_interface::{DapInterface, DataBreakpointTarget};
+use crate::dap::message::EvaluateResponseBody;
+use crate::dap::message_types::EvaluateContext;
+use crate::dap::requests::RequestHandle;
+use crate::data::memory::{Endianness, MemoryByte, MemoryEditFormat};
+use crate::data::types::DebugPointer;
+use crate::widget::DataBreakpointMenu;
+use egui::{Color32, ComboBox, Id, Key, Response, Sense, Slider, TextEdit, TextStyle, Ui, Widget};
+use epaint::FontId;
+use epaint::text::{LayoutJob, TextFormat};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub const BYTES_PER_ROW: usize = 16;
+
+/// The row index of the last row of the address space
+const LAST_ROW: usize = usize::MAX / BYTES_PER_ROW;
+
+const SCROLLBAR_WIDTH: f32 = 16.0;
+
+/// Where each part of a row starts, in characters
+const HEX_COLUMN: usize = 18;
+const ASCII_COLUMN: usize = HEX_COLUMN + BYTES_PER_ROW * 3 + 2;
+
+const INTEGER_WIDTHS: [usize; 4] = [1, 2, 4, 8];
+
+static NEXT_MEMORY_VIEW_ID: AtomicU64 = AtomicU64::new(0);
+
+pub struct MemoryView {
+    dap_interface: Arc<DapInterface>,
+    id: Id,
+    location_input: String,
+    location_error: Option<String>,
+    pending_evaluation: Option<RequestHandle<EvaluateResponseBody>>,
+
+    /// Row shown at the top of the view
+    top_row: usize,
+    /// The address the user asked to go to
+    location: Option<usize>,
+    /// The byte the user selected, highlighted in the view. Edits are written here.
+    selected_address: Option<usize>,
+    /// Scroll delta that did not amount to a whole row yet
+    scroll_remainder: f32,
+    bytes: Vec<MemoryByte>,
+
+    edit_format: MemoryEditFormat,
+    edit_input: String,
+    /// The writeMemory request in flight and how many bytes it's writing
+    pending_write: Option<(RequestHandle<usize>, usize)>,
+    /// The outcome of the last write, or the reason it could not be done
+    write_status: Option<Result<String, String>>,
+
+    /// How many bytes from the selected address the context menu watches
+    watch_width: usize,
+    watch_menu: DataBreakpointMenu,
+}
+
+impl MemoryView {
+    pub fn new(dap_interface: Arc<DapInterface>) -> Self {
+        let id = NEXT_MEMORY_VIEW_ID.fetch_add(1, Ordering::Relaxed);
+        Self {
+            dap_interface,
+            id: Id::new("memory-view").with(id),
+            location_input: String::new(),
+            location_error: None,
+            pending_evaluation: None,
+
+            top_row: 0,
+            location: None,
+            selected_address: None,
+            scroll_remainder: 0.0,
+            bytes: Vec::new(),
+
+            edit_format: MemoryEditFormat::Hex,
+            edit_input: String::new(),
+            pending_write: None,
+            write_status: None,
+
+            watch_width: 4,
+            watch_menu: DataBreakpointMenu::new(),
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    pub fn title(&self) -> String {
+        match self.location {
+            Some(address) => format!("Memory {}", DebugPointer::new(address)),
+            None => "Memory".into(),
+        }
+    }
+
+    /// Scrolls the view so the given address is near the top, and selects it.
+    pub fn go_to(&mut self, address: DebugPointer) {
+        let row = address.as_usize() / BYTES_PER_ROW;
+        self.top_row = row.saturating_sub(2);
+        self.location = Some(address.as_usize());
+        self.selected_address = Some(address.as_usize());
+    }
+
+    fn submit_location(&mut self) {
+        let location = self.location_input.trim();
+        self.location_error = None;
+
+        // Anything that looks like a number is an address, everything else is an expression for
+        // the debugger
+        if location.starts_with(|c: char| c.is_ascii_digit()) {
+            match location.parse::<DebugPointer>() {
+                Ok(address) => self.go_to(address),
+                Err(e) => self.location_error = Some(format!("Invalid address: {e}")),
+            }
+        } else if !location.is_empty() {
+            match self
+                .dap_interface
+                .session()
+                .request_evaluate(location, EvaluateContext::Watch)
+            {
+                Ok(handle) => self.pending_evaluation = Some(handle),
+                Err(e) => self.location_error = Some(e.to_string()),
+            }
+        }
+    }
+
+    fn poll_evaluation(&mut self) {
+        let Some(result) = self.pending_evaluation.as_ref().and_then(|h| h.poll()) else {
+            return;
+        };
+        self.pending_evaluation = None;
+
+        match result {
+            Ok(body) => {
+                let address = body
+                    .memory_reference
+                    .or_else(|| pointer_in_text(&body.result));
+                match address {
+                    Some(address) => self.go_to(address),
+                    None => {
+                        self.location_error =
+                            Some(format!("\"{}\" is not an address", body.result));
+                    }
+                }
+            }
+            Err(e) => self.location_error = Some(e.to_string()),
+        }
+    }
+
+    fn submit_edit(&mut self) {
+        let Some(address) = self.selected_address else {
+            return;
+        };
+
+        let data = match self.edit_format.encode(&self.edit_input) {
+            Ok(data) if data.is_empty() => return,
+            Ok(data) => data,
+            Err(e) => {
+                self.write_status = Some(Err(e));
+                return;
+            }
+        };
+
+        match self
+            .dap_interface
+            .session()
+            .write_memory(DebugPointer::new(address), &data)
+        {
+            Ok(handle) => {
+                self.pending_write = Some((handle, data.len()));
+                self.write_status = None;
+            }
+            Err(e) => self.write_status = Some(Err(e.to_string())),
+        }
+    }
+
+    fn poll_write(&mut self) {
+        let Some((handle, len)) = &self.pending_write else {
+            return;
+        };
+        let Some(result) = handle.poll() else {
+            return;
+        };
+        let len = *len;
+        self.pending_write = None;
+
+        self.write_status = Some(
+            result
+                .map(|written| {
+                    if written < len {
+                        format!("Only {written} of {len} bytes written")
+                    } else {
+                        format!("{written} bytes written")
+                    }
+                })
+                .map_err(|e| e.to_string()),
+        );
+    }
+
+    fn edit_ui(&mut self, ui: &mut Ui, can_write: bool) {
+        ui.add_enabled_ui(can_write && self.selected_address.is_some(), |ui| {
+            ui.horizontal(|ui| {
+                match self.selected_address {
+                    Some(address) => ui.label(format!("Write at {}:", DebugPointer::new(address))),
+                    None => ui.label("Select a byte to write at:"),
+                };
+
+                let is_integer = matches!(self.edit_format, MemoryEditFormat::Integer { .. });
+                ComboBox::from_id_salt(self.id.with("edit-format"))
+                    .selected_text(match self.edit_format {
+                        MemoryEditFormat::Hex => "Hex bytes",
+                        MemoryEditFormat::Integer { .. } => "Integer",
+                        MemoryEditFormat::String => "String",
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut self.edit_format,
+                            MemoryEditFormat::Hex,
+                            "Hex bytes",
+                        );
+                        if ui.selectable_label(is_integer, "Integer").clicked() && !is_integer {
+                            self.edit_format = MemoryEditFormat::Integer {
+                                width: 4,
+                                endianness: Endianness::Little,
+                            };
+                        }
+                        ui.selectable_value(
+                            &mut self.edit_format,
+                            MemoryEditFormat::String,
+                            "String",
+                        );
+                    });
+
+                if let MemoryEditFormat::Integer { width, endianness } = &mut self.edit_format {
+                    ComboBox::from_id_salt(self.id.with("edit-width"))
+                        .selected_text(format!("{} bits", *width * 8))
+                        .show_ui(ui, |ui| {
+                            for option in INTEGER_WIDTHS {
+                                ui.selectable_value(width, option, format!("{} bits", option * 8));
+                            }
+                        });
+                    ComboBox::from_id_salt(self.id.with("edit-endianness"))
+                        .selected_text(match endianness {
+                            Endianness::Little => "Little endian",
+                            Endianness::Big => "Big endian",
+                        })
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(endianness, Endianness::Little, "Little endian");
+                            ui.selectable_value(endianness, Endianness::Big, "Big endian");
+                        });
+                }
+
+                let input_res = ui.add(
+                    TextEdit::singleline(&mut self.edit_input)
+                        .hint_text("Value")
+                        .desired_width(160.0),
+                );
+                let submitted = input_res.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+                if ui.button("Write").clicked() || submitted {
+                    self.submit_edit();
+                }
+            });
+        });
+
+        if self.pending_write.is_some() {
+            ui.spinner();
+            ui.ctx().request_repaint();
+        }
+        match &self.write_status {
+            Some(Ok(status)) => {
+                ui.label(status);
+            }
+            Some(Err(error)) => {
+                ui.colored_label(ui.style().visuals.error_fg_color, error);
+            }
+            None => {}
+        }
+    }
+
+    /// The context menu of the view, to watch the memory at the selected address.
+    fn watch_menu_ui(&mut self, ui: &mut Ui) {
+        let Some(address) = self.selected_address else {
+            ui.weak("Select a byte to watch");
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            ui.label(format!("Watch at {}:", DebugPointer::new(address)));
+            for option in INTEGER_WIDTHS {
+                ui.selectable_value(&mut self.watch_width, option, format!("{option} bytes"));
+            }
+        });
+        let target = DataBreakpointTarget::Address {
+            address: DebugPointer::new(address),
+            bytes: self.watch_width as u64,
+        };
+        self.watch_menu.ui(ui, &self.dap_interface, &target);
+    }
+
+    /// Which byte of the row is at the given character of its text, if any.
+    fn byte_at_column(column: usize) -> Option<usize> {
+        if (HEX_COLUMN..ASCII_COLUMN - 2).contains(&column) {
+            // There's an extra space between the two halves of the row
+            let half_len = BYTES_PER_ROW / 2 * 3 + 1;
+            let column = column - HEX_COLUMN;
+            if column < half_len {
+                Some((column / 3).min(BYTES_PER_ROW / 2 - 1))
+            } else {
+                Some(BYTES_PER_ROW / 2 + (column - half_len) / 3)
+            }
+        } else if (ASCII_COLUMN..ASCII_COLUMN + BYTES_PER_ROW).contains(&column) {
+            Some(column - ASCII_COLUMN)
+        } else {
+            None
+        }
+    }
+
+    fn scroll_rows(&mut self, rows: isize) {
+        self.top_row = self.top_row.saturating_add_signed(rows).min(LAST_ROW);
+    }
+
+    fn row_layout_job(&self, ui: &Ui, address: usize, bytes: &[MemoryByte]) -> LayoutJob {
+        let font_id = FontId::monospace(ui.text_style_height(&TextStyle::Monospace));
+        let visuals = &ui.style().visuals;
+        let text_format = |color| TextFormat::simple(font_id.clone(), color);
+        let normal = text_format(visuals.text_color());
+        let weak = text_format(visuals.weak_text_color());
+        let unreadable = text_format(visuals.error_fg_color);
+        let mut selected = text_format(visuals.strong_text_color());
+        selected.background = visuals.selection.bg_fill;
+
+        let mut job = LayoutJob::default();
+        job.append(&format!("{address:016x}  "), 0.0, weak.clone());
+
+        for (i, byte) in bytes.iter().enumerate() {
+            let is_selected = self.selected_address == Some(address + i);
+            let (text, format) = match byte {
+                MemoryByte::Value(value) => (format!("{value:02x}"), &normal),
+                MemoryByte::Unknown => ("..".into(), &weak),
+                MemoryByte::Unreadable => ("??".into(), &unreadable),
+            };
+            let gap = if i == BYTES_PER_ROW / 2 - 1 {
+                "  "
+            } else {
+                " "
+            };
+            job.append(
+                &text,
+                0.0,
+                if is_selected { &selected } else { format }.clone(),
+            );
+            job.append(gap, 0.0, normal.clone());
+        }
+
+        job.append(" ", 0.0, normal.clone());
+        for (i, byte) in bytes.iter().enumerate() {
+            let is_selected = self.selected_address == Some(address + i);
+            let (c, format) = match byte {
+                MemoryByte::Value(value) if value.is_ascii_graphic() || *value == b' ' => {
+                    (*value as char, &normal)
+                }
+                MemoryByte::Value(_) => ('.', &weak),
+                MemoryByte::Unknown => (' ', &weak),
+                MemoryByte::Unreadable => ('?', &unreadable),
+            };
+            let format = if is_selected { &selected } else { format };
+            job.append(c.encode_utf8(&mut [0; 4]), 0.0, format.clone());
+        }
+
+        job
+    }
+}
+impl Widget for &mut MemoryView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let _span = tracy_client::span!("ui_update_memory_view");
+
+        self.poll_evaluation();
+        self.poll_write();
+
+        ui.horizontal(|ui| {
+            ui.label("Location:");
+            let input_res = ui.add(
+                TextEdit::singleline(&mut self.location_input)
+                    .hint_text("Address or expression")
+                    .desired_width(240.0),
+            );
+            let submitted = input_res.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+            if ui.button("Go").clicked() || submitted {
+                self.submit_location();
+            }
+
+            if self.pending_evaluation.is_some() {
+                ui.spinner();
+                ui.ctx().request_repaint();
+            }
+            if let Some(error) = &self.location_error {
+                ui.colored_label(ui.style().visuals.error_fg_color, error);
+            }
+        });
+
+        let debug_state = self.dap_interface.session().get_debug_state();
+        if debug_state.is_stopped() && !self.dap_interface.session().supports_read_memory() {
+            ui.colored_label(
+                ui.style().visuals.warn_fg_color,
+                "The debug adapter does not support reading memory",
+            );
+        }
+
+        if self.dap_interface.session().supports_write_memory() {
+            // A core dump can only be read
+            let can_edit = debug_state.is_stopped() && !debug_state.is_post_mortem();
+            ui.horizontal(|ui| self.edit_ui(ui, can_edit));
+        }
+
+        ui.separator();
+
+        let row_height = ui.text_style_height(&TextStyle::Monospace);
+        let available = ui.available_size();
+        let num_rows = ((available.y / row_height).floor() as usize).max(1);
+
+        ui.horizontal_top(|ui| {
+            let (rect, response) = ui.allocate_exact_size(
+                egui::vec2(available.x - SCROLLBAR_WIDTH - 8.0, available.y),
+                Sense::click(),
+            );
+
+            if response.hovered() {
+                let (scroll_delta, page_up, page_down, up, down) = ui.input(|i| {
+                    (
+                        i.smooth_scroll_delta.y,
+                        i.key_pressed(Key::PageUp),
+                        i.key_pressed(Key::PageDown),
+                        i.key_pressed(Key::ArrowUp),
+                        i.key_pressed(Key::ArrowDown),
+                    )
+                });
+
+                self.scroll_remainder += scroll_delta;
+                let rows = (self.scroll_remainder / row_height).trunc();
+                self.scroll_remainder -= rows * row_height;
+                self.scroll_rows(-rows as isize);
+
+                let page = num_rows as isize;
+                let keyboard_rows = match (page_up, page_down, up, down) {
+                    (true, ..) => -page,
+                    (_, true, ..) => page,
+                    (.., true, _) => -1,
+                    (.., true) => 1,
+                    _ => 0,
+                };
+                self.scroll_rows(keyboard_rows);
+            }
+
+            let first_address = self.top_row * BYTES_PER_ROW;
+            let num_rows = num_rows.min(LAST_ROW - self.top_row + 1);
+            self.bytes
+                .resize(num_rows * BYTES_PER_ROW, MemoryByte::Unknown);
+            let reading = self
+                .dap_interface
+                .session()
+                .read_memory(DebugPointer::new(first_address), &mut self.bytes)
+                .unwrap_or_else(|e| {
+                    log::error!("Failed to read memory: {e}");
+                    false
+                });
+
+            let painter = ui.painter_at(rect);
+            for (row, bytes) in self.bytes.chunks(BYTES_PER_ROW).enumerate() {
+                let address = first_address + row * BYTES_PER_ROW;
+                let job = self.row_layout_job(ui, address, bytes);
+                let pos = rect.min + egui::vec2(0.0, row as f32 * row_height);
+                let galley = painter.layout_job(job);
+
+                // Right clicking selects too, for the context menu
+                if (response.clicked() || response.secondary_clicked())
+                    && let Some(click_pos) = response.interact_pointer_pos()
+                    && (pos.y..pos.y + row_height).contains(&click_pos.y)
+                {
+                    let column = galley.cursor_from_pos(click_pos - pos).index;
+                    if let Some(i) = MemoryView::byte_at_column(column) {
+                        self.selected_address = Some(address + i);
+                    }
+                }
+
+                painter.galley(pos, galley, Color32::PLACEHOLDER);
+            }
+
+            if self
+                .dap_interface
+                .session()
+                .supports_data_breakpoint_bytes()
+            {
+                let menu = response.context_menu(|ui| self.watch_menu_ui(ui));
+                if menu.is_none() {
+                    self.watch_menu.close();
+                }
+            }
+
+            // Until the reads come back
+            if reading {
+                ui.ctx().request_repaint();
+            }
+
+            // The slider goes from the last row at the bottom to the first one at the top. It is
+            // not precise enough to reach every row of a 64-bit address space, but the mouse
+            // wheel is.
+            let mut slider_row = self.top_row as f64;
+            ui.spacing_mut().slider_width = available.y;
+            let slider_res = ui.add(
+                Slider::new(&mut slider_row, LAST_ROW as f64..=0.0)
+                    .vertical()
+                    .show_value(false),
+            );
+            if slider_res.changed() {
+                self.top_row = (slider_row as usize).min(LAST_ROW);
+            }
+        });
+
+        ui.response()
+    }
+}
+
+/// Finds the first hex number in a value printed by the debugger, like `(int *) 0x7ffc1c0`.
+fn pointer_in_text(text: &str) -> Option<DebugPointer> {
+    let start = text.find("0x")?;
+    let hex_len = text[start + 2..]
+        .find(|c: char| !c.is_ascii_hexdigit())
+        .unwrap_or(text.len() - start - 2);
+
+    text[start..start + 2 + hex_len].parse().ok()
+}
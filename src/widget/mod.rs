@@ -1,7 +1,32 @@
 pub mod file_picker;
 pub use file_picker::*;
 
+pub mod attach_dialog;
+pub mod breakpoints;
+pub mod call_stack;
+pub mod core_dump_dialog;
+pub mod data_breakpoint_menu;
+pub mod disassembly;
+pub mod function_breakpoints;
+pub mod memory_view;
+pub mod output_view;
 pub mod source_listing;
-mod var_view;
+pub mod terminal_view;
+pub mod threads;
+pub mod var_view;
+pub mod watch;
 
+pub use attach_dialog::*;
+pub use breakpoints::*;
+pub use call_stack::*;
+pub use core_dump_dialog::*;
+pub use data_breakpoint_menu::*;
+pub use disassembly::*;
+pub use function_breakpoints::*;
+pub use memory_view::*;
+pub use output_view::*;
 pub use source_listing::*;
+pub use terminal_view::*;
+pub use threads::*;
+pub use var_view::*;
+pub use watch::*;
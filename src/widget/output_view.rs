@@ -0,0 +1,279 @@
+use crate::dap::dap_interface::DapInterface;
+use crate::dap::message::CompletionsResponseBody;
+use crate::dap::message_types::{CompletionItem, OutputEventCategory};
+use crate::dap::requests::RequestHandle;
+use crate::data::output::{OutputLine, OutputLineKind};
+use crate::widget::VarView;
+use egui::{
+    Button, CollapsingHeader, Id, Key, Modifiers, Response, RichText, ScrollArea, TextEdit,
+    TextStyle, Ui, Widget,
+};
+use std::path::Path;
+use std::sync::Arc;
+
+enum Completions {
+    Loading(RequestHandle<CompletionsResponseBody>),
+    Loaded(Vec<CompletionItem>),
+    Failed(String),
+}
+
+/// Everything the debugger and the debuggee printed, with a line to type expressions or
+/// debugger commands in.
+pub struct OutputView {
+    dap_interface: Arc<DapInterface>,
+    /// The lines shown on the last frame, kept around to not allocate every frame
+    lines: Vec<OutputLine>,
+    /// Shows the children of structured output
+    children: VarView,
+    input: String,
+    /// The completions of the input, as it was when they were asked for
+    completions: Option<Completions>,
+}
+
+impl OutputView {
+    pub fn new(dap_interface: Arc<DapInterface>) -> Self {
+        Self {
+            children: VarView::new(Arc::clone(&dap_interface)),
+            dap_interface,
+            lines: Vec::new(),
+            input: String::new(),
+            completions: None,
+        }
+    }
+
+    fn line_ui(&mut self, ui: &mut Ui, line: &OutputLine) {
+        let visuals = &ui.style().visuals;
+        let color = match (line.kind, line.category) {
+            (OutputLineKind::Input, _) => Some(visuals.strong_text_color()),
+            (OutputLineKind::Result, _) => None,
+            (_, OutputEventCategory::Stderr) => Some(visuals.error_fg_color),
+            (_, OutputEventCategory::Important) => Some(visuals.warn_fg_color),
+            (_, OutputEventCategory::Console | OutputEventCategory::Telemetry) => {
+                Some(visuals.weak_text_color())
+            }
+            _ => None,
+        };
+
+        let mut text = match line.kind {
+            OutputLineKind::Input => RichText::new(format!("> {}", line.text)),
+            _ => RichText::new(&line.text),
+        }
+        .monospace();
+        if let Some(color) = color {
+            text = text.color(color);
+        }
+
+        ui.horizontal(|ui| {
+            ui.add_space(line.depth as f32 * ui.spacing().indent);
+
+            match line.kind {
+                OutputLineKind::GroupStart { collapsed } => {
+                    let icon = if collapsed { "▶" } else { "▼" };
+                    if ui.add(Button::new(icon).frame(false)).clicked() {
+                        self.dap_interface.toggle_output_group(line.id);
+                    }
+                    ui.label(text);
+                }
+                _ if line.variables_reference > 0 => {
+                    let id = Id::new("output-structured").with(line.id);
+                    ui.vertical(|ui| {
+                        CollapsingHeader::new(text).id_salt(id).show(ui, |ui| {
+                            self.children.variables_ui(ui, line.variables_reference, id);
+                        });
+                    });
+                }
+                _ => {
+                    ui.label(text);
+                }
+            }
+
+            if let Some((file, lineno)) = &line.location {
+                let name = Path::new(file)
+                    .file_name()
+                    .map(|name| name.to_string_lossy())
+                    .unwrap_or(file.to_string_lossy());
+                ui.weak(format!("{name}:{lineno}"));
+            }
+        });
+    }
+
+    /// Asks for the completions of the input, as if the cursor was at its end.
+    fn request_completions(&mut self) {
+        let column = self.input.encode_utf16().count() + 1;
+        self.completions = Some(
+            match self
+                .dap_interface
+                .session()
+                .request_completions(&self.input, column)
+            {
+                Ok(handle) => Completions::Loading(handle),
+                Err(e) => Completions::Failed(e.to_string()),
+            },
+        );
+    }
+
+    fn poll_completions(&mut self) {
+        if let Some(Completions::Loading(handle)) = &self.completions
+            && let Some(result) = handle.poll()
+        {
+            self.completions = match result {
+                // Only one way to go, no need to choose
+                Ok(body) if body.targets.len() == 1 => {
+                    self.input = apply_completion(&self.input, &body.targets[0]);
+                    None
+                }
+                Ok(body) => Some(Completions::Loaded(body.targets)),
+                Err(e) => Some(Completions::Failed(e.to_string())),
+            };
+        }
+    }
+
+    fn completions_ui(&mut self, ui: &mut Ui) {
+        let mut chosen = None;
+        match &self.completions {
+            None => return,
+            Some(Completions::Loading(_)) => {
+                ui.spinner();
+                ui.ctx().request_repaint();
+            }
+            Some(Completions::Failed(message)) => {
+                ui.colored_label(ui.style().visuals.error_fg_color, message);
+            }
+            Some(Completions::Loaded(targets)) if targets.is_empty() => {
+                ui.weak("No completions");
+            }
+            Some(Completions::Loaded(targets)) => {
+                ui.horizontal_wrapped(|ui| {
+                    for target in targets {
+                        let mut response = ui.small_button(&target.label);
+                        if let Some(detail) = &target.detail {
+                            response = response.on_hover_text(detail);
+                        }
+                        if response.clicked() {
+                            chosen = Some(target.clone());
+                        }
+                    }
+                });
+            }
+        }
+
+        if let Some(target) = chosen {
+            self.input = apply_completion(&self.input, &target);
+            self.completions = None;
+        }
+    }
+
+    /// The line to type expressions in. Enter evaluates them, Tab completes them.
+    fn input_ui(&mut self, ui: &mut Ui) {
+        let can_complete = self.dap_interface.session().supports_completions();
+        let input_id = Id::new("output-console-input");
+        let tab_pressed = ui.memory(|m| m.has_focus(input_id))
+            && ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::Tab));
+
+        self.completions_ui(ui);
+
+        let hint = if can_complete {
+            "Expression or debugger command, Tab to complete"
+        } else {
+            "Expression or debugger command"
+        };
+        let input_res = ui.add(
+            TextEdit::singleline(&mut self.input)
+                .id(input_id)
+                .font(TextStyle::Monospace)
+                .desired_width(f32::INFINITY)
+                // Tab completes instead of moving to the next widget
+                .lock_focus(true)
+                .hint_text(hint),
+        );
+
+        if input_res.changed() {
+            self.completions = None;
+        }
+        if tab_pressed && can_complete {
+            self.request_completions();
+        }
+        if input_res.has_focus() && ui.input(|i| i.key_pressed(Key::Escape)) {
+            self.completions = None;
+        }
+
+        if input_res.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+            let expression = self.input.trim().to_owned();
+            if !expression.is_empty() {
+                if let Err(e) = self
+                    .dap_interface
+                    .session()
+                    .evaluate_in_console(&expression)
+                {
+                    log::error!("Failed to evaluate: {e}");
+                }
+                self.input.clear();
+                self.completions = None;
+            }
+            input_res.request_focus();
+        }
+    }
+}
+
+impl Widget for &mut OutputView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let _span = tracy_client::span!("ui_update_output");
+
+        self.poll_completions();
+
+        if ui.button("Clear").clicked() {
+            self.dap_interface.clear_output();
+        }
+        ui.separator();
+
+        egui::Panel::bottom("output-console")
+            .show_separator_line(false)
+            .show_inside(ui, |ui| {
+                ui.add_space(ui.spacing().item_spacing.y);
+                self.input_ui(ui);
+            });
+
+        let row_height = ui.text_style_height(&TextStyle::Monospace);
+        let num_lines = self.dap_interface.output_len();
+
+        ScrollArea::both()
+            .auto_shrink(false)
+            .stick_to_bottom(true)
+            .show_rows(ui, row_height, num_lines, |ui, range| {
+                self.dap_interface.get_output(range, &mut self.lines);
+                let lines = std::mem::take(&mut self.lines);
+                for line in &lines {
+                    self.line_ui(ui, line);
+                }
+                self.lines = lines;
+            });
+        self.children.end_frame();
+
+        ui.response()
+    }
+}
+
+/// Puts the completion in the text, replacing what the debugger said it replaces.
+fn apply_completion(text: &str, completion: &CompletionItem) -> String {
+    let insert = completion.text.as_deref().unwrap_or(&completion.label);
+    // The debugger counts columns from 1, in UTF-16 code units
+    let utf16_len = text.encode_utf16().count();
+    let start = completion
+        .start
+        .map_or(utf16_len, |start| start.saturating_sub(1).min(utf16_len));
+    let end = (start + completion.length.unwrap_or(0)).min(utf16_len);
+
+    let byte_index = |utf16_index: usize| {
+        let mut units = 0;
+        text.char_indices()
+            .find(|(_, c)| {
+                let found = units >= utf16_index;
+                units += c.len_utf16();
+                found
+            })
+            .map_or(text.len(), |(i, _)| i)
+    };
+    let (start, end) = (byte_index(start), byte_index(end));
+
+    format!("{}{insert}{}", &text[..start], &text[end..])
+}
@@ -1,6 +1,11 @@
-use crate::dap::dap_interface::{DapInterface, DebugState};
-use crate::data::breakpoints::Breakpoint;
-use egui::{Response, ScrollArea, Ui, UiBuilder, Widget};
+use crate::dap::dap_interface::DapInterface;
+use crate::dap::message::ExceptionInfoResponseBody;
+use crate::dap::message_types::ExceptionDetails;
+use crate::data::breakpoints::{Breakpoint, CodeBreakpoint};
+use egui::{
+    CollapsingHeader, Popup, PopupCloseBehavior, Response, RichText, ScrollArea, TextEdit, Ui,
+    Widget,
+};
 use epaint::FontId;
 use epaint::text::LayoutJob;
 use std::ffi::OsStr;
@@ -15,6 +20,26 @@ pub struct SourceCode {
 
 const DEFAULT_LINE_HEIGHT_PX: f32 = 12.0;
 
+/// The attributes of a breakpoint, as they are typed in the gutter context menu.
+struct BreakpointEditor {
+    lineno: usize,
+    condition: String,
+    hit_condition: String,
+    log_message: String,
+}
+
+impl BreakpointEditor {
+    fn new(lineno: usize, breakpoint: Option<&CodeBreakpoint>) -> Self {
+        let text = |value: Option<&String>| value.cloned().unwrap_or_default();
+        Self {
+            lineno,
+            condition: text(breakpoint.and_then(|bp| bp.condition.as_ref())),
+            hit_condition: text(breakpoint.and_then(|bp| bp.hit_condition.as_ref())),
+            log_message: text(breakpoint.and_then(|bp| bp.log_message.as_ref())),
+        }
+    }
+}
+
 pub struct SourceListing {
     dap_interface: Arc<DapInterface>,
     source_code: SourceCode,
@@ -24,6 +49,8 @@ pub struct SourceListing {
 
     scroll_event_target: Option<usize>,
     last_debug_highlighted_line: usize,
+    /// Set while the context menu of a breakpoint is open
+    breakpoint_editor: Option<BreakpointEditor>,
 }
 
 impl SourceListing {
@@ -49,9 +76,14 @@ impl SourceListing {
 
             scroll_event_target: None,
             last_debug_highlighted_line: 0,
+            breakpoint_editor: None,
         })
     }
 
+    pub fn path(&self) -> &Path {
+        &self.source_code.path
+    }
+
     pub fn filename(&self) -> &str {
         self.source_code
             .path
@@ -59,31 +91,145 @@ impl SourceListing {
             .and_then(OsStr::to_str)
             .unwrap_or("<unknown>")
     }
+
+    /// Lets the user set the condition, hit condition and log message of the breakpoint on
+    /// `lineno`, adding it if there is none. The fields the debugger does not support are
+    /// disabled.
+    fn breakpoint_editor_ui(
+        editor: &mut Option<BreakpointEditor>,
+        dap_interface: &DapInterface,
+        path: &Path,
+        ui: &mut Ui,
+        lineno: usize,
+        breakpoint: Option<&CodeBreakpoint>,
+    ) {
+        let editor = match editor {
+            Some(editor) if editor.lineno == lineno => editor,
+            editor => editor.insert(BreakpointEditor::new(lineno, breakpoint)),
+        };
+
+        ui.strong(format!("Breakpoint on line {lineno}"));
+        egui::Grid::new("breakpoint-editor")
+            .num_columns(2)
+            .show(ui, |ui| {
+                let fields = [
+                    (
+                        "Condition",
+                        &mut editor.condition,
+                        dap_interface.session().supports_conditional_breakpoints(),
+                    ),
+                    (
+                        "Hit count",
+                        &mut editor.hit_condition,
+                        dap_interface
+                            .session()
+                            .supports_hit_conditional_breakpoints(),
+                    ),
+                    (
+                        "Log message",
+                        &mut editor.log_message,
+                        dap_interface.session().supports_log_points(),
+                    ),
+                ];
+                for (name, text, supported) in fields {
+                    ui.label(name);
+                    ui.add_enabled(supported, TextEdit::singleline(text))
+                        .on_disabled_hover_text("The debugger does not support this");
+                    ui.end_row();
+                }
+            });
+
+        let non_empty = |text: &String| (!text.trim().is_empty()).then(|| text.clone());
+        let mut result = Ok(());
+        ui.horizontal(|ui| {
+            if ui.button("Apply").clicked() {
+                let breakpoint = Breakpoint::Source(CodeBreakpoint {
+                    file: Arc::new(path.to_path_buf()),
+                    lineno,
+                    condition: non_empty(&editor.condition),
+                    hit_condition: non_empty(&editor.hit_condition),
+                    log_message: non_empty(&editor.log_message),
+                    ..Default::default()
+                });
+                result = dap_interface.put_breakpoint(breakpoint);
+                ui.close();
+            }
+            if let Some(breakpoint) = breakpoint
+                && ui.button("Remove").clicked()
+            {
+                result = dap_interface.remove_breakpoint(&Breakpoint::Source(breakpoint.clone()));
+                ui.close();
+            }
+        });
+
+        if let Err(e) = result {
+            log::error!("{e}");
+        }
+    }
+
+    /// Tells what the exception or panic the program stopped at is about.
+    fn exception_banner_ui(ui: &mut Ui, exception: &ExceptionInfoResponseBody) {
+        let color = ui.style().visuals.error_fg_color;
+        egui::Frame::new()
+            .fill(color.gamma_multiply(0.15))
+            .stroke(egui::Stroke::new(1.0, color))
+            .inner_margin(6.0)
+            .show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                ui.label(RichText::new(&exception.exception_id).strong().color(color));
+                if let Some(description) = &exception.description {
+                    ui.label(description);
+                }
+                if let Some(details) = &exception.details {
+                    CollapsingHeader::new("Details")
+                        .id_salt("exception-details")
+                        .show(ui, |ui| SourceListing::exception_details_ui(ui, details));
+                }
+            });
+    }
+
+    fn exception_details_ui(ui: &mut Ui, details: &ExceptionDetails) {
+        if let Some(type_name) = details
+            .full_type_name
+            .as_ref()
+            .or(details.type_name.as_ref())
+        {
+            ui.monospace(type_name);
+        }
+        if let Some(message) = &details.message {
+            ui.label(message);
+        }
+        if let Some(stack_trace) = &details.stack_trace {
+            ui.monospace(stack_trace);
+        }
+        for (i, inner) in details.inner_exception.iter().flatten().enumerate() {
+            CollapsingHeader::new("Caused by")
+                .id_salt(("exception-inner", i))
+                .show(ui, |ui| SourceListing::exception_details_ui(ui, inner));
+        }
+    }
 }
 impl Widget for &mut SourceListing {
     fn ui(self, ui: &mut Ui) -> Response {
         let _span = tracy_client::span!("ui_update_source_listing");
 
-        let debug_state = self.dap_interface.get_debug_state();
-        let mut stopped_at_line = None;
-
-        match debug_state {
-            DebugState::Stopped {
-                file: Some(file),
-                lineno,
-                ..
-            } if file == self.source_code.path => {
-                stopped_at_line = lineno;
-            }
-            DebugState::Paused => {}
-            _ => {}
-        }
+        let stopped_at_line = match self.dap_interface.session().get_current_location() {
+            Some((file, lineno)) if file == self.source_code.path => Some(lineno),
+            _ => None,
+        };
 
         self.dap_interface
+            .session()
             .get_file_breakpoints(&self.source_code.path, &mut self.list_breakpoints);
 
         ui.set_width(ui.available_width());
 
+        if stopped_at_line.is_some()
+            && let Some(exception) = self.dap_interface.session().get_exception()
+        {
+            SourceListing::exception_banner_ui(ui, &exception);
+        }
+
         let fresh_scroll_event = if let Some(lineno) = stopped_at_line
             && lineno != self.last_debug_highlighted_line
         {
@@ -122,6 +268,7 @@ impl Widget for &mut SourceListing {
                 None => None,
             };
 
+            let mut editor_open = false;
             for (i, line) in lines_in_range.iter().enumerate() {
                 let line_index = i + range.start;
                 let lineno = line_index + 1;
@@ -129,10 +276,12 @@ impl Widget for &mut SourceListing {
                 let line_breakpoint = self
                     .list_breakpoints
                     .iter()
-                    .map(|b| match b {
-                        Breakpoint::Source(b) => b,
+                    .filter_map(|b| match b {
+                        Breakpoint::Source(b) => Some(b),
+                        _ => None,
                     })
-                    .find(|b| b.lineno == lineno);
+                    .find(|b| b.lineno == lineno)
+                    .cloned();
 
                 let has_breakpoint = line_breakpoint.is_some();
 
@@ -157,10 +306,45 @@ impl Widget for &mut SourceListing {
                     .show(ui, |ui| {
                         ui.horizontal(|ui| {
                             ui.set_width(ui.available_width());
-                            let set_bp_res = ui.add_sized(
+                            let marker = match &line_breakpoint {
+                                Some(bp) if bp.is_logpoint() => "L",
+                                Some(bp) if bp.is_conditional() => "?",
+                                _ => "O",
+                            };
+                            let mut set_bp_res = ui.add_sized(
                                 [self.line_height_px, self.line_height_px],
-                                egui::Button::selectable(has_breakpoint, "O"),
+                                egui::Button::selectable(has_breakpoint, marker),
                             );
+                            if let Some(bp) = &line_breakpoint {
+                                if let Some(message) = &bp.log_message {
+                                    set_bp_res =
+                                        set_bp_res.on_hover_text(format!("Log: {message}"));
+                                } else if bp.is_conditional() {
+                                    let mut hover = Vec::new();
+                                    hover.extend(bp.condition.as_ref().map(|c| format!("If: {c}")));
+                                    hover.extend(
+                                        bp.hit_condition
+                                            .as_ref()
+                                            .map(|h| format!("Hit count: {h}")),
+                                    );
+                                    set_bp_res = set_bp_res.on_hover_text(hover.join("\n"));
+                                }
+                            }
+
+                            let editor = Popup::context_menu(&set_bp_res)
+                                .close_behavior(PopupCloseBehavior::CloseOnClickOutside)
+                                .show(|ui| {
+                                    SourceListing::breakpoint_editor_ui(
+                                        &mut self.breakpoint_editor,
+                                        &self.dap_interface,
+                                        &self.source_code.path,
+                                        ui,
+                                        lineno,
+                                        line_breakpoint.as_ref(),
+                                    )
+                                });
+                            editor_open |= editor.is_some();
+
                             if set_bp_res.clicked() {
                                 let dap_result = if let Some(bp) = line_breakpoint {
                                     self.dap_interface
@@ -196,6 +380,10 @@ impl Widget for &mut SourceListing {
                         });
                     });
             }
+
+            if !editor_open {
+                self.breakpoint_editor = None;
+            }
         });
 
         ui.response()
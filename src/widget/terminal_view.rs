@@ -0,0 +1,345 @@
+use crate::dap::dap_interface::DapInterface;
+use crate::data::terminal::{CellStyle, Terminal, TerminalColor, TerminalLine};
+use egui::{
+    Color32, Event, EventFilter, Id, Key, Rect, Response, ScrollArea, Sense, Stroke, TextEdit,
+    TextStyle, Ui, Visuals, Widget, vec2,
+};
+use epaint::text::{LayoutJob, TextFormat};
+use epaint::{FontId, StrokeKind};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often the terminal is drawn again while its program runs, to show what it prints.
+const REFRESH_INTERVAL: Duration = Duration::from_millis(50);
+
+const SCROLLBAR_WIDTH: f32 = 16.0;
+
+/// The first 16 colors of the palette, as xterm shows them.
+const ANSI_COLORS: [Color32; 16] = [
+    Color32::from_rgb(0, 0, 0),
+    Color32::from_rgb(205, 0, 0),
+    Color32::from_rgb(0, 205, 0),
+    Color32::from_rgb(205, 205, 0),
+    Color32::from_rgb(0, 0, 238),
+    Color32::from_rgb(205, 0, 205),
+    Color32::from_rgb(0, 205, 205),
+    Color32::from_rgb(229, 229, 229),
+    Color32::from_rgb(127, 127, 127),
+    Color32::from_rgb(255, 0, 0),
+    Color32::from_rgb(0, 255, 0),
+    Color32::from_rgb(255, 255, 0),
+    Color32::from_rgb(92, 92, 255),
+    Color32::from_rgb(255, 0, 255),
+    Color32::from_rgb(0, 255, 255),
+    Color32::from_rgb(255, 255, 255),
+];
+
+/// A terminal the debuggee runs in. What it prints is shown with its colors, and what's typed
+/// while the terminal has the focus is sent to it.
+pub struct TerminalView {
+    dap_interface: Arc<DapInterface>,
+    terminal_id: u64,
+    id: Id,
+    /// The lines shown on the last frame, kept around to not allocate every frame
+    lines: Vec<TerminalLine>,
+    search: String,
+    /// The line the search last found, highlighted
+    found_line: Option<usize>,
+    /// Whether the last search found nothing
+    not_found: bool,
+    /// Bring the found line into view on the next frame
+    scroll_to_found: bool,
+}
+
+impl TerminalView {
+    pub fn new(dap_interface: Arc<DapInterface>, terminal_id: u64) -> Self {
+        Self {
+            dap_interface,
+            terminal_id,
+            id: Id::new("terminal").with(terminal_id),
+            lines: Vec::new(),
+            search: String::new(),
+            found_line: None,
+            not_found: false,
+            scroll_to_found: false,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    pub fn title(&self) -> String {
+        self.dap_interface
+            .get_terminal(self.terminal_id)
+            .map(|terminal| terminal.title())
+            .unwrap_or_else(|| "Terminal".into())
+    }
+
+    /// Closes the terminal, hanging up the program in it if it's still running.
+    pub fn close(&self) {
+        self.dap_interface.close_terminal(self.terminal_id);
+    }
+
+    fn search_ui(&mut self, ui: &mut Ui, terminal: &Terminal) {
+        ui.horizontal(|ui| {
+            let search_res = ui.add(TextEdit::singleline(&mut self.search).hint_text("Find"));
+            if search_res.changed() {
+                self.found_line = None;
+                self.not_found = false;
+            }
+            let enter_pressed = search_res.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+            let previous = ui.button("▲").on_hover_text("Find previous").clicked();
+            let next = ui.button("▼").on_hover_text("Find next").clicked() || enter_pressed;
+
+            if previous || next {
+                let num_lines = terminal.num_lines();
+                let start = match self.found_line {
+                    Some(line) if next => line + 1,
+                    Some(line) => line + num_lines - 1,
+                    None if next => 0,
+                    None => num_lines - 1,
+                };
+                self.found_line = terminal.find(&self.search, start, next);
+                self.not_found = self.found_line.is_none() && !self.search.is_empty();
+                self.scroll_to_found = self.found_line.is_some();
+            }
+            if enter_pressed {
+                search_res.request_focus();
+            }
+
+            if self.not_found {
+                ui.colored_label(ui.style().visuals.warn_fg_color, "Not found");
+            }
+            if terminal.is_closed() {
+                ui.weak("The program exited");
+            }
+        });
+    }
+
+    fn screen_ui(&mut self, ui: &mut Ui, terminal: &Terminal) {
+        let font_id = FontId::monospace(ui.text_style_height(&TextStyle::Monospace));
+        let (row_height, char_width) =
+            ui.fonts_mut(|f| (f.row_height(&font_id), f.glyph_width(&font_id, 'M')));
+
+        // The program is told how much fits, to lay out what it prints
+        let available = ui.available_size();
+        let rows = (available.y / row_height)
+            .floor()
+            .clamp(1.0, u16::MAX.into()) as u16;
+        let columns = ((available.x - SCROLLBAR_WIDTH) / char_width)
+            .floor()
+            .clamp(1.0, u16::MAX.into()) as u16;
+        if !terminal.is_closed()
+            && let Err(e) = terminal.resize((rows, columns))
+        {
+            log::error!("Failed to resize terminal: {e}");
+        }
+
+        let has_focus = ui.memory(|m| m.has_focus(self.id));
+        let cursor = (!terminal.is_closed()).then(|| terminal.cursor());
+        let visuals = ui.style().visuals.clone();
+
+        let mut scroll_area = ScrollArea::vertical()
+            .auto_shrink(false)
+            .stick_to_bottom(true);
+        if let Some(found_line) = self.found_line.filter(|_| self.scroll_to_found) {
+            let offset = found_line as f32 * row_height - available.y / 2.0;
+            scroll_area = scroll_area.vertical_scroll_offset(offset.max(0.0));
+            self.scroll_to_found = false;
+        }
+
+        ui.spacing_mut().item_spacing.y = 0.0;
+        let output = scroll_area.show_rows(ui, row_height, terminal.num_lines(), |ui, range| {
+            terminal.get_lines(range.clone(), &mut self.lines);
+            for (index, line) in range.zip(&self.lines) {
+                let (rect, _) =
+                    ui.allocate_exact_size(vec2(ui.available_width(), row_height), Sense::hover());
+                let painter = ui.painter_at(rect);
+                if self.found_line == Some(index) {
+                    painter.rect_filled(rect, 0.0, visuals.selection.bg_fill);
+                }
+                let galley = painter.layout_job(line_layout_job(line, &font_id, &visuals));
+                painter.galley(rect.min, galley, visuals.text_color());
+
+                if let Some((_, column)) = cursor.filter(|(row, _)| *row == index) {
+                    let cursor_rect = Rect::from_min_size(
+                        rect.min + vec2(column as f32 * char_width, 0.0),
+                        vec2(char_width, row_height),
+                    );
+                    let color = visuals.text_color();
+                    if has_focus {
+                        painter.rect_filled(cursor_rect, 0.0, color.gamma_multiply(0.5));
+                    } else {
+                        painter.rect_stroke(
+                            cursor_rect,
+                            0.0,
+                            Stroke::new(1.0, color),
+                            StrokeKind::Inside,
+                        );
+                    }
+                }
+            }
+        });
+
+        let response = ui.interact(output.inner_rect, self.id, Sense::click());
+        if response.clicked() {
+            response.request_focus();
+        }
+        if response.has_focus() {
+            // Every key goes to the program, none moves the focus away
+            ui.memory_mut(|m| {
+                m.set_focus_lock_filter(
+                    self.id,
+                    EventFilter {
+                        tab: true,
+                        horizontal_arrows: true,
+                        vertical_arrows: true,
+                        escape: true,
+                    },
+                )
+            });
+            self.send_input(ui, terminal);
+        }
+    }
+
+    /// Sends what was typed to the program.
+    fn send_input(&self, ui: &Ui, terminal: &Terminal) {
+        let mut input = Vec::new();
+        ui.input(|i| {
+            for event in &i.events {
+                push_event_input(event, &mut input);
+            }
+        });
+
+        if !input.is_empty()
+            && !terminal.is_closed()
+            && let Err(e) = terminal.write_input(&input)
+        {
+            log::error!("Failed to write to terminal: {e}");
+        }
+    }
+}
+
+impl Widget for &mut TerminalView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let _span = tracy_client::span!("ui_update_terminal");
+
+        let Some(terminal) = self.dap_interface.get_terminal(self.terminal_id) else {
+            ui.weak("The terminal was closed");
+            return ui.response();
+        };
+        if !terminal.is_closed() {
+            ui.ctx().request_repaint_after(REFRESH_INTERVAL);
+        }
+
+        self.search_ui(ui, &terminal);
+        ui.separator();
+        self.screen_ui(ui, &terminal);
+
+        ui.response()
+    }
+}
+
+fn line_layout_job(line: &TerminalLine, font_id: &FontId, visuals: &Visuals) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    for (text, style) in line.spans() {
+        let (foreground, background) = cell_colors(&style, visuals);
+        let mut format = TextFormat::simple(font_id.clone(), foreground);
+        format.background = background;
+        format.italics = style.italic;
+        if style.underline {
+            format.underline = Stroke::new(1.0, foreground);
+        }
+        job.append(&text, 0.0, format);
+    }
+
+    job
+}
+
+/// The colors a cell is drawn with, its foreground and its background.
+fn cell_colors(style: &CellStyle, visuals: &Visuals) -> (Color32, Color32) {
+    // Bold text in one of the basic colors is shown in its bright variant
+    let foreground = match style.foreground {
+        TerminalColor::Indexed(index @ 0..8) if style.bold => TerminalColor::Indexed(index + 8),
+        color => color,
+    };
+    let mut foreground = color32(foreground).unwrap_or(visuals.text_color());
+    let mut background = color32(style.background).unwrap_or(Color32::TRANSPARENT);
+
+    if style.inverse {
+        (foreground, background) = (background, foreground);
+        if foreground == Color32::TRANSPARENT {
+            foreground = visuals.extreme_bg_color;
+        }
+    }
+    if style.dim {
+        foreground = foreground.gamma_multiply(0.6);
+    }
+
+    (foreground, background)
+}
+
+/// The color of the palette `color` stands for, None for the default colors.
+fn color32(color: TerminalColor) -> Option<Color32> {
+    match color {
+        TerminalColor::Default => None,
+        TerminalColor::Indexed(index @ 0..16) => Some(ANSI_COLORS[index as usize]),
+        // A 6x6x6 cube of colors
+        TerminalColor::Indexed(index @ 16..232) => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let index = index - 16;
+            Some(Color32::from_rgb(
+                level(index / 36),
+                level(index / 6 % 6),
+                level(index % 6),
+            ))
+        }
+        // Shades of gray, from darkest to lightest
+        TerminalColor::Indexed(index) => Some(Color32::from_gray(8 + (index - 232) * 10)),
+        TerminalColor::Rgb(red, green, blue) => Some(Color32::from_rgb(red, green, blue)),
+    }
+}
+
+/// Appends to `input` what a terminal sends the program for `event`.
+fn push_event_input(event: &Event, input: &mut Vec<u8>) {
+    let bytes: &[u8] = match event {
+        Event::Text(text) | Event::Paste(text) => text.as_bytes(),
+        // Ctrl+C and Ctrl+X come as copy and cut, but they are meant for the program
+        Event::Copy => b"\x03",
+        Event::Cut => b"\x18",
+        Event::Key {
+            key,
+            pressed: true,
+            modifiers,
+            ..
+        } => match key {
+            Key::Enter => b"\r",
+            Key::Backspace => b"\x7f",
+            Key::Tab => b"\t",
+            Key::Escape => b"\x1b",
+            Key::ArrowUp => b"\x1b[A",
+            Key::ArrowDown => b"\x1b[B",
+            Key::ArrowRight => b"\x1b[C",
+            Key::ArrowLeft => b"\x1b[D",
+            Key::Home => b"\x1b[H",
+            Key::End => b"\x1b[F",
+            Key::Insert => b"\x1b[2~",
+            Key::Delete => b"\x1b[3~",
+            Key::PageUp => b"\x1b[5~",
+            Key::PageDown => b"\x1b[6~",
+            // Ctrl and a letter is the control character of that letter, like ^D
+            _ if modifiers.ctrl && !modifiers.alt => match key.name().as_bytes() {
+                [letter @ b'A'..=b'Z'] => {
+                    input.push(letter & 0x1f);
+                    return;
+                }
+                _ => return,
+            },
+            _ => return,
+        },
+        _ => return,
+    };
+
+    input.extend_from_slice(bytes);
+}
@@ -0,0 +1,91 @@
+use crate::dap::dap_interface::{ContinueMode, DapInterface, StepKind};
+use crate::data::threads::{ThreadInfo, ThreadState};
+use egui::{Response, RichText, ScrollArea, Ui, Widget};
+use std::sync::Arc;
+
+pub struct ThreadsView {
+    dap_interface: Arc<DapInterface>,
+}
+
+impl ThreadsView {
+    pub fn new(dap_interface: Arc<DapInterface>) -> Self {
+        Self { dap_interface }
+    }
+
+    fn thread_ui(&self, ui: &mut Ui, thread: &ThreadInfo, selected: bool) {
+        let stopped = matches!(thread.state, ThreadState::Stopped { .. });
+
+        ui.horizontal(|ui| {
+            let text = RichText::new(format!("{} #{}", thread.name, thread.id)).monospace();
+            let response = ui.add_enabled(stopped, egui::Button::selectable(selected, text));
+            if response.clicked()
+                && !selected
+                && let Err(e) = self.dap_interface.session().select_thread(thread.id)
+            {
+                log::error!("Failed to select thread: {e}");
+            }
+
+            match &thread.state {
+                ThreadState::Running => {
+                    ui.weak("Running");
+                    if ui.small_button("Pause").clicked()
+                        && let Err(e) = self.dap_interface.session().request_pause(Some(thread.id))
+                    {
+                        log::error!("Failed to pause thread: {e}");
+                    }
+                }
+                ThreadState::Stopped { description } => {
+                    match description {
+                        Some(description) => ui.weak(format!("Stopped: {description}")),
+                        None => ui.weak("Stopped"),
+                    };
+
+                    let single_thread = self
+                        .dap_interface
+                        .session()
+                        .supports_single_thread_execution();
+                    if ui
+                        .add_enabled(single_thread, egui::Button::new("Continue").small())
+                        .on_disabled_hover_text("The debugger can only continue every thread")
+                        .clicked()
+                        && let Err(e) = self
+                            .dap_interface
+                            .session()
+                            .request_continue(ContinueMode::SingleThread(thread.id))
+                    {
+                        log::error!("Failed to continue thread: {e}");
+                    }
+                    if ui.small_button("Step").clicked()
+                        && let Err(e) = self
+                            .dap_interface
+                            .session()
+                            .request_step(StepKind::Over, Some(thread.id))
+                    {
+                        log::error!("Failed to step thread: {e}");
+                    }
+                }
+            }
+        });
+    }
+}
+
+impl Widget for &mut ThreadsView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let _span = tracy_client::span!("ui_update_threads");
+
+        let threads = self.dap_interface.session().get_threads();
+        let selected_thread_id = self.dap_interface.session().selected_thread_id();
+
+        ScrollArea::both().auto_shrink(false).show(ui, |ui| {
+            if threads.is_empty() {
+                ui.weak("No threads");
+            }
+
+            for thread in &threads {
+                self.thread_ui(ui, thread, selected_thread_id == Some(thread.id));
+            }
+        });
+
+        ui.response()
+    }
+}
@@ -1,24 +1,257 @@
+use crate::dap::dap_interface::{DapInterface, DataBreakpointTarget};
+use crate::dap::message_types::Variable;
+use crate::dap::requests::RequestHandle;
+use crate::data::variables::{ScopesState, VariablesState};
+use crate::widget::DataBreakpointMenu;
+use egui::{CollapsingHeader, Id, Key, Response, ScrollArea, TextEdit, TextStyle, Ui, Widget};
+use epaint::text::{LayoutJob, TextFormat};
 use std::sync::Arc;
-use egui::{Response, Ui, Widget};
-use crate::dap::dap_interface::DapInterface;
+
+/// Where changing the value of a variable is at.
+enum VariableEdit {
+    /// The new value being typed
+    Editing(String),
+    Pending(RequestHandle<()>),
+    Failed(String),
+}
 
 pub struct VarView {
     dap_interface: Arc<DapInterface>,
+    /// The context menu to watch a variable
+    watch_menu: DataBreakpointMenu,
+    /// Whether a variable had its context menu open this frame
+    watch_menu_open: bool,
+    /// The variable whose value is being changed, by its id
+    edit: Option<(Id, VariableEdit)>,
+    /// Whether the value being typed was just shown, and should take the focus
+    focus_edit: bool,
 }
 
 impl VarView {
     pub fn new(dap_interface: Arc<DapInterface>) -> Self {
         Self {
             dap_interface,
+            watch_menu: DataBreakpointMenu::new(),
+            watch_menu_open: false,
+            edit: None,
+            focus_edit: false,
+        }
+    }
+
+    /// Shows the children of `variables_reference`, fetching them if they were never requested.
+    /// [Self::end_frame] has to be called once every tree of the frame is shown.
+    pub fn variables_ui(&mut self, ui: &mut Ui, variables_reference: u64, parent_id: Id) {
+        let session = self.dap_interface.session();
+        match session.get_variables(variables_reference) {
+            Some(VariablesState::Loaded(variables)) => {
+                if variables.is_empty() {
+                    ui.weak("No variables");
+                }
+                for variable in &variables {
+                    self.variable_ui(ui, variable, variables_reference, parent_id);
+                }
+            }
+            Some(VariablesState::Failed(message)) => {
+                ui.colored_label(ui.style().visuals.error_fg_color, message);
+            }
+            Some(VariablesState::Pending) => {
+                ui.spinner();
+                ui.ctx().request_repaint();
+            }
+            None => {
+                if let Err(e) = session.request_variables(variables_reference) {
+                    log::error!("Failed to request variables: {e}");
+                }
+                ui.spinner();
+                ui.ctx().request_repaint();
+            }
         }
     }
+
+    fn variable_ui(
+        &mut self,
+        ui: &mut Ui,
+        variable: &Variable,
+        variables_reference: u64,
+        parent_id: Id,
+    ) {
+        // Ids are made of the variable names from the scope, so whatever was expanded stays
+        // expanded when stepping
+        let id = parent_id.with(&variable.name);
+        let label = variable_label(ui, variable);
+
+        if let Some((edit_id, _)) = &self.edit
+            && *edit_id == id
+            && self.edit_ui(ui, variable, variables_reference)
+        {
+            return;
+        }
+
+        let response = if variable.variables_reference > 0 {
+            CollapsingHeader::new(label)
+                .id_salt(id)
+                .show(ui, |ui| {
+                    self.variables_ui(ui, variable.variables_reference, id);
+                })
+                .header_response
+        } else {
+            ui.label(label)
+        };
+
+        if self.dap_interface.session().supports_data_breakpoints() {
+            let target = DataBreakpointTarget::Variable {
+                variables_reference,
+                name: variable.name.clone(),
+            };
+            let menu = response.context_menu(|ui| {
+                self.watch_menu.ui(ui, &self.dap_interface, &target);
+            });
+            self.watch_menu_open |= menu.is_some();
+        }
+
+        if response.double_clicked() && self.dap_interface.session().can_set_variable(variable) {
+            self.edit = Some((id, VariableEdit::Editing(variable.value.clone())));
+            self.focus_edit = true;
+        }
+
+        if let Some(var_type) = &variable.var_type {
+            response.on_hover_text(var_type);
+        }
+    }
+
+    /// Shows the variable being changed. Returns false once there's nothing left to show, and
+    /// the variable should be shown as usual.
+    fn edit_ui(&mut self, ui: &mut Ui, variable: &Variable, variables_reference: u64) -> bool {
+        let Some((_, edit)) = &mut self.edit else {
+            return false;
+        };
+
+        if let VariableEdit::Pending(handle) = edit
+            && let Some(result) = handle.poll()
+        {
+            match result {
+                // The new value is in the variable already
+                Ok(()) => {
+                    self.edit = None;
+                    return false;
+                }
+                Err(e) => *edit = VariableEdit::Failed(e.to_string()),
+            }
+        }
+
+        let mut done = false;
+        ui.horizontal(|ui| {
+            ui.monospace(format!("{} =", variable.name));
+            match edit {
+                VariableEdit::Editing(value) => {
+                    let input_res = ui.add(
+                        TextEdit::singleline(value)
+                            .font(TextStyle::Monospace)
+                            .desired_width(ui.available_width()),
+                    );
+                    if std::mem::take(&mut self.focus_edit) {
+                        input_res.request_focus();
+                    }
+                    if input_res.lost_focus() {
+                        // Anything but Enter leaves the variable as it was
+                        *edit = if ui.input(|i| i.key_pressed(Key::Enter)) {
+                            match self.dap_interface.session().set_variable(
+                                variables_reference,
+                                variable,
+                                value.as_str(),
+                            ) {
+                                Ok(handle) => VariableEdit::Pending(handle),
+                                Err(e) => VariableEdit::Failed(e.to_string()),
+                            }
+                        } else {
+                            done = true;
+                            return;
+                        };
+                    }
+                }
+                VariableEdit::Pending(_) => {
+                    ui.monospace(&variable.value);
+                    ui.spinner();
+                    ui.ctx().request_repaint();
+                }
+                VariableEdit::Failed(message) => {
+                    ui.monospace(&variable.value);
+                    ui.colored_label(ui.style().visuals.error_fg_color, message.as_str());
+                    done = ui.small_button("x").on_hover_text("Dismiss").clicked();
+                }
+            }
+        });
+
+        if done {
+            self.edit = None;
+        }
+        true
+    }
+
+    /// Forgets the context menu if no variable had it open this frame.
+    pub fn end_frame(&mut self) {
+        if !self.watch_menu_open {
+            self.watch_menu.close();
+        }
+        self.watch_menu_open = false;
+    }
 }
 impl Widget for &mut VarView {
     fn ui(self, ui: &mut Ui) -> Response {
-        ui.vertical(|ui| {
+        let _span = tracy_client::span!("ui_update_var_view");
 
+        ScrollArea::both().auto_shrink(false).show(ui, |ui| {
+            match self.dap_interface.session().get_scopes() {
+                ScopesState::None => {
+                    ui.weak("The program is not stopped");
+                }
+                ScopesState::Pending { .. } => {
+                    ui.spinner();
+                    ui.ctx().request_repaint();
+                }
+                ScopesState::Failed(message) => {
+                    ui.colored_label(ui.style().visuals.error_fg_color, message);
+                }
+                ScopesState::Loaded { scopes, .. } => {
+                    for scope in &scopes {
+                        let id = Id::new("var-view-scope").with(&scope.name);
+                        CollapsingHeader::new(&scope.name)
+                            .id_salt(id)
+                            .default_open(!scope.expensive)
+                            .show(ui, |ui| {
+                                self.variables_ui(ui, scope.variables_reference, id);
+                            });
+                    }
+                }
+            }
         });
 
+        self.end_frame();
+
         ui.response()
     }
-}
\ No newline at end of file
+}
+
+fn variable_label(ui: &Ui, variable: &Variable) -> LayoutJob {
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let visuals = &ui.style().visuals;
+
+    let mut job = LayoutJob::default();
+    job.append(
+        &variable.name,
+        0.0,
+        TextFormat::simple(font_id.clone(), visuals.strong_text_color()),
+    );
+    job.append(
+        " = ",
+        0.0,
+        TextFormat::simple(font_id.clone(), visuals.weak_text_color()),
+    );
+    job.append(
+        &variable.value,
+        0.0,
+        TextFormat::simple(font_id, visuals.text_color()),
+    );
+
+    job
+}
@@ -0,0 +1,225 @@
+use crate::dap::dap_interface::DapInterface;
+use crate::dap::message::EvaluateResponseBody;
+use crate::dap::message_types::EvaluateContext;
+use crate::dap::requests::RequestHandle;
+use crate::data::watch::{load_watch_expressions, save_watch_expressions};
+use crate::widget::VarView;
+use egui::{CollapsingHeader, Id, Key, Response, RichText, ScrollArea, TextEdit, Ui, Widget};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+enum WatchValue {
+    /// Not evaluated, because the program is not stopped
+    None,
+    Pending(RequestHandle<EvaluateResponseBody>),
+    Evaluated(EvaluateResponseBody),
+    Failed(String),
+}
+
+struct WatchExpression {
+    expression: String,
+    value: WatchValue,
+}
+
+/// Expressions evaluated every time the program stops, in the selected frame. They are kept in
+/// the workspace, so they are still there the next time.
+pub struct WatchView {
+    dap_interface: Arc<DapInterface>,
+    workspace_folder: PathBuf,
+    expressions: Vec<WatchExpression>,
+    new_expression: String,
+    /// The session, stop and frame the values were evaluated at
+    evaluated_at: Option<(u64, u64, Option<u64>)>,
+    /// Why the expressions could not be loaded or saved
+    file_error: Option<String>,
+    /// Shows the children of structured values
+    children: VarView,
+}
+
+impl WatchView {
+    pub fn new(dap_interface: Arc<DapInterface>, workspace_folder: PathBuf) -> Self {
+        let (expressions, file_error) = match load_watch_expressions(&workspace_folder) {
+            Ok(expressions) => (expressions, None),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+
+        Self {
+            children: VarView::new(Arc::clone(&dap_interface)),
+            dap_interface,
+            workspace_folder,
+            expressions: expressions
+                .into_iter()
+                .map(|expression| WatchExpression {
+                    expression,
+                    value: WatchValue::None,
+                })
+                .collect(),
+            new_expression: String::new(),
+            evaluated_at: None,
+            file_error,
+        }
+    }
+
+    fn save(&mut self) {
+        let expressions: Vec<String> = self
+            .expressions
+            .iter()
+            .map(|watch| watch.expression.clone())
+            .collect();
+        self.file_error = save_watch_expressions(&self.workspace_folder, &expressions).err();
+    }
+
+    fn evaluate(dap_interface: &DapInterface, expression: &str) -> WatchValue {
+        match dap_interface
+            .session()
+            .request_evaluate(expression, EvaluateContext::Watch)
+        {
+            Ok(handle) => WatchValue::Pending(handle),
+            Err(e) => WatchValue::Failed(e.to_string()),
+        }
+    }
+
+    /// Evaluates every expression again if the program stopped, or another frame or session was
+    /// selected since they were last evaluated.
+    fn update_values(&mut self) {
+        let session = self.dap_interface.session();
+        let stopped = session.get_debug_state().is_stopped();
+        let at = stopped.then(|| {
+            (
+                session.id(),
+                session.stop_generation(),
+                session.selected_frame_id(),
+            )
+        });
+        if at == self.evaluated_at {
+            return;
+        }
+        self.evaluated_at = at;
+
+        for watch in &mut self.expressions {
+            watch.value = if stopped {
+                WatchView::evaluate(&self.dap_interface, &watch.expression)
+            } else {
+                WatchValue::None
+            };
+        }
+    }
+
+    fn poll_values(&mut self) {
+        for watch in &mut self.expressions {
+            if let WatchValue::Pending(handle) = &watch.value
+                && let Some(result) = handle.poll()
+            {
+                watch.value = match result {
+                    Ok(body) => WatchValue::Evaluated(body),
+                    Err(e) => WatchValue::Failed(e.to_string()),
+                };
+            }
+        }
+    }
+
+    /// Shows an expression along with its value. Returns whether it should be removed.
+    fn expression_ui(
+        children: &mut VarView,
+        ui: &mut Ui,
+        watch: &WatchExpression,
+        index: usize,
+    ) -> bool {
+        let mut remove = false;
+        let id = Id::new("watch-expression")
+            .with(index)
+            .with(&watch.expression);
+
+        ui.horizontal(|ui| {
+            remove = ui.small_button("x").on_hover_text("Remove").clicked();
+
+            let expression = RichText::new(&watch.expression).monospace().strong();
+            match &watch.value {
+                WatchValue::None => {
+                    ui.label(expression);
+                    ui.weak("not available");
+                }
+                WatchValue::Pending(_) => {
+                    ui.label(expression);
+                    ui.spinner();
+                    ui.ctx().request_repaint();
+                }
+                WatchValue::Failed(message) => {
+                    ui.label(expression);
+                    ui.colored_label(ui.style().visuals.error_fg_color, message);
+                }
+                WatchValue::Evaluated(body) => {
+                    let value = RichText::new(format!("{} = {}", watch.expression, body.result))
+                        .monospace();
+                    let response = if body.variables_reference > 0 {
+                        CollapsingHeader::new(value)
+                            .id_salt(id)
+                            .show(ui, |ui| {
+                                children.variables_ui(ui, body.variables_reference, id);
+                            })
+                            .header_response
+                    } else {
+                        ui.label(value)
+                    };
+                    if let Some(result_type) = &body.result_type {
+                        response.on_hover_text(result_type);
+                    }
+                }
+            }
+        });
+
+        remove
+    }
+}
+
+impl Widget for &mut WatchView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let _span = tracy_client::span!("ui_update_watch");
+
+        self.update_values();
+        self.poll_values();
+
+        ui.horizontal(|ui| {
+            let input_res = ui.add(
+                TextEdit::singleline(&mut self.new_expression).hint_text("Expression to watch"),
+            );
+            let submitted = input_res.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter));
+            let expression = self.new_expression.trim().to_owned();
+            if (ui.button("Add").clicked() || submitted) && !expression.is_empty() {
+                let value = if self.evaluated_at.is_some() {
+                    WatchView::evaluate(&self.dap_interface, &expression)
+                } else {
+                    WatchValue::None
+                };
+                self.expressions.push(WatchExpression { expression, value });
+                self.new_expression.clear();
+                self.save();
+            }
+        });
+        if let Some(error) = &self.file_error {
+            ui.colored_label(ui.style().visuals.warn_fg_color, error);
+        }
+        ui.separator();
+
+        let mut removed = None;
+        ScrollArea::both().auto_shrink(false).show(ui, |ui| {
+            if self.expressions.is_empty() {
+                ui.weak("No watch expressions");
+            }
+
+            for (i, watch) in self.expressions.iter().enumerate() {
+                if WatchView::expression_ui(&mut self.children, ui, watch, i) {
+                    removed = Some(i);
+                }
+            }
+        });
+        self.children.end_frame();
+
+        if let Some(i) = removed {
+            self.expressions.remove(i);
+            self.save();
+        }
+
+        ui.response()
+    }
+}